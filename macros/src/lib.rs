@@ -0,0 +1,511 @@
+use proc_macro::{TokenStream, TokenTree, Delimiter};
+use quote::{quote, format_ident};
+use num_traits::ToPrimitive;
+
+use asdis_primitives::{Item, TextInstruction, TextInstructionPart, BinaryInstruction, Instruction , Num, CompactType, RV32Type, Long48Type, Long64Type, OperandKind, Constraint, Class, parse_bitspec, parse_value, validate, validate_text, validate_constraints, bits_len};
+use std::convert::From;
+
+///value of predicate, decimal or 0x prefixed hexadecimal
+fn value(s : &str) -> u64 {
+    parse_value(s).unwrap_or_else(|e| panic!("Not a value of predicate {}", e))
+}
+
+fn parse_token_string<T:Num>(ts : TokenStream) -> Instruction<T> {
+
+    enum State {
+        Empty,
+        Ident(String),
+        ///first character of ==, != or >= is read
+        Operator(String, char),
+        ///operator is read, value is expected, operator is given by its first character
+        Value(String, char),
+    }
+
+    let mut current = State::Empty;
+    let mut r = Vec::<Item<T>>::new();
+    let mut constraints = Vec::<Constraint>::new();
+    //`hint if` or `reserved if` is read, the following predicate marks encodings instead of restricting them
+    let mut mark = None;
+    let mut marks = Vec::<(Constraint, Class)>::new();
+
+    let mut iter = ts.into_iter();
+
+    let text = match iter.next() {
+        None => panic!("Empty token stream!"),
+        Some( tt ) => match tt {
+            TokenTree::Literal(g) => g.to_string() ,
+            _ => panic!("First argument must be a command description"),
+        }
+    };
+    assert!(text.len() > 2, "Quotes for command description not found");
+    let text = match TextInstruction::parse(&text[1..text.len() - 1]) { //without quotes
+        Ok( text ) => text,
+        Err( e ) => panic!("{}", e),
+    };
+
+    for tt in iter {
+        current = match (current, tt) {
+            (State::Ident( idnt ), TokenTree::Group(g)) => {
+                assert!(g.delimiter() == Delimiter::Bracket, "Only [] delimeters allowed for bitspecs");
+                assert!(mark.is_none(), "Predicate expected after if");
+                let bs = parse_bitspec( &g.stream().to_string() ).unwrap_or_else(|e| panic!("{}", e));
+                r.push( Item::Ident { name : idnt, bitspec : bs } );
+                State::Empty
+            },
+            (State::Value( idnt, op ), TokenTree::Group(g)) => {
+                assert!(g.delimiter() == Delimiter::Brace, "Only braces allowed for set of values");
+                let values : Vec<u64> = g.stream().into_iter().filter_map(|tt| match tt {
+                    TokenTree::Literal( l ) => Some( value(&l.to_string()) ),
+                    TokenTree::Punct( p ) if p.as_char() == ',' => None,
+                    _ => panic!("Only values allowed in set"),
+                }).collect();
+                match (op, mark.take()) {
+                    ('!', None) => constraints.extend( values.iter().map(|v| Constraint::ne(&idnt, *v)) ),
+                    ('=', None) => constraints.push( Constraint::is_in(&idnt, &values) ),
+                    ('=', Some( class )) => marks.push( (Constraint::is_in(&idnt, &values), class) ),
+                    ('!', Some( _ )) => panic!("Only == is allowed with set of values in hint or reserved predicate"),
+                    _ => panic!("Only == or != are allowed with set of values"),
+                }
+                State::Empty
+            },
+            (_, TokenTree::Group(_)) => panic!("Missplaced bispecs"),
+            (State::Ident( kw ), TokenTree::Ident(g)) if g.to_string() == "if" && mark.is_none() => {
+                mark = match &kw[..] {
+                    "hint" => Some( Class::Hint ),
+                    "reserved" => Some( Class::Reserved ),
+                    _ => panic!("Only hint or reserved could be followed by if"),
+                };
+                State::Empty
+            },
+            (State::Value( idnt, '=' ), TokenTree::Ident(g)) => {
+                let c = Constraint::same(&idnt, &g.to_string());
+                match mark.take() {
+                    Some( class ) => marks.push( (c, class) ),
+                    None => constraints.push( c ),
+                }
+                State::Empty
+            },
+            (State::Empty, TokenTree::Ident(g)) => State::Ident( g.to_string() ),
+            (_, TokenTree::Ident(_)) => panic!("misplaced Ident"),
+            (State::Ident( idnt ), TokenTree::Punct(g)) if ['=', '!', '>'].contains(&g.as_char()) => State::Operator( idnt, g.as_char() ),
+            (State::Operator( idnt, c ), TokenTree::Punct(g)) if g.as_char() == '=' => State::Value( idnt, c ),
+            (State::Empty, TokenTree::Punct(g)) if g.as_char() == ',' => State::Empty,
+            (_, TokenTree::Punct(_)) => panic!("Only ',' allowed as separator of items, and ==, != or >= in predicates"),
+            (State::Value( idnt, op ), TokenTree::Literal(g)) => {
+                let v = value(&g.to_string());
+                let c = match op {
+                    '!' => Constraint::ne(&idnt, v),
+                    '>' => Constraint::ge(&idnt, v),
+                    _ => Constraint::eq(&idnt, v),
+                };
+                match mark.take() {
+                    Some( class ) => marks.push( (c, class) ),
+                    None => constraints.push( c ),
+                }
+                State::Empty
+            },
+            (State::Empty, TokenTree::Literal(g)) => {
+                let str = &g.to_string();
+                match T::from_str_radix(str, 2) {
+                    Err( err ) => panic!("Not a binary string : {}", err),
+                    Ok( val ) => r.push( Item::Bits::<T> { len : str.len(), val  } ),
+                }
+                State::Empty
+            },
+            (_, TokenTree::Literal(_)) => panic!("misplaced Literal"),
+        };
+    }
+    if !matches!(current, State::Empty) || mark.is_some() {
+        panic!("Incomplete item at the end");
+    }
+
+    let marked : Vec<Constraint> = marks.iter().map(|(c, _)| c.clone()).collect();
+    if let Err( e ) = validate(&r).and_then(|_| validate_text(&text, &r)).and_then(|_| validate_constraints(&constraints, &r))
+                                  .and_then(|_| validate_constraints(&marked, &r)) {
+        panic!("{}", e);
+    }
+
+    let bin = BinaryInstruction { list : r };
+
+    let i = constraints.into_iter().fold(Instruction::<T>::new(bin, text), |i, c| i.when(c));
+    marks.into_iter().fold(i, |i, (c, class)| match class {
+        Class::Reserved => i.reserved_when(c),
+        _ => i.hint_when(c),
+    })
+}
+
+#[proc_macro]
+pub fn instruction16(items: TokenStream) -> TokenStream {
+    let r  = parse_token_string::<CompactType>(items);
+    TokenStream::from( quote! { #r } )
+}
+
+#[proc_macro]
+pub fn instruction32(items: TokenStream) -> TokenStream {
+    let r  = parse_token_string::<RV32Type>(items);
+    TokenStream::from( quote! { #r } )
+}
+
+#[proc_macro]
+pub fn instruction48(items: TokenStream) -> TokenStream {
+    let r  = parse_token_string::<Long48Type>(items);
+    TokenStream::from( quote! { #r } )
+}
+
+#[proc_macro]
+pub fn instruction64(items: TokenStream) -> TokenStream {
+    let r  = parse_token_string::<Long64Type>(items);
+    TokenStream::from( quote! { #r } )
+}
+
+///register operands of typed instruction enums, if operand kind is not given in text, other idents are integers
+const REGISTERS : [&str; 4] = ["rd", "rs1", "rs2", "rs3"];
+const FREGISTERS : [&str; 4] = ["frd", "frs1", "frs2", "frs3"];
+///compressed register operands, which encode x8..x15 or f8..f15
+const CREGISTERS : [&str; 3] = ["rdp", "rs1p", "rs2p"];
+const CFREGISTERS : [&str; 2] = ["frdp", "frs2p"];
+
+///ident of instruction as field of typed enum variant.
+///Runs are tuples of (lowest bit in word, lowest bit in value, count of bits)
+struct Field {
+    name : String,
+    kind : Option<OperandKind>,
+    runs : Vec<(u32, u32, u32)>,
+    width : u32,
+}
+
+impl Field {
+    ///register type and number of first encoded register, None if field is not a register
+    fn register(&self) -> Option<(proc_macro2::TokenStream, u8)> {
+        match self.kind {
+            Some( OperandKind::XReg ) => Some( (quote! { ::asdis::primitives::Reg }, 0) ),
+            Some( OperandKind::FReg ) => Some( (quote! { ::asdis::primitives::FReg }, 0) ),
+            Some( OperandKind::CReg ) => Some( (quote! { ::asdis::primitives::Reg }, 8) ),
+            Some( OperandKind::CFReg ) => Some( (quote! { ::asdis::primitives::FReg }, 8) ),
+            Some( _ ) => None,
+            None if REGISTERS.contains(&&self.name[..]) => Some( (quote! { ::asdis::primitives::Reg }, 0) ),
+            None if FREGISTERS.contains(&&self.name[..]) => Some( (quote! { ::asdis::primitives::FReg }, 0) ),
+            None if CREGISTERS.contains(&&self.name[..]) => Some( (quote! { ::asdis::primitives::Reg }, 8) ),
+            None if CFREGISTERS.contains(&&self.name[..]) => Some( (quote! { ::asdis::primitives::FReg }, 8) ),
+            None => None,
+        }
+    }
+
+    ///without kind imm is signed, other idents are unsigned
+    fn signed(&self) -> bool {
+        match self.kind {
+            Some( kind ) => matches!(kind, OperandKind::Simm( _ ) | OperandKind::PcRel),
+            None => self.name == "imm",
+        }
+    }
+
+    fn ty(&self) -> proc_macro2::TokenStream {
+        if let Some( (ty, _) ) = self.register() {
+            return ty;
+        }
+        let sign = if self.signed() { "i" } else { "u" };
+        let bits = match self.width { 0..=8 => 8, 9..=16 => 16, 17..=32 => 32, _ => 64 };
+        let ty = format_ident!("{}", format!("{}{}", sign, bits));
+        quote! { #ty }
+    }
+
+    ///expression which extracts raw bits of field from word `w` as u64
+    fn raw(&self) -> proc_macro2::TokenStream {
+        let runs = self.runs.iter().map(|(wlo, vlo, len)| {
+            let mask = proc_macro2::Literal::u64_unsuffixed((1u64 << len) - 1);
+            quote! { (((w as u64) >> #wlo) & #mask) << #vlo }
+        });
+        quote! { ( 0u64 #( | #runs )* ) }
+    }
+
+    ///expression which extracts value of field from word `w`
+    fn decode(&self) -> proc_macro2::TokenStream {
+        let raw = self.raw();
+        let ty = self.ty();
+        if let Some( (_, first) ) = self.register() {
+            quote! { #ty(#raw as u8 + #first) }
+        } else if self.signed() {
+            let shift = 64 - self.width;
+            quote! { (((#raw << #shift) as i64) >> #shift) as #ty }
+        } else {
+            quote! { #raw as #ty }
+        }
+    }
+
+    ///expression which gives value of field, bound by its name, as u64
+    fn value(&self) -> proc_macro2::TokenStream {
+        let name = format_ident!("{}", self.name);
+        if let Some( (_, first) ) = self.register() {
+            quote! { (#name.0.wrapping_sub(#first) as u64) }
+        } else if self.signed() {
+            quote! { (*#name as i64 as u64) }
+        } else {
+            quote! { (*#name as u64) }
+        }
+    }
+
+    ///expression which is true if value of field, bound by its name, is kept by bits of word
+    fn fits(&self) -> proc_macro2::TokenStream {
+        let value = self.value();
+        let cover = proc_macro2::Literal::u64_unsuffixed(self.runs.iter().fold(0u64, |m, (_, vlo, len)| m | (((1u64 << len) - 1) << vlo)));
+        if self.signed() && self.register().is_none() {
+            let shift = 64 - self.width;
+            quote! { (((#value & #cover) << #shift) as i64 >> #shift) as u64 == #value }
+        } else {
+            quote! { #value & #cover == #value }
+        }
+    }
+
+    ///expression which puts value of field, bound by its name, to bits of word
+    fn encode(&self, word : &proc_macro2::Ident) -> proc_macro2::TokenStream {
+        let value = self.value();
+        let runs = self.runs.iter().map(|(wlo, vlo, len)| {
+            let mask = proc_macro2::Literal::u64_unsuffixed((1u64 << len) - 1);
+            quote! { (((#value >> #vlo) & #mask) << #wlo) as #word }
+        });
+        quote! { #( | #runs )* }
+    }
+}
+
+///fields of instruction, in order of operands in text, then other idents
+fn fields<T:Num>(i : &Instruction<T>) -> Vec<Field> {
+    let mut names = Vec::<String>::new();
+    for part in &i.text.list {
+        if let TextInstructionPart::TextIdent(_, name) | TextInstructionPart::TypedIdent(_, name, _) = part {
+            if !names.contains(name) {
+                names.push( name.clone() );
+            }
+        }
+    }
+    for item in &i.bin.list {
+        if let Item::Ident { name, .. } = item {
+            if !names.contains(name) {
+                names.push( name.clone() );
+            }
+        }
+    }
+
+    names.iter().map(|name| {
+        //pairs of (bit of word, bit of value)
+        let mut bits = Vec::<(u32, u32)>::new();
+        let mut pos = bits_len(&i.bin.list);
+        for item in &i.bin.list {
+            match item {
+                Item::Bits { len, .. } => pos -= *len as u32,
+                Item::Ident { name : n, bitspec } => {
+                    for b in bitspec {
+                        pos -= 1;
+                        if n == name {
+                            bits.push( (pos, *b) );
+                        }
+                    }
+                },
+            }
+        }
+        let mut runs = Vec::<(u32, u32, u32)>::new();
+        for (w, v) in bits {
+            match runs.last_mut() {
+                Some( (wlo, vlo, len) ) if *wlo == w + 1 && *vlo == v + 1 => { *wlo = w; *vlo = v; *len += 1; },
+                _ => runs.push( (w, v, 1) ),
+            }
+        }
+        let width = runs.iter().map(|(_, vlo, len)| vlo + len).max().unwrap_or(0);
+        Field { name : name.clone(), kind : i.text.kind(name), runs, width }
+    }).collect()
+}
+
+///expression which checks constraint on bits of word `w`
+fn check(c : &Constraint, f : &[Field]) -> proc_macro2::TokenStream {
+    let raw = f.iter().find(|f| f.name == c.name()).expect("Constrained ident is validated").raw();
+    match c {
+        Constraint::Eq( _, v ) => quote! { #raw == #v },
+        Constraint::Ne( _, v ) => quote! { #raw != #v },
+        Constraint::In( _, v ) => quote! { [ #( #v ),* ].contains(&#raw) },
+        Constraint::Ge( _, v ) => quote! { #raw >= #v },
+        Constraint::Same( _, other ) => {
+            let other = f.iter().find(|f| f.name == *other).expect("Constrained ident is validated").raw();
+            quote! { #raw == #other }
+        },
+    }
+}
+
+///expressions which check predicates of instruction on bits of word `w`, reserved encodings are excluded
+fn checks<T:Num>(i : &Instruction<T>, f : &[Field]) -> Vec<proc_macro2::TokenStream> {
+    let mut r : Vec<proc_macro2::TokenStream> = i.constraints.iter().map(|c| {
+        let check = check(c, f);
+        quote! { && #check }
+    }).collect();
+    //the first satisfied mark gives class, so hints before reserved mark take precedence
+    let mut hints = Vec::new();
+    for (c, class) in &i.marks {
+        let check = check(c, f);
+        match class {
+            Class::Reserved => r.push( quote! { && !( #check #( && !#hints )* ) } ),
+            _ => hints.push( quote! { (#check) } ),
+        }
+    }
+    r
+}
+
+///variants of typed enum: name, instruction and tokens of chained calls like `.sem(...)`
+fn parse_variants<T:Num>(ts : TokenStream) -> Vec<(proc_macro2::Ident, Instruction<T>, proc_macro2::TokenStream)> {
+    let mut variants = Vec::new();
+    //name of variant before its row, None after row until comma
+    let mut current = None;
+    let mut chain = false;
+    for tt in ts {
+        match tt {
+            TokenTree::Punct( p ) if p.as_char() == ',' => chain = false,
+            TokenTree::Ident( i ) if !chain && current.is_none() => current = Some( format_ident!("{}", i.to_string()) ),
+            TokenTree::Punct( p ) if p.as_char() == '=' && current.is_some() => (),
+            TokenTree::Group( g ) if g.delimiter() == Delimiter::Parenthesis && current.is_some() => {
+                variants.push( (current.take().unwrap(), parse_token_string::<T>(g.stream()), proc_macro2::TokenStream::new()) );
+                chain = true;
+            },
+            tt => match variants.last_mut() {
+                Some( (_, _, c) ) if chain => c.extend( proc_macro2::TokenStream::from( TokenStream::from(tt) ) ),
+                _ => panic!("Variants must be given as Name = (\"text\", items...)"),
+            },
+        }
+    }
+    variants
+}
+
+///enum of typed instructions with encode and decode functions, input is like
+///`pub enum Name { Variant = ("text", items...), ... }`. Variants may be grouped like
+///`group { Variant = ("text", items...).sem(...), ... }`, then function `group()` returns
+///instructions of the group for tables, with chained calls applied
+fn parse_enum<T:Num>(ts : TokenStream, word : &str, ty : &str) -> TokenStream {
+    let mut vis = proc_macro2::TokenStream::new();
+    let mut iter = ts.into_iter();
+    let name = loop {
+        match iter.next() {
+            Some( TokenTree::Ident( i ) ) if i.to_string() == "enum" => break match iter.next() {
+                Some( TokenTree::Ident( n ) ) => format_ident!("{}", n.to_string()),
+                _ => panic!("Name of enum expected"),
+            },
+            Some( tt ) => vis.extend( proc_macro2::TokenStream::from( TokenStream::from(tt) ) ),
+            None => panic!("enum keyword expected"),
+        }
+    };
+    let body = match iter.next() {
+        Some( TokenTree::Group( g ) ) if g.delimiter() == Delimiter::Brace => g.stream(),
+        _ => panic!("Braces with variants expected"),
+    };
+
+    //groups are split off, other tokens are variants without group
+    let mut variants = Vec::new();
+    //attributes like doc comments go to function of group
+    let mut groups = Vec::<(proc_macro2::Ident, std::ops::Range<usize>, proc_macro2::TokenStream)>::new();
+    let mut attrs = proc_macro2::TokenStream::new();
+    let mut rest = Vec::<TokenTree>::new();
+    let mut body = body.into_iter().peekable();
+    while let Some( tt ) = body.next() {
+        match (tt, body.peek()) {
+            (TokenTree::Punct( p ), Some( TokenTree::Group( g ) )) if p.as_char() == '#' && g.delimiter() == Delimiter::Bracket => {
+                attrs.extend( proc_macro2::TokenStream::from( vec![TokenTree::Punct( p ), body.next().unwrap()].into_iter().collect::<TokenStream>() ) );
+            },
+            (TokenTree::Ident( i ), Some( TokenTree::Group( g ) )) if g.delimiter() == Delimiter::Brace => {
+                let start = variants.len();
+                if let Some( TokenTree::Group( g ) ) = body.next() {
+                    variants.extend( parse_variants::<T>(g.stream()) );
+                }
+                groups.push( (format_ident!("{}", i.to_string()), start..variants.len(), std::mem::take(&mut attrs)) );
+            },
+            (tt, _) => rest.push( tt ),
+        }
+    }
+    variants.extend( parse_variants::<T>(rest.into_iter().collect()) );
+
+    let word = format_ident!("{}", word);
+    let fields : Vec<Vec<Field>> = variants.iter().map(|(_, i, _)| fields(i)).collect();
+
+    let decl = variants.iter().zip(&fields).map(|((v, _, _), f)| {
+        if f.is_empty() {
+            return quote! { #v };
+        }
+        let names = f.iter().map(|f| format_ident!("{}", f.name));
+        let types = f.iter().map(|f| f.ty());
+        quote! { #v { #( #names : #types ),* } }
+    });
+
+    let encode = variants.iter().zip(&fields).map(|((v, i, _), f)| {
+        let pattern = proc_macro2::Literal::u64_unsuffixed(i.pattern().to_u64().unwrap());
+        let names = f.iter().map(|f| format_ident!("{}", f.name));
+        let puts = f.iter().map(|f| f.encode(&word));
+        let fits = f.iter().map(|f| f.fits());
+        let checks = checks(i, f);
+        match f.is_empty() {
+            true => quote! { #name::#v => Some( #pattern ) },
+            false => quote! { #name::#v { #( #names ),* } => {
+                let w = #pattern #( #puts )*;
+                match true #( && #fits )* #( #checks )* {
+                    true => Some( w ),
+                    false => None,
+                }
+            } },
+        }
+    });
+
+    //the most specific encodings are checked first
+    let mut order : Vec<usize> = (0..variants.len()).collect();
+    order.sort_by_key(|n| std::cmp::Reverse( variants[*n].1.specificity() ));
+    let decode = order.iter().map(|n| {
+        let (v, i, _) = &variants[*n];
+        let mask = proc_macro2::Literal::u64_unsuffixed(i.mask().to_u64().unwrap());
+        let pattern = proc_macro2::Literal::u64_unsuffixed(i.pattern().to_u64().unwrap());
+        let f = &fields[*n];
+        let names = f.iter().map(|f| format_ident!("{}", f.name));
+        let values = f.iter().map(|f| f.decode());
+        let checks = checks(i, f);
+        match f.is_empty() {
+            true => quote! { if w & #mask == #pattern { return Some( #name::#v ); } },
+            false => quote! { if w & #mask == #pattern #( #checks )* { return Some( #name::#v { #( #names : #values ),* } ); } },
+        }
+    });
+
+    let ty = format_ident!("{}", ty);
+    let groups = groups.iter().map(|(g, range, attrs)| {
+        let rows = variants[range.clone()].iter().map(|(_, i, chain)| quote! { #i #chain });
+        quote! {
+            #attrs
+            pub fn #g() -> Vec<::asdis::primitives::Instruction<::asdis::primitives::#ty>> {
+                vec! [ #( #rows ),* ]
+            }
+        }
+    });
+
+    TokenStream::from( quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis enum #name {
+            #( #decl ),*
+        }
+
+        impl #name {
+            ///binary encoding, None if value of field does not fit to its bits or predicate of encoding does not hold
+            pub fn encode(&self) -> Option<#word> {
+                match self {
+                    #( #encode ),*
+                }
+            }
+
+            ///decode instruction, the most specific encoding wins
+            pub fn decode(w : #word) -> Option<#name> {
+                #( #decode )*
+                None
+            }
+
+            #( #groups )*
+        }
+    } )
+}
+
+#[proc_macro]
+pub fn instruction_enum16(items: TokenStream) -> TokenStream {
+    parse_enum::<CompactType>(items, "u16", "CompactType")
+}
+
+#[proc_macro]
+pub fn instruction_enum32(items: TokenStream) -> TokenStream {
+    parse_enum::<RV32Type>(items, "u32", "RV32Type")
+}
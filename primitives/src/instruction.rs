@@ -0,0 +1,806 @@
+use std::fmt;
+use std::convert::From;
+use regex::Regex;
+use lazy_static::lazy_static;
+use quote::{ToTokens, TokenStreamExt};
+use proc_macro2::{TokenStream, TokenTree, Ident, Group, Delimiter, Span, Punct, Literal, Spacing};
+use std::num::ParseIntError;
+use num_traits::int::PrimInt;
+use super::semantics::Sem;
+
+
+pub trait Num {
+    type IType : std::fmt::Binary + ToTokens  + fmt::Debug + PrimInt;
+    type DType : PrimInt + std::fmt::UpperHex;
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self::IType, ParseIntError>;
+    fn i_one() -> Self::IType;
+    fn i_zero() -> Self::IType;
+    fn i_max_bit() -> u32;
+    fn get_bit( v : Self::IType, bit : u32 ) -> Self::DType;
+    fn d_zero() -> Self::DType;
+    fn type_name() -> &'static str;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompactType {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RV32Type {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Long48Type {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Long64Type {}
+
+impl Num for CompactType
+{
+    type IType = u16;
+    type DType = u32;
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self::IType, ParseIntError> {
+        u16::from_str_radix(src, radix)
+    }
+    fn i_one() -> Self::IType { 1 }
+    fn i_zero() -> Self::IType { 0 }
+    fn i_max_bit() -> u32 { 15 }
+    fn d_zero() -> Self::DType { 0 }
+    fn get_bit( v : Self::IType, bit : u32 ) -> Self::DType {
+        ( (v & (2.pow(bit) as u16)) >> bit ).into()
+    }
+    fn type_name() -> &'static str { "CompactType" }
+}
+
+impl Num for RV32Type
+{
+    type IType = u32;
+    type DType = u32;
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self::IType, ParseIntError> {
+        u32::from_str_radix(src, radix)
+    }
+    fn i_one() -> Self::IType { 1 }
+    fn i_zero() -> Self::IType { 0 }
+    fn i_max_bit() -> u32 { 31 }
+    fn d_zero() -> Self::DType { 0 }
+    fn get_bit( v : Self::IType, bit : u32 ) -> Self::DType {
+        (v & 2_u32.pow(bit)) >> bit
+    }
+    fn type_name() -> &'static str { "RV32Type" }
+}
+
+impl Num for Long48Type
+{
+    type IType = u64;
+    type DType = u64;
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self::IType, ParseIntError> {
+        u64::from_str_radix(src, radix)
+    }
+    fn i_one() -> Self::IType { 1 }
+    fn i_zero() -> Self::IType { 0 }
+    fn i_max_bit() -> u32 { 47 }
+    fn d_zero() -> Self::DType { 0 }
+    fn get_bit( v : Self::IType, bit : u32 ) -> Self::DType {
+        (v >> bit) & 1
+    }
+    fn type_name() -> &'static str { "Long48Type" }
+}
+
+impl Num for Long64Type
+{
+    type IType = u64;
+    type DType = u64;
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self::IType, ParseIntError> {
+        u64::from_str_radix(src, radix)
+    }
+    fn i_one() -> Self::IType { 1 }
+    fn i_zero() -> Self::IType { 0 }
+    fn i_max_bit() -> u32 { 63 }
+    fn d_zero() -> Self::DType { 0 }
+    fn get_bit( v : Self::IType, bit : u32 ) -> Self::DType {
+        (v >> bit) & 1
+    }
+    fn type_name() -> &'static str { "Long64Type" }
+}
+
+/// Item represents part of binary encoded instruction, it is either just bits, or ident with bit sepcification
+#[derive(PartialEq, Eq, Debug)]
+pub enum Item<T : Num> {
+    Bits {len : usize, val : T::IType},
+    Ident {name:String, bitspec:Vec<u32>},
+}
+
+impl<T : Num> fmt::Display for Item<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Bits { len, val } => write!(f, "{:0width$b}", val, width = len),
+            Item::Ident {name, bitspec} => write!(f, "{}{:?}", name, bitspec),
+        }
+    }
+}
+
+
+
+///helper function, to append absolute path, so expanded macro does not depend on names imported at call site
+fn app_path(tokens: &mut TokenStream, path : &[&str]) {
+    for name in path {
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Ident( Ident::new(name, Span::call_site())) );
+    }
+}
+
+///helper function, to append given string as String in TokenStream
+fn app_string_from(tokens: &mut TokenStream, s : &str) {
+    app_path(tokens, &["std", "string", "String", "from"]);
+    let mut in_str_group = TokenStream::new();
+    in_str_group.append( TokenTree::Literal( Literal::string(s) ) );
+    tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, in_str_group ) ) );
+}
+
+impl<T:Num> ToTokens for Item<T> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        app_path(tokens, &["asdis", "primitives", "Item"]);
+
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new('<', Spacing::Joint) ) );
+        app_path(tokens, &["asdis", "primitives", T::type_name()]);
+        tokens.append( TokenTree::Punct( Punct::new('>', Spacing::Joint) ) );
+
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        match self {
+            Item::Bits {len, val}  => {
+                tokens.append( TokenTree::Ident( Ident::new("Bits", Span::call_site())) );
+                let mut inside_braces = TokenStream::new();
+                inside_braces.append( TokenTree::Ident( Ident::new("len", Span::call_site())) );
+                inside_braces.append( TokenTree::Punct( Punct::new(':', Spacing::Alone) ) );
+                inside_braces.append( TokenTree::Literal( Literal::usize_suffixed(*len) ) );
+                inside_braces.append( TokenTree::Punct( Punct::new(',', Spacing::Alone) ) );
+                inside_braces.append( TokenTree::Ident( Ident::new("val", Span::call_site())) );
+                inside_braces.append( TokenTree::Punct( Punct::new(':', Spacing::Alone) ) );
+                val.to_tokens( &mut inside_braces );
+                tokens.append( TokenTree::Group( Group::new( Delimiter::Brace, inside_braces ) ) );
+            },
+            Item::Ident {name, bitspec }=> {
+                tokens.append( TokenTree::Ident( Ident::new("Ident", Span::call_site())) );
+                let mut inside_braces = TokenStream::new();
+                inside_braces.append( TokenTree::Ident( Ident::new("name", Span::call_site())) );
+                inside_braces.append( TokenTree::Punct( Punct::new(':', Spacing::Alone) ) );
+                app_string_from( &mut inside_braces, &name[..] );
+                inside_braces.append( TokenTree::Punct( Punct::new(',', Spacing::Alone) ) );
+                inside_braces.append( TokenTree::Ident( Ident::new("bitspec", Span::call_site())) );
+                inside_braces.append( TokenTree::Punct( Punct::new(':', Spacing::Alone) ) );
+                app_path(&mut inside_braces, &["std", "vec"]);
+                inside_braces.append( TokenTree::Punct( Punct::new('!', Spacing::Joint) ) );
+                let mut item_list = TokenStream::new();
+                item_list.append_separated( bitspec.iter(), Punct::new(',', Spacing::Alone));
+                inside_braces.append( TokenTree::Group( Group::new( Delimiter::Bracket, item_list ) ) );
+                tokens.append( TokenTree::Group( Group::new( Delimiter::Brace, inside_braces ) ) );
+            },
+        };
+    }
+}
+
+
+/// Binary Instruction represents description of instruction binary representation, it consists of list of Items
+#[derive(PartialEq, Eq, Debug)]
+pub struct BinaryInstruction<T:Num> {
+    pub list : Vec<Item<T>>,
+}
+
+impl<T:Num> ToTokens for BinaryInstruction<T> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        app_path(tokens, &["asdis", "primitives", "BinaryInstruction"]);
+
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new('<', Spacing::Joint) ) );
+        app_path(tokens, &["asdis", "primitives", T::type_name()]);
+        tokens.append( TokenTree::Punct( Punct::new('>', Spacing::Joint) ) );
+
+        let mut inside_braces = TokenStream::new();
+        inside_braces.append( TokenTree::Ident( Ident::new("list", Span::call_site())) );
+        inside_braces.append( TokenTree::Punct( Punct::new(':', Spacing::Alone) ) );
+        app_path(&mut inside_braces, &["std", "vec"]);
+        inside_braces.append( TokenTree::Punct( Punct::new('!', Spacing::Joint) ) );
+
+        let mut item_list = TokenStream::new();
+        item_list.append_separated( self.list.iter(), Punct::new(',', Spacing::Alone));
+
+        inside_braces.append( TokenTree::Group( Group::new( Delimiter::Bracket, item_list ) ) );
+        tokens.append( TokenTree::Group( Group::new( Delimiter::Brace, inside_braces ) ) );
+    }
+}
+
+
+///kind of operand, given in text template like {imm:simm12}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    XReg,
+    FReg,
+    VReg,
+    ///x8-x15 encoded by 3 bits
+    CReg,
+    ///f8-f15 encoded by 3 bits
+    CFReg,
+    Simm(u32),
+    Uimm(u32),
+    Rm,
+    Csr,
+    ///signed offset from address of instruction, width is given by bitspec
+    PcRel,
+    ///vector mask bit, 0 is shown as v0.t and 1 is not shown
+    VMask,
+}
+
+impl OperandKind {
+    pub fn parse(s : &str) -> Option<OperandKind> {
+        let width = |p : &str| s.strip_prefix(p).and_then(|w| w.parse::<u32>().ok()).filter(|w| *w > 0 && *w <= 64);
+        match s {
+            "xreg" => Some( OperandKind::XReg ),
+            "freg" => Some( OperandKind::FReg ),
+            "vreg" => Some( OperandKind::VReg ),
+            "creg" => Some( OperandKind::CReg ),
+            "cfreg" => Some( OperandKind::CFReg ),
+            "rm" => Some( OperandKind::Rm ),
+            "csr" => Some( OperandKind::Csr ),
+            "pcrel" => Some( OperandKind::PcRel ),
+            "vm" => Some( OperandKind::VMask ),
+            _ => width("simm").map(OperandKind::Simm).or_else(|| width("uimm").map(OperandKind::Uimm)),
+        }
+    }
+
+    ///number of bits of encoded value, None if any width is allowed
+    pub fn width(&self) -> Option<u32> {
+        match self {
+            OperandKind::XReg | OperandKind::FReg | OperandKind::VReg => Some( 5 ),
+            OperandKind::CReg | OperandKind::CFReg | OperandKind::Rm => Some( 3 ),
+            OperandKind::Csr => Some( 12 ),
+            OperandKind::Simm( w ) | OperandKind::Uimm( w ) => Some( *w ),
+            OperandKind::VMask => Some( 1 ),
+            OperandKind::PcRel => None,
+        }
+    }
+}
+
+impl ToTokens for OperandKind {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        app_path(tokens, &["asdis", "primitives", "OperandKind"]);
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        let (name, width) = match self {
+            OperandKind::XReg => ("XReg", None),
+            OperandKind::FReg => ("FReg", None),
+            OperandKind::VReg => ("VReg", None),
+            OperandKind::CReg => ("CReg", None),
+            OperandKind::CFReg => ("CFReg", None),
+            OperandKind::Simm( w ) => ("Simm", Some( *w )),
+            OperandKind::Uimm( w ) => ("Uimm", Some( *w )),
+            OperandKind::Rm => ("Rm", None),
+            OperandKind::Csr => ("Csr", None),
+            OperandKind::PcRel => ("PcRel", None),
+            OperandKind::VMask => ("VMask", None),
+        };
+        tokens.append( TokenTree::Ident( Ident::new(name, Span::call_site())) );
+        if let Some( w ) = width {
+            let mut inside = TokenStream::new();
+            inside.append( TokenTree::Literal( Literal::u32_unsuffixed(w) ) );
+            tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, inside ) ) );
+        }
+    }
+}
+
+///Text instruction part is represent part of textual description of instruction, it either just text, or text followed by some variable name,
+///which could be annotated with kind of operand
+#[derive(PartialEq, Eq, Debug)]
+pub enum TextInstructionPart {
+    Text(String),
+    TextIdent(String, String),
+    TypedIdent(String, String, OperandKind),
+}
+
+
+impl ToTokens for TextInstructionPart {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        app_path(tokens, &["asdis", "primitives", "TextInstructionPart"]);
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        match self {
+            TextInstructionPart::Text(s) => {
+                tokens.append( TokenTree::Ident( Ident::new("Text", Span::call_site())) );
+                let mut string = TokenStream::new();
+                app_string_from(&mut string, &s[..]);
+                tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, string ) ) );
+            },
+            TextInstructionPart::TextIdent(s1, s2) => {
+                tokens.append( TokenTree::Ident( Ident::new("TextIdent", Span::call_site())) );
+                let mut strings = TokenStream::new();
+                app_string_from(&mut strings, &s1[..]);
+                strings.append( TokenTree::Punct( Punct::new(',', Spacing::Alone) ) );
+                app_string_from(&mut strings, &s2[..]);
+                tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, strings ) ) );
+            },
+            TextInstructionPart::TypedIdent(s1, s2, kind) => {
+                tokens.append( TokenTree::Ident( Ident::new("TypedIdent", Span::call_site())) );
+                let mut strings = TokenStream::new();
+                app_string_from(&mut strings, &s1[..]);
+                strings.append( TokenTree::Punct( Punct::new(',', Spacing::Alone) ) );
+                app_string_from(&mut strings, &s2[..]);
+                strings.append( TokenTree::Punct( Punct::new(',', Spacing::Alone) ) );
+                kind.to_tokens(&mut strings);
+                tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, strings ) ) );
+            },
+        }
+    }
+}
+
+impl TextInstructionPart {
+    fn text(s : &str) -> TextInstructionPart {
+        TextInstructionPart::Text(String::from(s))
+    }
+    #[cfg(test)]
+    fn text_ident(s1 : &str, s2 : &str) -> TextInstructionPart {
+        TextInstructionPart::TextIdent(String::from(s1), String::from(s2))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct TextInstruction {
+   pub list : Vec<TextInstructionPart>,
+}
+
+impl From<&str> for TextInstruction {
+    fn from(text : &str) -> TextInstruction {
+        TextInstruction::parse(text).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl TextInstruction {
+    ///parse text template, first word is mnemonic, then every word is ident. Ident could be annotated
+    ///with kind of operand like {imm:simm12}
+    pub fn parse(text : &str) -> Result<TextInstruction, String> {
+        lazy_static! {
+            /// asm ident must start with a letter, then letter, number of '.' any number of times
+            static ref  RE : Regex = Regex::new(r"\{([[:alpha:]][[:alnum:]]*):([[:alnum:]]+)\}|[[:alpha:]]([[:alnum:]]|\.)*").unwrap();
+        }
+
+        let mut list = Vec::<TextInstructionPart>::new();
+        let mut captures = RE.captures_iter(text);
+        let mnemonic = match captures.next() {
+            None => return Ok( TextInstruction { list } ),
+            Some( c ) => c,
+        };
+        if mnemonic.get(1).is_some() {
+            return Err( format!("Mnemonic can't be an operand : {}", text) );
+        }
+
+        //text before the first operand is kept whole, including characters before mnemonic like in <illegal>
+        let mut from = 0;
+        for c in captures {
+            let m = c.get(0).expect("Whole match always exists");
+            let prefix = String::from(&text[from..m.start()]);
+            match (c.get(1), c.get(2)) {
+                (Some( ident ), Some( kind )) => {
+                    let kind = OperandKind::parse(kind.as_str()).ok_or_else(|| format!("Unknown kind of operand {}", kind.as_str()))?;
+                    list.push( TextInstructionPart::TypedIdent(prefix, String::from(ident.as_str()), kind) );
+                },
+                _ => list.push( TextInstructionPart::TextIdent(prefix, String::from(m.as_str())) ),
+            }
+            from = m.end();
+        }
+        if list.is_empty() || from < text.len() {
+            list.push( TextInstructionPart::text(&text[from..]) );
+        }
+
+        Ok( TextInstruction { list } )
+    }
+
+    ///first word of text
+    pub fn mnemonic(&self) -> &str {
+        match self.list.first() {
+            Some( TextInstructionPart::Text( s ) ) | Some( TextInstructionPart::TextIdent( s, _ ) ) |
+            Some( TextInstructionPart::TypedIdent( s, _, _ ) ) => s.split_whitespace().next().unwrap_or(""),
+            None => "",
+        }
+    }
+
+    ///kind of operand, if ident is annotated in text
+    pub fn kind(&self, ident : &str) -> Option<OperandKind> {
+        self.list.iter().find_map(|t| match t {
+            TextInstructionPart::TypedIdent( _, i, kind ) if i == ident => Some( *kind ),
+            _ => None,
+        })
+    }
+}
+
+impl ToTokens for TextInstruction {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        app_path(tokens, &["asdis", "primitives", "TextInstruction"]);
+        let mut inside_braces = TokenStream::new();
+        inside_braces.append( TokenTree::Ident( Ident::new("list", Span::call_site())) );
+        inside_braces.append( TokenTree::Punct( Punct::new(':', Spacing::Alone) ) );
+        app_path(&mut inside_braces, &["std", "vec"]);
+        inside_braces.append( TokenTree::Punct( Punct::new('!', Spacing::Joint) ) );
+        let mut item_list = TokenStream::new();
+        item_list.append_separated( self.list.iter(), Punct::new(',', Spacing::Alone));
+        inside_braces.append( TokenTree::Group( Group::new( Delimiter::Bracket, item_list ) ) );
+        tokens.append( TokenTree::Group( Group::new( Delimiter::Brace, inside_braces ) ) );
+    }
+}
+
+/// total number of bits covered by items
+pub fn bits_len<T:Num>( v : &[Item<T>] ) -> u32 {
+    let mut r : u32 = 0;
+    for item in v {
+        match item {
+            Item::Bits {len, .. } => r += *len as u32,
+            Item::Ident { name:_ , bitspec } => r += bitspec.len() as u32,
+        };
+    };
+    r
+}
+
+/// parse bitspec like `12|10:5` to list of bits, from highest to lowest
+pub fn parse_bitspec( s : &str ) -> Result<Vec<u32>, String> {
+    let mut bitspec = Vec::new();
+    for part in s.split('|') {
+        let bit = |b : &str| b.trim().parse::<u32>().map_err(|e| format!("Wrong bitspec {} : {}", s, e));
+        match part.split_once(':') {
+            None => bitspec.push( bit(part)? ),
+            Some( (a, b) ) => {
+                let (a, b) = (bit(a)?, bit(b)?);
+                if a < b {
+                    return Err( format!("bitspec pair : first integer must be >= then second : {}", s) );
+                }
+                bitspec.extend( (b..=a).rev() );
+            },
+        }
+    }
+    Ok( bitspec )
+}
+
+/// parse number in decimal or 0x prefixed hexadecimal form
+pub fn parse_value( s : &str ) -> Result<u64, String> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some( h ) => u64::from_str_radix(h, 16),
+        None => s.parse::<u64>(),
+    }.map_err(|e| format!("Wrong value {} : {}", s, e))
+}
+
+/// check binary description of instruction: number of bits must be equal to the word size,
+/// and every bit of ident must be specified only once
+pub fn validate<T:Num>( v : &[Item<T>] ) -> Result<(), String> {
+    let bl = bits_len(v);
+    if bl != T::i_max_bit() + 1 {
+        return Err( format!("Number of the bits in provided ISA and specified command must be equal! {} != {}", bl, T::i_max_bit() + 1) );
+    }
+    let mut seen = Vec::<(&str, u32)>::new();
+    for item in v {
+        if let Item::Ident { name, bitspec } = item {
+            for sbit in bitspec {
+                if seen.contains(&(&name[..], *sbit)) {
+                    return Err( format!("Bit {} of {} is specified twice", sbit, name) );
+                }
+                seen.push( (&name[..], *sbit) );
+            }
+        }
+    }
+    Ok( () )
+}
+
+/// number of bits of ident value, given by the highest bit in its bitspecs
+pub fn ident_width<T:Num>( v : &[Item<T>], ident : &str ) -> u32 {
+    v.iter().filter_map(|item| match item {
+        Item::Ident { name, bitspec } if name == ident => bitspec.iter().max().map(|m| m + 1),
+        _ => None,
+    }).max().unwrap_or(0)
+}
+
+/// check that typed operands of text have idents in binary part, with bitspecs of operand width
+pub fn validate_text<T:Num>( text : &TextInstruction, v : &[Item<T>] ) -> Result<(), String> {
+    for t in &text.list {
+        if let TextInstructionPart::TypedIdent( _, ident, kind ) = t {
+            let width = ident_width(v, ident);
+            if width == 0 {
+                return Err( format!("Operand {} is not found in binary part", ident) );
+            }
+            if let Some( w ) = kind.width() {
+                if w != width {
+                    return Err( format!("Operand {} of {} bits has {} bits in binary part", ident, w, width) );
+                }
+            }
+        }
+    }
+    Ok( () )
+}
+
+/// build mask and pattern for binary instruction
+fn mask_pattern<T:Num>(instr : &BinaryInstruction::<T>) -> (T::IType, T::IType) {
+    let mut mask  =  T::i_zero();
+    let mut pattern  = T::i_zero();
+    for item in &instr.list {
+        match item {
+            Item::Bits { len, val } => {
+                for _i in 0..*len {
+                    mask = mask << 1;
+                    mask = mask | T::i_one();
+                }
+                pattern = pattern.rotate_left( *len as u32 );
+                pattern = pattern | *val;
+            },
+            Item::Ident { name:_, bitspec } => {
+                let l = bitspec.len();
+                mask = mask.rotate_left( l as u32);
+                pattern = pattern.rotate_left( l as u32);
+            }
+        }
+    }
+    (mask, pattern)
+}
+
+/// constraint on value of ident, used to single out special encodings like hints.
+/// Value is compared with ident value as it is extracted, i.e. not shifted by start bit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    Eq(String, u64),
+    Ne(String, u64),
+    In(String, Vec<u64>),
+    Ge(String, u64),
+    ///value of ident is equal to value of other ident
+    Same(String, String),
+}
+
+impl Constraint {
+    pub fn eq(name : &str, v : u64) -> Constraint { Constraint::Eq(String::from(name), v) }
+    pub fn ne(name : &str, v : u64) -> Constraint { Constraint::Ne(String::from(name), v) }
+    pub fn is_in(name : &str, v : &[u64]) -> Constraint { Constraint::In(String::from(name), v.to_vec()) }
+    pub fn ge(name : &str, v : u64) -> Constraint { Constraint::Ge(String::from(name), v) }
+    pub fn same(name : &str, other : &str) -> Constraint { Constraint::Same(String::from(name), String::from(other)) }
+
+    /// name of constrained ident
+    pub fn name(&self) -> &str {
+        match self {
+            Constraint::Eq( name, _ ) | Constraint::Ne( name, _ ) | Constraint::In( name, _ ) | Constraint::Ge( name, _ )
+            | Constraint::Same( name, _ ) => name,
+        }
+    }
+
+    /// check constraint, value of ident is given by function, missing idents are zero
+    pub fn check(&self, value : &dyn Fn(&str) -> Option<u64>) -> bool {
+        match self {
+            Constraint::Eq( name, v ) => value(name).unwrap_or(0) == *v,
+            Constraint::Ne( name, v ) => value(name).unwrap_or(0) != *v,
+            Constraint::In( name, v ) => v.contains( &value(name).unwrap_or(0) ),
+            Constraint::Ge( name, v ) => value(name).unwrap_or(0) >= *v,
+            Constraint::Same( name, other ) => value(name).unwrap_or(0) == value(other).unwrap_or(0),
+        }
+    }
+}
+
+impl ToTokens for Constraint {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        app_path(tokens, &["asdis", "primitives", "Constraint"]);
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        let mut inside = TokenStream::new();
+        app_string_from(&mut inside, self.name());
+        inside.append( TokenTree::Punct( Punct::new(',', Spacing::Alone) ) );
+        match self {
+            Constraint::Eq( _, v ) | Constraint::Ne( _, v ) | Constraint::Ge( _, v ) => {
+                let variant = match self { Constraint::Eq( _, _ ) => "Eq", Constraint::Ne( _, _ ) => "Ne", _ => "Ge" };
+                tokens.append( TokenTree::Ident( Ident::new(variant, Span::call_site())) );
+                inside.append( TokenTree::Literal( Literal::u64_suffixed(*v) ) );
+            },
+            Constraint::In( _, v ) => {
+                tokens.append( TokenTree::Ident( Ident::new("In", Span::call_site())) );
+                app_path(&mut inside, &["std", "vec"]);
+                inside.append( TokenTree::Punct( Punct::new('!', Spacing::Joint) ) );
+                let mut list = TokenStream::new();
+                list.append_separated( v.iter().map(|v| Literal::u64_suffixed(*v)), Punct::new(',', Spacing::Alone));
+                inside.append( TokenTree::Group( Group::new( Delimiter::Bracket, list ) ) );
+            },
+            Constraint::Same( _, other ) => {
+                tokens.append( TokenTree::Ident( Ident::new("Same", Span::call_site())) );
+                app_string_from(&mut inside, other);
+            },
+        }
+        tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, inside ) ) );
+    }
+}
+
+/// check that constrained idents are present in binary part, and values fit into their bits
+pub fn validate_constraints<T:Num>( constraints : &[Constraint], v : &[Item<T>] ) -> Result<(), String> {
+    for c in constraints {
+        let width = ident_width(v, c.name());
+        if width == 0 {
+            return Err( format!("Constrained ident {} is not found in binary part", c.name()) );
+        }
+        let values = match c {
+            Constraint::Eq( _, v ) | Constraint::Ne( _, v ) | Constraint::Ge( _, v ) => std::slice::from_ref(v),
+            Constraint::In( _, v ) => &v[..],
+            Constraint::Same( _, other ) if ident_width(v, other) == 0 =>
+                return Err( format!("Constrained ident {} is not found in binary part", other) ),
+            Constraint::Same( _, _ ) => &[],
+        };
+        if let Some( v ) = values.iter().find(|v| width < 64 && **v >> width != 0) {
+            return Err( format!("Value {} does not fit into {} bits of {}", v, width, c.name()) );
+        }
+    }
+    Ok( () )
+}
+
+/// class of encoding: ordinary instruction, hint (executes as no-op on processors which does not
+/// know it), or reserved for future use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    Normal,
+    Hint,
+    Reserved,
+}
+
+///full description of instruction, binary part for processor and textual representation for human
+#[derive(Debug)]
+pub struct Instruction<T:Num> {
+    pub bin : BinaryInstruction<T>,
+    pub text : TextInstruction,
+    pub constraints : Vec<Constraint>,
+    pub class : Class,
+    ///hint or reserved encodings inside of encoding of instruction, singled out by constraint
+    pub marks : Vec<(Constraint, Class)>,
+    ///what instruction does, if it is described
+    pub sem : Option<Sem>,
+    mask : T::IType,
+    pattern : T::IType,
+}
+
+impl<T:Num> Instruction<T> {
+    pub fn new( bin : BinaryInstruction<T>, text : TextInstruction) -> Instruction<T> {
+        let (mask, pattern) = mask_pattern(&bin);
+        Instruction { bin, text, constraints : Vec::new(), class : Class::Normal, marks : Vec::new(), sem : None, mask, pattern }
+    }
+    pub fn mask(&self) -> T::IType { self.mask }
+    pub fn pattern(&self) -> T::IType { self.pattern }
+
+    /// add constraint on ident value, instruction matches only if all constraints are satisfied
+    pub fn when(mut self, c : Constraint) -> Instruction<T> {
+        self.constraints.push(c);
+        self
+    }
+    /// mark encoding as hint
+    pub fn hint(mut self) -> Instruction<T> {
+        self.class = Class::Hint;
+        self
+    }
+    /// mark encoding as reserved
+    pub fn reserved(mut self) -> Instruction<T> {
+        self.class = Class::Reserved;
+        self
+    }
+    /// mark encodings, where constraint is satisfied, as hints
+    pub fn hint_when(mut self, c : Constraint) -> Instruction<T> {
+        self.marks.push( (c, Class::Hint) );
+        self
+    }
+    /// mark encodings, where constraint is satisfied, as reserved
+    pub fn reserved_when(mut self, c : Constraint) -> Instruction<T> {
+        self.marks.push( (c, Class::Reserved) );
+        self
+    }
+    /// class of encoding with given ident values, the first satisfied mark wins
+    pub fn class_of(&self, value : &dyn Fn(&str) -> Option<u64>) -> Class {
+        self.marks.iter().find(|(c, _)| c.check(value)).map_or(self.class, |(_, class)| *class)
+    }
+    /// describe what instruction does, to lift it into IR
+    pub fn sem(mut self, s : Sem) -> Instruction<T> {
+        self.sem = Some( s );
+        self
+    }
+    /// count of fixed bits and of constraints, more specific instruction wins in decoder
+    pub fn specificity(&self) -> (u32, usize) {
+        (self.mask.count_ones(), self.constraints.len())
+    }
+    /// check all constraints, value of ident is given by function
+    pub fn satisfies(&self, value : &dyn Fn(&str) -> Option<u64>) -> bool {
+        self.constraints.iter().all(|c| c.check(value))
+    }
+}
+
+impl<T:Num> ToTokens for Instruction<T> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+
+        app_path(tokens, &["asdis", "primitives", "Instruction"]);
+
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new('<', Spacing::Joint) ) );
+        app_path(tokens, &["asdis", "primitives", T::type_name()]);
+        tokens.append( TokenTree::Punct( Punct::new('>', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Punct( Punct::new(':', Spacing::Joint) ) );
+        tokens.append( TokenTree::Ident( Ident::new("new", Span::call_site())) );
+
+        let mut inside_parenthesis = TokenStream::new();
+        self.bin.to_tokens( &mut inside_parenthesis );
+        inside_parenthesis.append( TokenTree::Punct( Punct::new(',', Spacing::Alone) ) );
+        self.text.to_tokens( &mut inside_parenthesis );
+
+        tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, inside_parenthesis ) ) );
+
+        let marks = self.marks.iter().map(|(c, class)| (c, if *class == Class::Reserved { "reserved_when" } else { "hint_when" }));
+        for (c, method) in self.constraints.iter().map(|c| (c, "when")).chain(marks) {
+            tokens.append( TokenTree::Punct( Punct::new('.', Spacing::Alone) ) );
+            tokens.append( TokenTree::Ident( Ident::new(method, Span::call_site())) );
+            let mut inside = TokenStream::new();
+            c.to_tokens(&mut inside);
+            tokens.append( TokenTree::Group( Group::new( Delimiter::Parenthesis, inside ) ) );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ti1() {
+        assert_eq!( TextInstruction::from("c.nop"),
+                            TextInstruction { list : vec![ TextInstructionPart::text("c.nop") ] } );
+    }
+
+    #[test]
+    fn leading_text() {
+        assert_eq!( TextInstruction::from("<illegal>"),
+                            TextInstruction { list : vec![ TextInstructionPart::text("<illegal>") ] } );
+        assert_eq!( TextInstruction::from("<illegal>").mnemonic(), "<illegal>" );
+    }
+
+    #[test]
+    fn ti2() {
+        assert_eq!( TextInstruction::from("mv rd, rs1"),
+                            TextInstruction { list : vec![ TextInstructionPart::text_ident("mv ", "rd"),
+                                                                           TextInstructionPart::text_ident(", ", "rs1") ] } );
+    }
+    #[test]
+    fn ti3() {
+        assert_eq!( TextInstruction::from("c.sw rs1, imm (rs2)"),
+                            TextInstruction { list : vec![ TextInstructionPart::text_ident("c.sw ", "rs1"),
+                                                                           TextInstructionPart::text_ident(", ", "imm"),
+                                                                           TextInstructionPart::text_ident(" (", "rs2"),
+                                                                           TextInstructionPart::text(")")
+                                                                            ] } );
+    }
+
+    #[test]
+    fn bitspec() {
+        assert_eq!( parse_bitspec("12|10:5"), Ok( vec![12, 10, 9, 8, 7, 6, 5] ) );
+        assert_eq!( parse_bitspec("4 : 1 | 11"), Ok( vec![4, 3, 2, 1, 11] ) );
+        assert!( parse_bitspec("1:4").is_err() );
+        assert!( parse_bitspec("4:").is_err() );
+        assert_eq!( parse_value("0x1F"), Ok( 31 ) );
+        assert!( parse_value("x1").is_err() );
+    }
+
+    #[test]
+    fn typed() {
+        let t = TextInstruction::parse("addi {rd:xreg}, {rs1:xreg}, {imm:simm12}").unwrap();
+        assert_eq!( t.list, vec![ TextInstructionPart::TypedIdent(String::from("addi "), String::from("rd"), OperandKind::XReg),
+                                  TextInstructionPart::TypedIdent(String::from(", "), String::from("rs1"), OperandKind::XReg),
+                                  TextInstructionPart::TypedIdent(String::from(", "), String::from("imm"), OperandKind::Simm(12)) ] );
+        assert_eq!( t.kind("imm"), Some( OperandKind::Simm(12) ) );
+        assert_eq!( t.kind("rs2"), None );
+        assert!( TextInstruction::parse("{rd:xreg}").is_err() );
+        assert!( TextInstruction::parse("addi {rd:word}").is_err() );
+        assert_eq!( OperandKind::parse("uimm5"), Some( OperandKind::Uimm(5) ) );
+        assert_eq!( OperandKind::parse("simm0"), None );
+        assert_eq!( OperandKind::parse("vm"), Some( OperandKind::VMask ) );
+
+        let bin = vec![ Item::<RV32Type>::Ident { name : String::from("imm"), bitspec : vec![11,10,9,8,7,6,5,4,3,2,1,0] },
+                        Item::<RV32Type>::Ident { name : String::from("rd"), bitspec : vec![4,3,2,1,0] },
+                        Item::<RV32Type>::Bits { len : 15, val : 0x13 } ];
+        assert!( validate_text(&TextInstruction::parse("li {rd:xreg}, {imm:simm12}").unwrap(), &bin).is_ok() );
+        assert!( validate_text(&TextInstruction::parse("li {rd:xreg}, {imm:pcrel}").unwrap(), &bin).is_ok() );
+        assert!( validate_text(&TextInstruction::parse("li {rd:xreg}, {imm:simm13}").unwrap(), &bin).is_err() );
+        assert!( validate_text(&TextInstruction::parse("li {rd:creg}, {imm:simm12}").unwrap(), &bin).is_err() );
+        assert!( validate_text(&TextInstruction::parse("li {rs1:xreg}, {imm:simm12}").unwrap(), &bin).is_err() );
+    }
+}
\ No newline at end of file
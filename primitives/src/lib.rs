@@ -0,0 +1,10 @@
+//! Descriptions of instructions: binary and text parts, operand kinds, constraints and semantics.
+//! They are shared by asdis and its instruction macros
+
+mod instruction;
+mod reg;
+mod semantics;
+
+pub use instruction::*;
+pub use reg::*;
+pub use semantics::*;
@@ -0,0 +1,291 @@
+use std::fmt;
+
+///operand of instruction semantics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    ///x register of ident, or of its compressed form with p suffix
+    Reg(&'static str),
+    ///immediate ident, sign extended
+    Imm(&'static str),
+    ///immediate ident, zero extended
+    Uimm(&'static str),
+    ///fixed register, like sp of c.addi16sp
+    X(u8),
+    Const(i64),
+    ///address of instruction
+    Pc,
+}
+
+///binary operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Sll,
+    Srl,
+    Sra,
+    Slt,
+    Sltu,
+    Mul,
+    Mulh,
+    Mulhsu,
+    Mulhu,
+    Div,
+    Divu,
+    Rem,
+    Remu,
+    Min,
+    Minu,
+    Max,
+    Maxu,
+    ///result is the second operand, like new value of amoswap
+    Second,
+    ///a & ~b
+    Andn,
+    ///a | ~b
+    Orn,
+    ///~(a ^ b)
+    Xnor,
+    Rol,
+    Ror,
+    ///(a << n) + b, like sh1add
+    ShAdd(u8),
+    ///(low 32 bits of a << n) + b, like add.uw and sh1add.uw
+    ShAddUw(u8),
+    ///low 32 bits of a << b
+    SllUw,
+    ///a with bit b cleared
+    Bclr,
+    ///a with bit b set
+    Bset,
+    ///a with bit b inverted
+    Binv,
+    ///bit b of a
+    Bext,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Op::ShAdd( n ) => return write!(f, "sh{}add", n),
+            Op::ShAddUw( 0 ) => "add.uw",
+            Op::ShAddUw( n ) => return write!(f, "sh{}add.uw", n),
+            Op::Add => "add",
+            Op::Sub => "sub",
+            Op::And => "and",
+            Op::Or => "or",
+            Op::Xor => "xor",
+            Op::Sll => "sll",
+            Op::Srl => "srl",
+            Op::Sra => "sra",
+            Op::Slt => "slt",
+            Op::Sltu => "sltu",
+            Op::Mul => "mul",
+            Op::Mulh => "mulh",
+            Op::Mulhsu => "mulhsu",
+            Op::Mulhu => "mulhu",
+            Op::Div => "div",
+            Op::Divu => "divu",
+            Op::Rem => "rem",
+            Op::Remu => "remu",
+            Op::Min => "min",
+            Op::Minu => "minu",
+            Op::Max => "max",
+            Op::Maxu => "maxu",
+            Op::Second => "second",
+            Op::Andn => "andn",
+            Op::Orn => "orn",
+            Op::Xnor => "xnor",
+            Op::Rol => "rol",
+            Op::Ror => "ror",
+            Op::SllUw => "sll.uw",
+            Op::Bclr => "bclr",
+            Op::Bset => "bset",
+            Op::Binv => "binv",
+            Op::Bext => "bext",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+///unary operation of bit-manipulation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unary {
+    ///count of leading zero bits
+    Clz,
+    ///count of trailing zero bits
+    Ctz,
+    ///count of set bits
+    Cpop,
+    ///each byte is all ones if it is not zero
+    OrcB,
+    ///byte order reversed
+    Rev8,
+}
+
+impl fmt::Display for Unary {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Unary::Clz => "clz",
+            Unary::Ctz => "ctz",
+            Unary::Cpop => "cpop",
+            Unary::OrcB => "orc.b",
+            Unary::Rev8 => "rev8",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+///condition of branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cond {
+    Eq,
+    Ne,
+    Lt,
+    Ge,
+    Ltu,
+    Geu,
+}
+
+impl fmt::Display for Cond {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Cond::Eq => "==",
+            Cond::Ne => "!=",
+            Cond::Lt => "<s",
+            Cond::Ge => ">=s",
+            Cond::Ltu => "<u",
+            Cond::Geu => ">=u",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+///change of CSR by csrrw, csrrs and csrrc
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrOp {
+    Write,
+    Set,
+    Clear,
+}
+
+///what instruction does, used to lift it into IR. Operands are idents of instruction or fixed values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sem {
+    Nop,
+    ///dst = a op b, word operation works on low 32 bits and sign extends result
+    Op { op : Op, dst : Operand, a : Operand, b : Operand, word : bool },
+    ///dst = op a, word operation works on low 32 bits
+    Unary { op : Unary, dst : Operand, a : Operand, word : bool },
+    ///dst = low bits of a, sign or zero extended
+    Extend { dst : Operand, a : Operand, bits : u8, signed : bool },
+    ///dst = memory at base + offset
+    Load { dst : Operand, base : Operand, offset : Operand, bytes : u8, signed : bool },
+    ///memory at base + offset = src
+    Store { src : Operand, base : Operand, offset : Operand, bytes : u8 },
+    ///jump to pc + imm if a cond b
+    Branch { cond : Cond, a : Operand, b : Operand },
+    ///link = pc + length, jump to pc + imm
+    Jal { link : Operand },
+    ///link = pc + length, jump to base + offset with lowest bit cleared
+    Jalr { link : Operand, base : Operand, offset : Operand },
+    ///rd = memory at rs1, then memory at rs1 = rd op rs2
+    Amo { op : Op, bytes : u8 },
+    ///rd = memory at rs1, and reservation of address is made
+    LoadReserved { bytes : u8 },
+    ///memory at rs1 = rs2 if reservation is valid, rd = 0 on success
+    StoreConditional { bytes : u8 },
+    ///rd = csr, then csr is changed by src
+    Csr { op : CsrOp, src : Operand },
+    ///registers of rlist are stored below sp, then sp is decreased by stack adjustment
+    Push,
+    ///registers of rlist are loaded from top of frame and sp is increased by stack adjustment,
+    ///then a0 is zeroed if zero is set, and control returns if ret is set
+    Pop { ret : bool, zero : bool },
+}
+
+const RD : Operand = Operand::Reg("rd");
+
+impl Sem {
+    ///dst = a + b, like lui, auipc, c.li and c.mv
+    pub fn add(dst : Operand, a : Operand, b : Operand) -> Sem {
+        Sem::Op { op : Op::Add, dst, a, b, word : false }
+    }
+
+    ///rd = rs1 op rs2
+    pub fn reg(op : Op) -> Sem {
+        Sem::Op { op, dst : RD, a : Operand::Reg("rs1"), b : Operand::Reg("rs2"), word : false }
+    }
+
+    ///rd = rs1 op imm
+    pub fn imm(op : Op) -> Sem {
+        Sem::Op { op, dst : RD, a : Operand::Reg("rs1"), b : Operand::Imm("imm"), word : false }
+    }
+
+    ///rd = rs1 op shift amount
+    pub fn shift(op : Op) -> Sem {
+        Sem::Op { op, dst : RD, a : Operand::Reg("rs1"), b : Operand::Uimm("imm"), word : false }
+    }
+
+    ///rd = rs1 op shift amount, which is named shamt in bit-manipulation tables
+    pub fn shamt(op : Op) -> Sem {
+        Sem::Op { op, dst : RD, a : Operand::Reg("rs1"), b : Operand::Uimm("shamt"), word : false }
+    }
+
+    ///rd = rd op b, for compressed instructions
+    pub fn rd(op : Op, b : Operand) -> Sem {
+        Sem::Op { op, dst : RD, a : RD, b, word : false }
+    }
+
+    ///rd = low bits of rd, for compressed instructions
+    pub fn extend(bits : u8, signed : bool) -> Sem {
+        Sem::Extend { dst : RD, a : RD, bits, signed }
+    }
+
+    ///rd = low bits of rs1, like sext.b and zext.h
+    pub fn extend_rs1(bits : u8, signed : bool) -> Sem {
+        Sem::Extend { dst : RD, a : Operand::Reg("rs1"), bits, signed }
+    }
+
+    ///rd = op rs1
+    pub fn unary(op : Unary) -> Sem {
+        Sem::Unary { op, dst : RD, a : Operand::Reg("rs1"), word : false }
+    }
+
+    ///rd = memory at rs1 + imm
+    pub fn load(bytes : u8, signed : bool) -> Sem {
+        Sem::Load { dst : RD, base : Operand::Reg("rs1"), offset : Operand::Imm("imm"), bytes, signed }
+    }
+
+    ///memory at rs1 + imm = rs2
+    pub fn store(bytes : u8) -> Sem {
+        Sem::Store { src : Operand::Reg("rs2"), base : Operand::Reg("rs1"), offset : Operand::Imm("imm"), bytes }
+    }
+
+    ///branch comparing rs1 and rs2
+    pub fn branch(cond : Cond) -> Sem {
+        Sem::Branch { cond, a : Operand::Reg("rs1"), b : Operand::Reg("rs2") }
+    }
+
+    ///operation on low 32 bits
+    pub fn word(self) -> Sem {
+        match self {
+            Sem::Op { op, dst, a, b, .. } => Sem::Op { op, dst, a, b, word : true },
+            Sem::Unary { op, dst, a, .. } => Sem::Unary { op, dst, a, word : true },
+            s => s,
+        }
+    }
+
+    ///compressed load or store, offset is unsigned and base is given register or rs1
+    pub fn compressed(self, base : Operand) -> Sem {
+        let offset = Operand::Uimm("imm");
+        match self {
+            Sem::Load { dst, bytes, signed, .. } => Sem::Load { dst, base, offset, bytes, signed },
+            Sem::Store { src, bytes, .. } => Sem::Store { src, base, offset, bytes },
+            s => s,
+        }
+    }
+}
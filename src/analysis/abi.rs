@@ -0,0 +1,328 @@
+//! Calling convention checks of psABI: alignment of stack pointer, callee-saved registers and ra
+//! restored before return, gp and tp not changed outside of startup code.
+//!
+//! Instructions are lifted into IR and values of registers and stack slots are tracked over blocks:
+//! addresses in stack frame, and values which registers had at function entry
+
+use crate::primitives::Op;
+use crate::analysis::traverse::Insn;
+use crate::analysis::cfg::{Cfg, EdgeKind};
+use crate::analysis::defuse::{Loc, ABI_NAMES, CLOBBERED};
+use crate::analysis::flow::{RA, SP};
+use crate::analysis::ir::{Lifter, Stmt, Value};
+use crate::isa::{Xlen, March, sign_extend};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+///s0-s11, which function restores before return
+const SAVED : [u8; 12] = [8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+const GP : u8 = 3;
+const TP : u8 = 4;
+///names of functions which usually set up gp, tp and stack, more could be given with Convention
+pub const STARTUP : [&str; 7] = ["_start", "start", "__start", "_reset", "reset_handler", "_crt0", "_init"];
+///stack alignment of standard ABIs, used if it is not given by Tag_RISCV_stack_align
+pub const STACK_ALIGN : i64 = 16;
+
+///what is checked: alignment of sp, and functions which are allowed to change gp and tp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Convention {
+    pub stack_align : i64,
+    ///names of startup functions, compared ignoring case
+    pub startup : Vec<String>,
+}
+
+impl Default for Convention {
+    fn default() -> Convention {
+        Convention { stack_align : STACK_ALIGN, startup : STARTUP.iter().map(|s| s.to_string()).collect() }
+    }
+}
+
+impl Convention {
+    ///convention of ABI of ISA, ilp32e and lp64e align stack to 4 and 8 bytes
+    pub fn new(march : &March) -> Convention {
+        let stack_align = match (march.has("e"), march.xlen) {
+            (true, Xlen::X32) => 4,
+            (true, Xlen::X64) => 8,
+            (false, _) => STACK_ALIGN,
+        };
+        Convention { stack_align, ..Default::default() }
+    }
+
+    ///function which sets up gp, tp and stack, by its name
+    fn is_startup(&self, name : &str) -> bool {
+        self.startup.iter().any(|s| s.eq_ignore_ascii_case(name))
+    }
+}
+
+///what is wrong with function at address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    ///sp is set to offset from its value at function entry, which is not multiple of alignment
+    Misaligned(i64, i64),
+    ///callee-saved register has other value at return than at entry
+    Clobbered(u8),
+    RaNotRestored,
+    ///gp or tp is written
+    Global(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Violation {
+    pub addr : u64,
+    pub kind : Kind,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Misaligned( offset, align ) => write!(f, "sp is not {}-byte aligned, it is entry sp {} {}", align,
+                                                 if *offset < 0 { "-" } else { "+" }, offset.unsigned_abs()),
+            Kind::Clobbered( r ) => write!(f, "{} is changed and not restored before return", ABI_NAMES[*r as usize]),
+            Kind::RaNotRestored => write!(f, "ra is not restored before return"),
+            Kind::Global( r ) => write!(f, "{} is changed outside of startup code", ABI_NAMES[*r as usize]),
+        }
+    }
+}
+
+///known value of register or stack slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Val {
+    ///address of sp at function entry plus offset
+    Frame(i64),
+    ///value of register at function entry
+    Entry(u8),
+    Const(u64),
+}
+
+///known values before or after instruction, unknown ones are absent
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct State {
+    regs : BTreeMap<u8, Val>,
+    ///stack slots by offset from sp at entry
+    slots : BTreeMap<i64, Val>,
+}
+
+impl State {
+    fn entry() -> State {
+        let mut regs : BTreeMap<u8, Val> = SAVED.iter().chain(&[RA, GP, TP]).map(|r| (*r, Val::Entry( *r ))).collect();
+        regs.insert(SP, Val::Frame( 0 ));
+        State { regs, slots : BTreeMap::new() }
+    }
+
+    ///values known on both paths
+    fn meet(&self, other : &State) -> State {
+        let regs = self.regs.iter().filter(|(r, v)| other.regs.get(r) == Some( v )).map(|(r, v)| (*r, *v)).collect();
+        let slots = self.slots.iter().filter(|(s, v)| other.slots.get(s) == Some( v )).map(|(s, v)| (*s, *v)).collect();
+        State { regs, slots }
+    }
+}
+
+///checks of one function
+struct Checker<'a> {
+    cfg : &'a Cfg<'a>,
+    lifter : Lifter,
+    bits : u32,
+    startup : bool,
+    stack_align : i64,
+}
+
+impl Checker<'_> {
+    fn signed(&self, c : u64) -> i64 {
+        sign_extend(c, self.bits)
+    }
+
+    fn value(&self, state : &State, temps : &BTreeMap<u32, Val>, v : Value) -> Option<Val> {
+        match v {
+            Value::Reg( 0 ) => Some( Val::Const( 0 ) ),
+            Value::Reg( r ) => state.regs.get(&r).cloned(),
+            Value::Temp( t ) => temps.get(&t).cloned(),
+            Value::Const( c ) => Some( Val::Const( c ) ),
+        }
+    }
+
+    ///callee-saved registers and ra, which do not have their entry values when control goes back to caller
+    fn returned(&self, state : &State, addr : u64, out : &mut BTreeSet<Violation>) {
+        for r in SAVED {
+            if state.regs.get(&r) != Some( &Val::Entry( r ) ) {
+                out.insert( Violation { addr, kind : Kind::Clobbered( r ) } );
+            }
+        }
+        if state.regs.get(&RA) != Some( &Val::Entry( RA ) ) {
+            out.insert( Violation { addr, kind : Kind::RaNotRestored } );
+        }
+    }
+
+    fn set(&self, state : &mut State, addr : u64, r : u8, v : Option<Val>, out : &mut BTreeSet<Violation>) {
+        match (r, v) {
+            (SP, Some( Val::Frame( offset ) )) if offset % self.stack_align != 0 =>
+                _ = out.insert( Violation { addr, kind : Kind::Misaligned( offset, self.stack_align ) } ),
+            (GP | TP, _) if !self.startup => _ = out.insert( Violation { addr, kind : Kind::Global( r ) } ),
+            _ => {},
+        }
+        match v {
+            Some( v ) => state.regs.insert(r, v),
+            None => state.regs.remove(&r),
+        };
+    }
+
+    ///state after instruction, violations found by it are added to out
+    fn step(&mut self, state : &mut State, insn : &Insn, out : &mut BTreeSet<Violation>) {
+        let mut temps = BTreeMap::new();
+        let bytes = self.bits as u8 / 8;
+        for s in self.lifter.lift(insn) {
+            match s {
+                Stmt::Assign { dst, op, a, b, word : false } => {
+                    let v = match (op, self.value(state, &temps, a), self.value(state, &temps, b)) {
+                        (Op::Add, Some( Val::Frame( f ) ), Some( Val::Const( c ) )) |
+                        (Op::Add, Some( Val::Const( c ) ), Some( Val::Frame( f ) )) =>
+                            Some( Val::Frame( f + self.signed(c) ) ),
+                        (Op::Add, Some( Val::Const( x ) ), Some( Val::Const( y ) )) =>
+                            Some( Val::Const( x.wrapping_add(y) ) ),
+                        _ => None,
+                    };
+                    temps.extend( v.map(|v| (dst, v)) );
+                },
+                Stmt::Load { dst, addr, bytes : n, .. } => {
+                    if let (Some( Val::Frame( o ) ), true) = (self.value(state, &temps, addr), n == bytes) {
+                        temps.extend( state.slots.get(&o).map(|v| (dst, *v)) );
+                    }
+                },
+                Stmt::Store { addr, value, bytes : n } => {
+                    if let Some( Val::Frame( o ) ) = self.value(state, &temps, addr) {
+                        //only whole register saves are kept
+                        match self.value(state, &temps, value).filter(|_| n == bytes) {
+                            Some( v ) => state.slots.insert(o, v),
+                            None => state.slots.remove(&o),
+                        };
+                    }
+                },
+                Stmt::Set { reg, value } => {
+                    let v = self.value(state, &temps, value);
+                    self.set(state, insn.addr, reg, v, out);
+                },
+                Stmt::Call { .. } => {
+                    for r in CLOBBERED.iter().filter(|r| **r != RA) {
+                        state.regs.remove(r);
+                    }
+                },
+                Stmt::Return => self.returned(state, insn.addr, out),
+                //tail call
+                Stmt::Jump { target : Value::Const( t ) } if !self.cfg.blocks.contains_key(&t) =>
+                    self.returned(state, insn.addr, out),
+                Stmt::Intrinsic { writes, .. } => {
+                    for w in writes {
+                        if let Loc::X( r ) = w {
+                            self.set(state, insn.addr, r, None, out);
+                        }
+                    }
+                },
+                Stmt::Extend { dst, .. } | Stmt::CsrRead { dst, .. } | Stmt::StoreConditional { dst, .. } => {
+                    temps.remove(&dst);
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+///violations of calling convention in function, startup code could change gp and tp
+pub fn check(cfg : &Cfg, xlen : Xlen, startup : bool, stack_align : i64) -> Vec<Violation> {
+    let mut c = Checker { cfg, lifter : Lifter::new(xlen), bits : xlen.bits(), startup, stack_align : stack_align.max(1) };
+    let mut states = BTreeMap::<u64, State>::new();
+    states.insert(cfg.function.start, State::entry());
+    let mut work = vec![cfg.function.start];
+    let mut ignored = BTreeSet::new();
+    while let Some( a ) = work.pop() {
+        let Some( b ) = cfg.blocks.get(&a) else { continue };
+        let mut state = states[&a].clone();
+        for i in &b.insns {
+            c.step(&mut state, i, &mut ignored);
+        }
+        for to in b.edges.iter().filter(|e| e.kind != EdgeKind::Call).filter_map(|e| e.to) {
+            if !cfg.blocks.contains_key(&to) {
+                continue;
+            }
+            let next = match states.get(&to) {
+                Some( s ) => s.meet(&state),
+                None => state.clone(),
+            };
+            if states.get(&to) != Some( &next ) {
+                states.insert(to, next);
+                work.push(to);
+            }
+        }
+    }
+    //violations are collected once states do not change
+    let mut out = BTreeSet::new();
+    for (a, state) in &states {
+        let mut state = state.clone();
+        for i in &cfg.blocks[a].insns {
+            c.step(&mut state, i, &mut out);
+        }
+    }
+    out.into_iter().collect()
+}
+
+///violations of all functions, one per line
+pub fn text(cfgs : &[Cfg], xlen : Xlen, names : &BTreeMap<u64, String>, conv : &Convention) -> Vec<String> {
+    let mut lines = Vec::new();
+    for g in cfgs {
+        let name = names.get(&g.function.start).unwrap_or(&g.function.name);
+        for v in check(g, xlen, conv.is_startup(name), conv.stack_align) {
+            lines.push( format!("{:#010X} {}: {}", v.addr, name, v.kind) );
+        }
+    }
+    if lines.is_empty() {
+        lines.push( String::from("No violations found") );
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::with_cfgs;
+
+    fn checked(code : &[u8], startup : bool) -> Vec<Violation> {
+        with_cfgs("rv32gc", code, 0, &[0], |t, g| check(&g[0], t.xlen, startup, STACK_ALIGN))
+    }
+
+    #[test]
+    fn conforming() {
+        //c.addi16sp sp, -32; c.swsp ra, 28; c.swsp s0, 24; c.mv s0, a0; c.jal 0x108; c.mv a0, s0; c.lwsp ra, 28;
+        //c.lwsp s0, 24; c.addi16sp sp, 32; c.jr ra
+        let code = [0x3d, 0x71, 0x06, 0xce, 0x22, 0xcc, 0x2a, 0x84, 0x01, 0x22, 0x22, 0x85, 0xf2, 0x40, 0x62, 0x44,
+                    0x05, 0x61, 0x82, 0x80];
+        assert_eq!(checked(&code, false), vec![]);
+    }
+
+    #[test]
+    fn violations() {
+        //addi sp, sp, -20; sw s1, 16(sp); li s1, 1; beqz a0, 0x14; lw s1, 16(sp); 0x14: addi tp, tp, 1; mv ra, a0;
+        //addi sp, sp, 20; ret
+        let code = [0x13, 0x01, 0xc1, 0xfe, 0x23, 0x28, 0x91, 0x00, 0x93, 0x04, 0x10, 0x00, 0x63, 0x04, 0x05, 0x00,
+                    0x83, 0x24, 0x01, 0x01, 0x13, 0x02, 0x12, 0x00, 0x93, 0x00, 0x05, 0x00, 0x13, 0x01, 0x41, 0x01,
+                    0x67, 0x80, 0x00, 0x00];
+        assert_eq!(checked(&code, false), vec![Violation { addr : 0, kind : Kind::Misaligned( -20, 16 ) },
+                                               Violation { addr : 0x14, kind : Kind::Global( TP ) },
+                                               Violation { addr : 0x20, kind : Kind::Clobbered( 9 ) },
+                                               Violation { addr : 0x20, kind : Kind::RaNotRestored }]);
+        assert!(!checked(&code, true).iter().any(|v| matches!(v.kind, Kind::Global( _ ))));
+    }
+
+    #[test]
+    fn push_pop() {
+        //cm.push {ra, s0-s1}, -16; c.mv s0, a0; c.add a0, s0; cm.popret {ra, s0-s1}, 16
+        let code = [0x62, 0xb8, 0x2a, 0x84, 0x22, 0x95, 0x62, 0xbe];
+        let v = with_cfgs("rv32im_zca_zcmp", &code, 0, &[0], |t, g| check(&g[0], t.xlen, false, STACK_ALIGN));
+        assert_eq!(v, vec![]);
+    }
+
+    #[test]
+    fn convention() {
+        assert_eq!(Convention::new(&March::parse("rv32emc").unwrap()).stack_align, 4);
+        assert_eq!(Convention::new(&March::parse("rv64imac").unwrap()).stack_align, 16);
+        let c = Convention { startup : vec![String::from("Boot")], ..Default::default() };
+        assert!(c.is_startup("boot") && !c.is_startup("_start"));
+    }
+}
@@ -0,0 +1,268 @@
+//! Control flow graphs of functions: basic blocks and typed edges between them
+
+use crate::analysis::traverse::Insn;
+use crate::analysis::flow::Flow;
+use crate::analysis::functions::{body, Function};
+use crate::analysis::defuse::{effects, liveness, show};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    Fallthrough,
+    ///taken branch or jump
+    Taken,
+    Call,
+    ///indirect jump or call, target is known if it is recovered from jump table
+    Indirect,
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            EdgeKind::Fallthrough => "fallthrough",
+            EdgeKind::Taken => "taken",
+            EdgeKind::Call => "call",
+            EdgeKind::Indirect => "indirect",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+///edge to target address, indirect edges have no target unless it is recovered from jump table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub to : Option<u64>,
+    pub kind : EdgeKind,
+}
+
+///instructions executed one after another, control enters only the first one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    pub start : u64,
+    pub insns : Vec<&'a Insn>,
+    pub edges : Vec<Edge>,
+}
+
+impl<'a> Block<'a> {
+    ///address after last instruction
+    pub fn end(&self) -> u64 {
+        self.insns.last().map_or(self.start, |i| i.addr + i.len as u64)
+    }
+}
+
+///graph of function, blocks are sorted by address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cfg<'a> {
+    pub function : Function,
+    pub blocks : BTreeMap<u64, Block<'a>>,
+}
+
+///edges leaving instruction which ends block, next is the address of following block if it is reachable
+fn edges(insn : &Insn, next : Option<u64>) -> Vec<Edge> {
+    let edge = |to, kind| Edge { to, kind };
+    let mut result = match insn.flow {
+        Flow::Next | Flow::Return | Flow::Stop => Vec::new(),
+        Flow::Jump( t ) | Flow::Branch( t ) => vec![edge(Some( t ), EdgeKind::Taken)],
+        Flow::Call( t ) => vec![edge(Some( t ), EdgeKind::Call)],
+        Flow::IndirectJump | Flow::IndirectCall if insn.targets.is_empty() => vec![edge(None, EdgeKind::Indirect)],
+        Flow::IndirectJump | Flow::IndirectCall => insn.targets.iter().collect::<BTreeSet<_>>().into_iter()
+            .map(|t| edge(Some( *t ), EdgeKind::Indirect)).collect(),
+    };
+    if insn.flow.falls_through() {
+        if let Some( n ) = next {
+            result.push( edge(Some( n ), EdgeKind::Fallthrough) );
+        }
+    }
+    result
+}
+
+///graph of each function. Blocks start at function start, at targets of branches, jumps and jump tables,
+///and after control transfers including calls
+pub fn cfgs<'a>(insns : &'a BTreeMap<u64, Insn>, functions : &[Function]) -> Vec<Cfg<'a>> {
+    let starts : BTreeSet<u64> = functions.iter().map(|f| f.start).collect();
+    functions.iter().map(|f| {
+        let body = body(insns, f.start, &starts);
+        let addrs : BTreeSet<u64> = body.iter().map(|i| i.addr).collect();
+        let mut leaders = BTreeSet::new();
+        leaders.insert(f.start);
+        for i in &body {
+            if let Flow::Jump( t ) | Flow::Branch( t ) = i.flow {
+                if addrs.contains(&t) {
+                    leaders.insert(t);
+                }
+            }
+            leaders.extend( i.targets.iter().filter(|t| addrs.contains(t)) );
+            let next = i.addr + i.len as u64;
+            if i.flow != Flow::Next || !addrs.contains(&next) {
+                leaders.insert(next);
+            }
+        }
+
+        let mut blocks = BTreeMap::new();
+        let mut current : Option<Block> = None;
+        for i in body {
+            if leaders.contains(&i.addr) {
+                if let Some( b ) = current.take() {
+                    blocks.insert(b.start, b);
+                }
+            }
+            let b = current.get_or_insert_with(|| Block { start : i.addr, insns : Vec::new(), edges : Vec::new() });
+            b.insns.push(i);
+            let next = i.addr + i.len as u64;
+            if leaders.contains(&next) {
+                b.edges = edges(i, Some( next ).filter(|n| addrs.contains(n)));
+            }
+        }
+        if let Some( b ) = current {
+            blocks.insert(b.start, b);
+        }
+        Cfg { function : f.clone(), blocks }
+    }).collect()
+}
+
+///text of graphs: blocks with their instructions and edges. With live, registers live at starts and ends
+///of blocks are shown, and locations read and written by instructions
+pub fn text(cfgs : &[Cfg], names : &BTreeMap<u64, String>, live : bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let target = |e : &Edge| match e.to {
+        None => String::from("?"),
+        Some( t ) => match names.get(&t) {
+            Some( name ) => format!("{:#010X} <{}>", t, name),
+            None => format!("{:#010X}", t),
+        },
+    };
+    for g in cfgs {
+        lines.push( format!("{:#010X} <{}>:", g.function.start, g.function.name) );
+        let liveness = live.then(|| liveness(g));
+        for b in g.blocks.values() {
+            lines.push( format!("  block {:#010X}..{:#010X}", b.start, b.end()) );
+            if let Some( l ) = &liveness {
+                lines.push( format!("    live in: {}", show(&l.live_in[&b.start])) );
+            }
+            for i in &b.insns {
+                match live {
+                    true => {
+                        let e = effects(i);
+                        lines.push( format!("    {:#010X} {:<32} # reads: {}; writes: {}", i.addr, i.decoded.text,
+                                            show(&e.reads), show(&e.writes)) );
+                    },
+                    false => lines.push( format!("    {:#010X} {}", i.addr, i.decoded.text) ),
+                }
+            }
+            if let Some( l ) = &liveness {
+                lines.push( format!("    live out: {}", show(&l.live_out[&b.start])) );
+            }
+            for e in &b.edges {
+                lines.push( format!("    -> {} {}", target(e), e.kind) );
+            }
+        }
+        lines.push( String::new() );
+    }
+    lines
+}
+
+fn escape(s : &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+///graphs in Graphviz DOT format, each function is a cluster. Calls to functions of other graphs are
+///edges to their entry blocks, unknown targets are separate nodes
+pub fn dot(name : &str, cfgs : &[Cfg], names : &BTreeMap<u64, String>) -> Vec<String> {
+    let mut lines = Vec::new();
+    lines.push( format!("digraph \"{}\" {{", escape(name)) );
+    lines.push( String::from("    node [shape=box fontname=\"monospace\"];") );
+    let blocks : BTreeSet<u64> = cfgs.iter().flat_map(|g| g.blocks.keys().cloned()).collect();
+    let mut external = BTreeSet::new();
+    let mut edges = Vec::new();
+    for (n, g) in cfgs.iter().enumerate() {
+        lines.push( format!("    subgraph cluster_{} {{", n) );
+        lines.push( format!("        label=\"{}\";", escape(&g.function.name)) );
+        for b in g.blocks.values() {
+            let mut label = String::new();
+            if b.start == g.function.start {
+                label.push_str( &format!("{}:\\l", escape(&g.function.name)) );
+            }
+            for i in &b.insns {
+                label.push_str( &format!("{:#010X} {}\\l", i.addr, escape(&i.decoded.text)) );
+            }
+            lines.push( format!("        b{:x} [label=\"{}\"];", b.start, label) );
+            for (k, e) in b.edges.iter().enumerate() {
+                let to = match e.to {
+                    Some( t ) if blocks.contains(&t) => format!("b{:x}", t),
+                    Some( t ) => {
+                        external.insert(t);
+                        format!("x{:x}", t)
+                    },
+                    None => format!("i{:x}_{}", b.start, k),
+                };
+                if e.to.is_none() {
+                    lines.push( format!("        {} [label=\"?\" shape=plaintext];", to) );
+                }
+                let style = match e.kind {
+                    EdgeKind::Fallthrough => "",
+                    EdgeKind::Taken => " color=darkgreen",
+                    EdgeKind::Call => " style=dashed color=blue",
+                    EdgeKind::Indirect => " style=dotted color=red",
+                };
+                edges.push( format!("    b{:x} -> {} [label=\"{}\"{}];", b.start, to, e.kind, style) );
+            }
+        }
+        lines.push( String::from("    }") );
+    }
+    for t in external {
+        let label = match names.get(&t) {
+            Some( name ) => format!("{:#010X} <{}>", t, escape(name)),
+            None => format!("{:#010X}", t),
+        };
+        lines.push( format!("    x{:x} [label=\"{}\" shape=ellipse];", t, label) );
+    }
+    lines.extend(edges);
+    lines.push( String::from("}") );
+    lines
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::with_cfgs;
+    use crate::analysis::functions::test::CODE;
+
+    #[test]
+    fn blocks() {
+        with_cfgs("rv32gc", &CODE, 0, &[0], |_, g| {
+            assert_eq!(g.len(), 3);
+
+            let starts : Vec<u64> = g[0].blocks.keys().cloned().collect();
+            assert_eq!(starts, vec![0, 4]);
+            assert_eq!(g[0].blocks[&0].edges, vec![Edge { to : Some( 8 ), kind : EdgeKind::Call },
+                                                   Edge { to : Some( 4 ), kind : EdgeKind::Fallthrough }]);
+            assert!(g[0].blocks[&4].edges.is_empty());
+
+            //tail at 0x2c is placed after other function
+            let starts : Vec<u64> = g[1].blocks.keys().cloned().collect();
+            assert_eq!(starts, vec![8, 0xe, 0x10, 0x2c]);
+            assert_eq!(g[1].blocks[&8].end(), 0xe);
+            assert_eq!(g[1].blocks[&8].edges, vec![Edge { to : Some( 0x10 ), kind : EdgeKind::Taken },
+                                                   Edge { to : Some( 0xe ), kind : EdgeKind::Fallthrough }]);
+            assert_eq!(g[1].blocks[&0xe].edges, vec![Edge { to : Some( 0x2c ), kind : EdgeKind::Taken }]);
+            assert_eq!(g[1].blocks[&0x10].insns.len(), 3);
+
+            assert_eq!(g[2].blocks.len(), 1);
+        });
+    }
+
+    #[test]
+    fn graphviz() {
+        with_cfgs("rv32gc", &CODE, 0, &[0], |_, g| {
+            let lines = dot("code", g, &BTreeMap::new());
+            assert_eq!(lines[0], "digraph \"code\" {");
+            assert!(lines.contains(&String::from("    b0 -> b8 [label=\"call\" style=dashed color=blue];")));
+            assert!(lines.contains(&String::from("    b8 -> b10 [label=\"taken\" color=darkgreen];")));
+            assert!(lines.contains(&String::from("    b8 -> be [label=\"fallthrough\"];")));
+            assert!(lines.iter().any(|l| l.starts_with("        b18 [label=\"sub_00000018:\\l0x00000018 addi r2, r2, 0xFE0\\l")));
+            assert_eq!(lines.last().unwrap(), "}");
+        });
+    }
+}
@@ -0,0 +1,246 @@
+//! Constant propagation over blocks of function: register values set by lui, auipc, li and addi.
+//! Values are forgotten when register is written by other instruction, and after calls.
+//! Addresses built from lui or auipc are references of instructions which use them
+
+use crate::analysis::traverse::Insn;
+use crate::analysis::flow::Flow;
+use crate::analysis::cfg::{Cfg, EdgeKind};
+use crate::isa::Xlen;
+use std::collections::BTreeMap;
+
+const LOADS : [&str; 15] = ["lb", "lh", "lw", "ld", "lbu", "lhu", "lwu", "flh", "flw", "fld", "flq",
+                            "c.lw", "c.ld", "c.flw", "c.fld"];
+const STORES : [&str; 12] = ["sb", "sh", "sw", "sd", "fsh", "fsw", "fsd", "fsq", "c.sw", "c.sd", "c.fsw", "c.fsd"];
+
+///how address is used by instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    ///address is computed into register
+    Address,
+    Read,
+    Write,
+    ///jalr to known address, which does not return here
+    Jump,
+    ///jalr to known address with link
+    Call,
+}
+
+///address used by instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reference {
+    pub addr : u64,
+    pub access : Access,
+}
+
+///known value of register, address is true if it is built from lui or auipc
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Value {
+    v : u64,
+    address : bool,
+}
+
+///known values of x registers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regs {
+    values : [Option<Value>; 32],
+    mask : u64,
+}
+
+impl Regs {
+    pub fn new(xlen : Xlen) -> Regs {
+        Regs { values : [None; 32], mask : xlen.mask() }
+    }
+
+    pub fn get(&self, r : u64) -> Option<u64> {
+        self.value(r).map(|v| v.v)
+    }
+
+    fn value(&self, r : u64) -> Option<Value> {
+        match r {
+            0 => Some( Value { v : 0, address : false } ),
+            _ => self.values.get(r as usize).cloned().flatten(),
+        }
+    }
+
+    fn set(&mut self, r : Option<u64>, v : Option<Value>) {
+        match r {
+            Some( r ) if r != 0 && r < 32 => self.values[r as usize] = v.map(|v| Value { v : v.v & self.mask, ..v }),
+            _ => {},
+        }
+    }
+
+    ///value of base register plus offset of instruction
+    fn sum(&self, insn : &Insn, base : &str) -> Option<Value> {
+        let d = &insn.decoded;
+        let v = self.value(d.register(base)?)?;
+        Some( Value { v : v.v.wrapping_add(d.signed("imm").unwrap_or(0) as u64) & self.mask, ..v } )
+    }
+
+    ///values which are the same in both states
+    fn meet(&mut self, other : &Regs) {
+        for (v, o) in self.values.iter_mut().zip(other.values.iter()) {
+            if v != o {
+                *v = None;
+            }
+        }
+    }
+
+    ///execute instruction, returns address it uses if it is known
+    pub fn step(&mut self, insn : &Insn) -> Option<Reference> {
+        let d = &insn.decoded;
+        let m = &d.mnemonic[..];
+        let rd = d.register("rd");
+        //only addresses are references, not other constants
+        let reference = |v : Option<Value>, access| v.filter(|v| v.address).map(|v| Reference { addr : v.v, access });
+        let imm = |address| d.signed("imm").map(|v| Value { v : v as u64, address });
+        let result = match m {
+            "lui" | "c.lui" => {
+                self.set(rd, imm(true));
+                return None;
+            },
+            "auipc" => {
+                self.set(rd, imm(true).map(|v| Value { v : insn.addr.wrapping_add(v.v), ..v }));
+                return None;
+            },
+            "c.li" => {
+                self.set(rd, imm(false));
+                return None;
+            },
+            "addi" | "c.addi" => {
+                let v = self.sum(insn, if m == "addi" { "rs1" } else { "rd" });
+                self.set(rd, v);
+                return reference(v, Access::Address);
+            },
+            "c.mv" => {
+                let v = d.register("rs2").and_then(|r| self.value(r));
+                self.set(rd, v);
+                return None;
+            },
+            "jalr" => match rd {
+                Some( 0 ) => reference(self.sum(insn, "rs1"), Access::Jump),
+                _ => reference(self.sum(insn, "rs1"), Access::Call),
+            },
+            _ if LOADS.contains(&m) => reference(self.sum(insn, "rs1"), Access::Read),
+            _ if STORES.contains(&m) => reference(self.sum(insn, "rs1"), Access::Write),
+            _ => None,
+        };
+        self.set(rd, None);
+        if matches!(insn.flow, Flow::Call( _ ) | Flow::IndirectCall) {
+            self.values = [None; 32];
+        }
+        result
+    }
+}
+
+///register values at starts of blocks of function. State at start of block is the meet of states at
+///ends of blocks which pass control to it, blocks which are not reached have no state
+pub fn entry_states(cfg : &Cfg, xlen : Xlen) -> BTreeMap<u64, Regs> {
+    let mut states : BTreeMap<u64, Regs> = BTreeMap::new();
+    states.insert(cfg.function.start, Regs::new(xlen));
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in cfg.blocks.values() {
+            let mut regs = match states.get(&b.start) {
+                Some( regs ) => regs.clone(),
+                None => continue,
+            };
+            for i in &b.insns {
+                regs.step(i);
+            }
+            let targets = b.edges.iter()
+                .filter(|e| e.kind != EdgeKind::Call)
+                .filter_map(|e| e.to)
+                .filter(|t| cfg.blocks.contains_key(t) && *t != cfg.function.start);
+            for t in targets {
+                let state = states.entry(t).or_insert_with(|| { changed = true; regs.clone() });
+                let old = state.clone();
+                state.meet(&regs);
+                changed |= *state != old;
+            }
+        }
+    }
+    states
+}
+
+///references of instructions of function
+pub fn propagate(cfg : &Cfg, xlen : Xlen) -> BTreeMap<u64, Reference> {
+    let mut result = BTreeMap::new();
+    for (start, regs) in entry_states(cfg, xlen) {
+        let mut regs = regs;
+        for i in &cfg.blocks[&start].insns {
+            if let Some( r ) = regs.step(i) {
+                result.insert(i.addr, r);
+            }
+        }
+    }
+    result
+}
+
+///comments with addresses used by instructions, like objdump shows targets: # 0x80001880 <name>
+pub fn annotations(cfgs : &[Cfg], xlen : Xlen, names : &BTreeMap<u64, String>) -> BTreeMap<u64, String> {
+    let mut result = BTreeMap::new();
+    for g in cfgs {
+        for (addr, r) in propagate(g, xlen) {
+            let comment = match names.get(&r.addr) {
+                Some( name ) => format!("# {:#x} <{}>", r.addr, name),
+                None => format!("# {:#x}", r.addr),
+            };
+            result.insert(addr, comment);
+        }
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::{with_tables, with_cfgs};
+
+    #[test]
+    fn addresses() {
+        //lui a0, 0x10002; addi a0, a0, 0x10; lw a1, 4(a0); auipc a2, 0x1; sw a1, -8(a2);
+        //c.lw a3, 0(a0); li a0, 1; lw a1, 0(a0); auipc t0, 0; jalr ra, 0x20(t0); jalr x0, 0(a2)
+        let code = [0x37, 0x25, 0x00, 0x10, 0x13, 0x05, 0x05, 0x01, 0x83, 0x25, 0x45, 0x00,
+                    0x17, 0x16, 0x00, 0x00, 0x23, 0x2c, 0xb6, 0xfe, 0x14, 0x41, 0x05, 0x45,
+                    0x83, 0x25, 0x05, 0x00, 0x97, 0x02, 0x00, 0x00, 0xe7, 0x80, 0x02, 0x02,
+                    0x67, 0x00, 0x06, 0x00];
+        let refs = with_tables(|t| {
+            let mut regs = Regs::new(t.xlen);
+            let mut offset = 0;
+            let mut refs = Vec::new();
+            while offset < code.len() {
+                let insn = t.decode_at(&code, offset, 0x8000_0000 + offset as u64).unwrap();
+                offset += insn.len;
+                refs.push( regs.step(&insn).map(|r| (r.addr, r.access)) );
+                if insn.addr == 0x8000_0016 {
+                    assert_eq!(regs.get(10), Some( 1 ));
+                }
+            }
+            refs
+        });
+        assert_eq!(refs, vec![None, Some( (0x1000_2010, Access::Address) ), Some( (0x1000_2014, Access::Read) ),
+                              None, Some( (0x8000_1004, Access::Write) ), Some( (0x1000_2010, Access::Read) ),
+                              None, None, None, Some( (0x8000_003c, Access::Call) ), None]);
+    }
+
+    #[test]
+    fn blocks() {
+        //0x0: auipc a0, 0x2; c.beqz a1, 0xa; c.li a2, 5; c.addi a2, 1; 0xa: addi a0, a0, -1920;
+        //0xe: lui a3, 0x10000; c.beqz a1, 0x18; c.lui a3, 0x1; c.nop; 0x18: sw a1, 0(a3); c.jr ra
+        let code = [0x17, 0x25, 0x00, 0x00, 0x99, 0xc1, 0x15, 0x46, 0x05, 0x06, 0x13, 0x05,
+                    0x05, 0x88, 0xb7, 0x06, 0x00, 0x10, 0x99, 0xc1, 0x85, 0x66, 0x01, 0x00,
+                    0x23, 0xa0, 0xb6, 0x00, 0x82, 0x80];
+        let base = 0x8000_0000;
+        let comments = with_cfgs("rv32gc", &code, base, &[base], |t, g| {
+            let mut names = BTreeMap::new();
+            names.insert(0x8000_1880, String::from("uart_base"));
+            annotations(g, t.xlen, &names)
+        });
+        //a0 is the same on both paths to 0xa, a3 differs at 0x18, c.li value is not an address
+        let mut expected = BTreeMap::new();
+        expected.insert(0x8000_000a, String::from("# 0x80001880 <uart_base>"));
+        assert_eq!(comments, expected);
+    }
+}
@@ -0,0 +1,844 @@
+//! Pseudo-C of functions: statements are built from IR of blocks, and if/else and loops are recovered
+//! from branches by dominators. Registers have ABI names, stack accesses are named slots, saves and
+//! restores of registers in prologue and epilogue are hidden. Control flow which is not structured is goto
+//!
+//! Output is for reading, not for compiling: types are guessed and values are not checked
+
+use crate::primitives::{Op, Cond};
+use crate::analysis::traverse::Insn;
+use crate::analysis::flow::{Flow, SP, RA};
+use crate::analysis::cfg::{Cfg, EdgeKind};
+use crate::analysis::defuse::{effects, liveness, Liveness, Loc, ABI_NAMES, ARGUMENTS};
+use crate::analysis::ir::{Lifter, Stmt, Value};
+use crate::isa::{Xlen, sign_extend};
+use std::collections::{BTreeMap, BTreeSet};
+
+///frame pointer, which could address stack slots like sp
+const FP : u8 = 8;
+
+///expression of C text, atomic ones do not need parentheses as operands
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Expr {
+    text : String,
+    atomic : bool,
+    ///offset from sp at function entry, if value is address in stack frame
+    frame : Option<i64>,
+    ///stack slot, if value is loaded from it
+    slot : Option<i64>,
+    ///value, if it is known constant
+    constant : Option<u64>,
+}
+
+impl Expr {
+    fn atom(text : String) -> Expr {
+        Expr { text, atomic : true, frame : None, slot : None, constant : None }
+    }
+
+    fn operand(&self) -> String {
+        match self.atomic {
+            true => self.text.clone(),
+            false => format!("({})", self.text),
+        }
+    }
+}
+
+///how block ends
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    ///goes to taken block if condition is true, otherwise to fall
+    Branch { cond : Cond, a : String, b : String, taken : u64, fall : u64 },
+    Goto(u64),
+    ///indirect jump to known targets
+    Switch(String, Vec<u64>),
+    ///last statement, like return or tail call
+    End(String),
+    ///control leaves function without statement
+    Leave,
+}
+
+///translated block
+struct Block {
+    lines : Vec<String>,
+    term : Term,
+}
+
+///C text of IR values of one function
+struct Translator<'a> {
+    names : &'a BTreeMap<u64, String>,
+    int : &'static str,
+    uint : &'static str,
+    bits : u32,
+    ///registers which point into stack frame: offset from sp at entry
+    frame : BTreeMap<u8, i64>,
+    ///registers with constant values in current block
+    known : BTreeMap<u8, u64>,
+    ///lines which set constants, reads of those registers are replaced by constants
+    pending : BTreeMap<u8, usize>,
+    ///lines of constants which are not read by name
+    dead : BTreeSet<usize>,
+    ///stack slots with saved registers
+    saved : BTreeMap<i64, u8>,
+    ///registers written by translated blocks
+    written : BTreeSet<u8>,
+    ///argument registers written since last call
+    args : BTreeSet<u8>,
+    temps : BTreeMap<u32, Expr>,
+    returns : bool,
+}
+
+fn reg(r : u8) -> String {
+    String::from(ABI_NAMES[r as usize & 31])
+}
+
+fn slot_name(offset : i64) -> String {
+    match offset < 0 {
+        true => format!("local_{:x}", -offset),
+        false => format!("arg_{:x}", offset),
+    }
+}
+
+fn negate(cond : Cond) -> Cond {
+    match cond {
+        Cond::Eq => Cond::Ne,
+        Cond::Ne => Cond::Eq,
+        Cond::Lt => Cond::Ge,
+        Cond::Ge => Cond::Lt,
+        Cond::Ltu => Cond::Geu,
+        Cond::Geu => Cond::Ltu,
+    }
+}
+
+impl<'a> Translator<'a> {
+    fn new(names : &'a BTreeMap<u64, String>, xlen : Xlen) -> Translator<'a> {
+        let (int, uint) = match xlen {
+            Xlen::X32 => ("int32_t", "uint32_t"),
+            Xlen::X64 => ("int64_t", "uint64_t"),
+        };
+        Translator { names, int, uint, bits : xlen.bits(), frame : BTreeMap::new(), known : BTreeMap::new(), pending : BTreeMap::new(), dead : BTreeSet::new(), saved : BTreeMap::new(), written : BTreeSet::new(),
+                     args : BTreeSet::new(), temps : BTreeMap::new(), returns : false }
+    }
+
+    ///constant as signed decimal if it is small, as hex otherwise
+    fn constant(&self, v : u64) -> String {
+        let s = self.signed(v);
+        match (-4096..4096).contains(&s) {
+            true => s.to_string(),
+            false => format!("{:#x}", v),
+        }
+    }
+
+    ///constant as signed value of register width
+    fn signed(&self, v : u64) -> i64 {
+        sign_extend(v, self.bits)
+    }
+
+    ///result of comparison of constants
+    fn holds(&self, cond : Cond, x : u64, y : u64) -> bool {
+        let (sx, sy) = (self.signed(x), self.signed(y));
+        let (ux, uy) = (sx as u64 >> (64 - self.bits), sy as u64 >> (64 - self.bits));
+        match cond {
+            Cond::Eq => sx == sy,
+            Cond::Ne => sx != sy,
+            Cond::Lt => sx < sy,
+            Cond::Ge => sx >= sy,
+            Cond::Ltu => ux < uy,
+            Cond::Geu => ux >= uy,
+        }
+    }
+
+    ///name of code address
+    fn function(&self, addr : u64) -> String {
+        self.names.get(&addr).cloned().unwrap_or_else(|| format!("sub_{:08x}", addr))
+    }
+
+    fn value(&self, v : Value) -> Expr {
+        match v {
+            Value::Reg( r ) => match self.known.get(&r) {
+                Some( c ) => self.value(Value::Const( *c )),
+                None => Expr { frame : self.frame.get(&r).cloned(), ..Expr::atom(reg(r)) },
+            },
+            Value::Temp( t ) => self.temps.get(&t).cloned().unwrap_or_else(|| Expr::atom(format!("v{}", t))),
+            Value::Const( c ) => Expr { constant : Some( c ), ..Expr::atom(self.constant(c)) },
+        }
+    }
+
+    fn binary(&self, op : Op, a : &Expr, b : &Expr, word : bool) -> Expr {
+        let folded = match (a.constant, b.constant, word) {
+            (Some( x ), Some( y ), false) => match op {
+                Op::Add => Some( x.wrapping_add(y) ),
+                Op::Sub => Some( x.wrapping_sub(y) ),
+                Op::And => Some( x & y ),
+                Op::Or => Some( x | y ),
+                Op::Xor => Some( x ^ y ),
+                Op::Sll => Some( x.wrapping_shl(y as u32) ),
+                Op::Andn => Some( x & !y ),
+                Op::Orn => Some( x | !y ),
+                Op::Xnor => Some( !(x ^ y) ),
+                Op::ShAdd( n ) => Some( (x << n).wrapping_add(y) ),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some( c ) = folded {
+            let c = c & (u64::MAX >> (64 - self.bits));
+            return self.value(Value::Const( c ));
+        }
+        let unsigned = |e : &Expr| format!("({}){}", self.uint, e.operand());
+        let call = |f : &str| format!("{}({}, {})", f, a.text, b.text);
+        let text = match op {
+            Op::Add if a.text == "0" => b.text.clone(),
+            Op::Add if b.text == "0" => a.text.clone(),
+            Op::Add if b.text.starts_with('-') => format!("{} - {}", a.operand(), &b.text[1..]),
+            Op::Add => format!("{} + {}", a.operand(), b.operand()),
+            Op::Sub => format!("{} - {}", a.operand(), b.operand()),
+            Op::And => format!("{} & {}", a.operand(), b.operand()),
+            Op::Or => format!("{} | {}", a.operand(), b.operand()),
+            Op::Xor if b.text == "-1" => format!("~{}", a.operand()),
+            Op::Xor => format!("{} ^ {}", a.operand(), b.operand()),
+            Op::Sll => format!("{} << {}", a.operand(), b.operand()),
+            Op::Srl => format!("{} >> {}", unsigned(a), b.operand()),
+            Op::Sra => format!("{} >> {}", a.operand(), b.operand()),
+            Op::Slt => format!("{} < {}", a.operand(), b.operand()),
+            Op::Sltu => format!("{} < {}", unsigned(a), unsigned(b)),
+            Op::Mul => format!("{} * {}", a.operand(), b.operand()),
+            Op::Div => format!("{} / {}", a.operand(), b.operand()),
+            Op::Divu => format!("{} / {}", unsigned(a), unsigned(b)),
+            Op::Rem => format!("{} % {}", a.operand(), b.operand()),
+            Op::Remu => format!("{} % {}", unsigned(a), unsigned(b)),
+            Op::Mulh | Op::Mulhsu | Op::Mulhu | Op::Min | Op::Minu | Op::Max | Op::Maxu => call(&op.to_string()),
+            Op::Second => b.text.clone(),
+            Op::Andn => format!("{} & ~{}", a.operand(), b.operand()),
+            Op::Orn => format!("{} | ~{}", a.operand(), b.operand()),
+            Op::Xnor => format!("~({} ^ {})", a.operand(), b.operand()),
+            Op::Rol | Op::Ror => call(&op.to_string()),
+            Op::ShAdd( n ) => format!("({} << {}) + {}", a.operand(), n, b.operand()),
+            Op::ShAddUw( n ) => format!("((uint32_t){} << {}) + {}", a.operand(), n, b.operand()),
+            Op::SllUw => format!("(uint32_t){} << {}", a.operand(), b.operand()),
+            Op::Bclr => format!("{} & ~(1 << {})", a.operand(), b.operand()),
+            Op::Bset => format!("{} | (1 << {})", a.operand(), b.operand()),
+            Op::Binv => format!("{} ^ (1 << {})", a.operand(), b.operand()),
+            Op::Bext => format!("({} >> {}) & 1", unsigned(a), b.operand()),
+        };
+        let atomic = text == a.text || text == b.text || matches!(op, Op::Mulh | Op::Mulhsu | Op::Mulhu | Op::Min
+                                                                      | Op::Minu | Op::Max | Op::Maxu | Op::Rol | Op::Ror);
+        let frame = match op {
+            Op::Add if !word => a.frame.zip(b.text.parse::<i64>().ok()).map(|(f, c)| f + c),
+            _ => None,
+        };
+        match word {
+            true => Expr { frame : None, ..Expr::atom(format!("(int32_t)({})", text)) },
+            false => Expr { text, atomic, frame, slot : None, constant : None },
+        }
+    }
+
+    ///memory at address, or stack slot
+    fn memory(&self, addr : &Expr, bytes : u8, signed : bool) -> (String, Option<i64>) {
+        match addr.frame {
+            Some( offset ) => (slot_name(offset), Some( offset )),
+            None => (format!("*({}int{}_t *){}", if signed { "" } else { "u" }, bytes as u32 * 8, addr.operand()), None),
+        }
+    }
+
+    ///arguments of call: argument registers up to the last one written before it
+    fn call_args(&mut self) -> String {
+        let last = ARGUMENTS.iter().rev().find(|r| self.args.contains(r));
+        let list : Vec<String> = match last {
+            Some( last ) => (ARGUMENTS[0]..=*last).map(reg).collect(),
+            None => Vec::new(),
+        };
+        self.args.clear();
+        list.join(", ")
+    }
+
+    ///set register, writes which change frame registers and restores of saved registers are hidden
+    fn set(&mut self, lines : &mut Vec<String>, r : u8, e : Expr) {
+        if (r == SP || r == FP) && e.frame.is_some() {
+            self.frame.insert(r, e.frame.unwrap_or(0));
+            return;
+        }
+        self.frame.remove(&r);
+        match e.constant {
+            Some( c ) => self.known.insert(r, c),
+            None => self.known.remove(&r),
+        };
+        if e.slot.is_some_and(|s| self.saved.get(&s) == Some( &r )) {
+            return;
+        }
+        self.written.insert(r);
+        if ARGUMENTS.contains(&r) {
+            self.args.insert(r);
+        }
+        if r == ARGUMENTS[0] {
+            self.returns = true;
+        }
+        if let Some( l ) = self.pending.remove(&r) {
+            self.dead.insert(l);
+        }
+        if e.constant.is_some() {
+            self.pending.insert(r, lines.len());
+        }
+        lines.push( format!("{} = {};", reg(r), e.text) );
+    }
+
+    ///statements of block, live is liveness of function to find out if result of call is used
+    fn block(&mut self, lifter : &mut Lifter, insns : &[&Insn], next : Option<u64>, blocks : &BTreeSet<u64>,
+             live : &Liveness) -> Block {
+        let mut lines = Vec::new();
+        let mut term = match next {
+            Some( n ) => Term::Goto(n),
+            None => Term::Leave,
+        };
+        self.args.clear();
+        self.known.clear();
+        self.pending.clear();
+        self.dead.clear();
+        for i in insns {
+            let stmts = lifter.lift(i);
+            let mut uses = BTreeMap::<u32, usize>::new();
+            for s in &stmts {
+                for v in used(s) {
+                    if let Value::Temp( t ) = v {
+                        *uses.entry(t).or_default() += 1;
+                    }
+                }
+            }
+            for s in stmts {
+                let temp = match &s {
+                    Stmt::Assign { dst, op, a, b, word } => Some( (*dst, self.binary(*op, &self.value(*a), &self.value(*b), *word)) ),
+                    Stmt::Unary { dst, op, a, word } => {
+                        let f = format!("{}{}", op, if *word { "w" } else { "" }).replace('.', "_");
+                        Some( (*dst, Expr::atom(format!("{}({})", f, self.value(*a).text))) )
+                    },
+                    Stmt::Extend { dst, a, bits, signed } => {
+                        let t = format!("({}int{}_t){}", if *signed { "" } else { "u" }, bits, self.value(*a).operand());
+                        Some( (*dst, Expr::atom(t)) )
+                    },
+                    Stmt::Load { dst, addr, bytes, signed } => {
+                        let (text, slot) = self.memory(&self.value(*addr), *bytes, *signed);
+                        Some( (*dst, Expr { slot, ..Expr::atom(text) }) )
+                    },
+                    Stmt::CsrRead { dst, csr } => Some( (*dst, Expr::atom(format!("csr_read({:#x})", csr))) ),
+                    Stmt::StoreConditional { dst, addr, value, bytes } =>
+                        Some( (*dst, Expr::atom(format!("store_conditional{}({}, {})", *bytes as u32 * 8,
+                                                        self.value(*addr).text, self.value(*value).text))) ),
+                    _ => None,
+                };
+                if let Some( (t, e) ) = temp {
+                    //value used more than once is computed once
+                    let e = match uses.get(&t).cloned().unwrap_or(0) > 1 && !e.atomic {
+                        true => {
+                            lines.push( format!("v{} = {};", t, e.text) );
+                            Expr { text : format!("v{}", t), atomic : true, ..e }
+                        },
+                        false => e,
+                    };
+                    self.temps.insert(t, e);
+                    continue;
+                }
+                match s {
+                    //return address of call
+                    Stmt::Set { reg : RA, value : Value::Const( _ ) } if matches!(i.flow, Flow::Call( _ ) | Flow::IndirectCall) => {
+                        self.dead.extend( self.pending.remove(&RA) );
+                    },
+                    Stmt::Set { reg : r, value } => {
+                        let e = self.value(value);
+                        self.set(&mut lines, r, e);
+                    },
+                    Stmt::Store { addr, value, bytes } => {
+                        let (target, slot) = self.memory(&self.value(addr), bytes, true);
+                        match (slot, value) {
+                            //register saved in prologue before it is changed
+                            (Some( s ), Value::Reg( r )) if saved_register(r) && !self.written.contains(&r) => {
+                                self.saved.insert(s, r);
+                            },
+                            _ => lines.push( format!("{} = {};", target, self.value(value).text) ),
+                        }
+                    },
+                    Stmt::Branch { cond, a, b, target } => {
+                        let fall = next.unwrap_or(i.addr + i.len as u64);
+                        let (a, b) = (self.value(a), self.value(b));
+                        //condition of constants is known, only one path is taken
+                        term = match (a.constant, b.constant) {
+                            (Some( x ), Some( y )) => Term::Goto( if self.holds(cond, x, y) { target } else { fall } ),
+                            _ => Term::Branch { cond, a : a.operand(), b : b.operand(), taken : target, fall },
+                        };
+                    },
+                    Stmt::Call { target } => {
+                        let t = self.value(target);
+                        let f = match t.constant {
+                            Some( c ) => self.function(c),
+                            None => format!("(*{})", t.operand()),
+                        };
+                        let call = format!("{}({})", f, self.call_args());
+                        self.known.clear();
+                        self.pending.clear();
+                        let used = live.after.get(&i.addr).is_some_and(|l| l.contains(&Loc::X( ARGUMENTS[0] )));
+                        match used {
+                            true => self.set(&mut lines, ARGUMENTS[0], Expr::atom(call)),
+                            false => lines.push( format!("{};", call) ),
+                        }
+                    },
+                    Stmt::Jump { target } if self.value(target).constant.is_some_and(|t| blocks.contains(&t)) =>
+                        term = Term::Goto(self.value(target).constant.unwrap_or(0)),
+                    Stmt::Jump { target } if self.value(target).constant.is_some() => {
+                        let t = self.value(target).constant.unwrap_or(0);
+                        let call = format!("{}({})", self.function(t), self.call_args());
+                        self.pending.clear();
+                        self.returns = true;
+                        term = Term::End(format!("return {};", call));
+                    },
+                    Stmt::Jump { target } => {
+                        let t = self.value(target).text;
+                        term = match i.targets.is_empty() {
+                            true => Term::End(format!("goto *{};", t)),
+                            false => Term::Switch(t, i.targets.iter().cloned().collect::<BTreeSet<u64>>().into_iter().collect()),
+                        };
+                    },
+                    Stmt::Return => term = Term::End(String::from("return;")),
+                    Stmt::CsrWrite { csr, value } => lines.push( format!("csr_write({:#x}, {});", csr, self.value(value).text) ),
+                    Stmt::Reserve { addr } => lines.push( format!("reserve({});", self.value(addr).text) ),
+                    Stmt::Intrinsic { name, reads, writes } => {
+                        let loc = |l : &Loc| match l {
+                            Loc::X( r ) => Some( reg(*r) ),
+                            Loc::F( _ ) | Loc::V( _ ) => Some( l.to_string() ),
+                            _ => None,
+                        };
+                        let args : Vec<String> = reads.iter().filter_map(loc).collect();
+                        self.pending.retain(|r, _| !reads.contains(&Loc::X( *r )));
+                        let results : Vec<String> = writes.iter().filter_map(loc).collect();
+                        let call = format!("__{}({})", name.replace('.', "_"), args.join(", "));
+                        match results.len() {
+                            1 => lines.push( format!("{} = {};", results[0], call) ),
+                            _ => lines.push( format!("{};", call) ),
+                        }
+                        for w in writes {
+                            if let Loc::X( r ) = w {
+                                self.frame.remove(&r);
+                                self.known.remove(&r);
+                                self.written.insert(r);
+                            }
+                        }
+                        if i.flow == Flow::Stop {
+                            term = Term::Leave;
+                        }
+                    },
+                    _ => {},
+                }
+            }
+        }
+        //constants, which are replaced in this block and are not used after it
+        let out = insns.last().and_then(|i| live.after.get(&i.addr));
+        for (r, l) in &self.pending {
+            if out.is_none_or(|o| !o.contains(&Loc::X( *r ))) {
+                self.dead.insert(*l);
+            }
+        }
+        let lines = lines.into_iter().enumerate().filter(|(n, _)| !self.dead.contains(n)).map(|(_, l)| l).collect();
+        Block { lines, term }
+    }
+}
+
+///values read by statement
+fn used(s : &Stmt) -> Vec<Value> {
+    match s {
+        Stmt::Assign { a, b, .. } => vec![*a, *b],
+        Stmt::Extend { a, .. } | Stmt::Unary { a, .. } => vec![*a],
+        Stmt::Load { addr, .. } | Stmt::Reserve { addr } => vec![*addr],
+        Stmt::Store { addr, value, .. } | Stmt::StoreConditional { addr, value, .. } => vec![*addr, *value],
+        Stmt::Set { value, .. } | Stmt::CsrWrite { value, .. } => vec![*value],
+        Stmt::Branch { a, b, .. } => vec![*a, *b],
+        Stmt::Jump { target } | Stmt::Call { target } => vec![*target],
+        _ => Vec::new(),
+    }
+}
+
+///ra and s0-s11, which are saved by callee
+fn saved_register(r : u8) -> bool {
+    r == RA || r == 8 || r == 9 || (18..=27).contains(&r)
+}
+
+///dominators of each block, post is true for post-dominators
+fn dominators(blocks : &BTreeMap<u64, Block>, entry : u64, post : bool) -> BTreeMap<u64, BTreeSet<u64>> {
+    let all : BTreeSet<u64> = blocks.keys().cloned().collect();
+    let mut preds = BTreeMap::<u64, Vec<u64>>::new();
+    for (a, b) in blocks {
+        for s in successors(&b.term) {
+            match post {
+                true => preds.entry(*a).or_default().push(s),
+                false => preds.entry(s).or_default().push(*a),
+            }
+        }
+    }
+    let mut dom : BTreeMap<u64, BTreeSet<u64>> = all.iter().map(|a| (*a, all.clone())).collect();
+    let roots : BTreeSet<u64> = match post {
+        true => all.iter().filter(|a| !preds.contains_key(a)).cloned().collect(),
+        false => std::iter::once(entry).collect(),
+    };
+    for r in &roots {
+        dom.insert(*r, std::iter::once(*r).collect());
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for a in all.iter().filter(|a| !roots.contains(a)) {
+            let mut set : Option<BTreeSet<u64>> = None;
+            for p in preds.get(a).into_iter().flatten() {
+                set = Some( match set {
+                    None => dom[p].clone(),
+                    Some( s ) => s.intersection(&dom[p]).cloned().collect(),
+                } );
+            }
+            let mut set = set.unwrap_or_default();
+            set.insert(*a);
+            if set != dom[a] {
+                dom.insert(*a, set);
+                changed = true;
+            }
+        }
+    }
+    dom
+}
+
+fn successors(term : &Term) -> Vec<u64> {
+    match term {
+        Term::Branch { taken, fall, .. } => vec![*taken, *fall],
+        Term::Goto( t ) => vec![*t],
+        Term::Switch( _, targets ) => targets.clone(),
+        Term::End( _ ) | Term::Leave => Vec::new(),
+    }
+}
+
+///loop being emitted: continue goes to header, break goes to follow
+struct Loop {
+    header : u64,
+    follow : Option<u64>,
+    body : BTreeSet<u64>,
+}
+
+///structured text of function
+struct Structurer<'a> {
+    blocks : &'a BTreeMap<u64, Block>,
+    ipdom : BTreeMap<u64, u64>,
+    loops : BTreeMap<u64, BTreeSet<u64>>,
+    names : &'a BTreeMap<u64, String>,
+    uint : &'static str,
+    emitted : BTreeSet<u64>,
+    gotos : BTreeSet<u64>,
+    active : Vec<Loop>,
+    lines : Vec<String>,
+}
+
+impl<'a> Structurer<'a> {
+    fn new(blocks : &'a BTreeMap<u64, Block>, entry : u64, names : &'a BTreeMap<u64, String>, uint : &'static str)
+           -> Structurer<'a> {
+        let pdom = dominators(blocks, entry, true);
+        let ipdom = pdom.iter().filter_map(|(a, set)| {
+            let strict : BTreeSet<u64> = set.iter().filter(|p| *p != a).cloned().collect();
+            strict.iter().find(|p| pdom[p] == strict).map(|p| (*a, *p))
+        }).collect();
+        //natural loops of back edges to blocks which dominate their sources
+        let dom = dominators(blocks, entry, false);
+        let mut preds = BTreeMap::<u64, Vec<u64>>::new();
+        for (a, b) in blocks {
+            for s in successors(&b.term) {
+                preds.entry(s).or_default().push(*a);
+            }
+        }
+        let mut loops = BTreeMap::<u64, BTreeSet<u64>>::new();
+        for (a, b) in blocks {
+            for h in successors(&b.term).into_iter().filter(|h| dom[a].contains(h)) {
+                let body = loops.entry(h).or_default();
+                body.insert(h);
+                let mut stack = vec![*a];
+                while let Some( n ) = stack.pop() {
+                    if body.insert(n) {
+                        stack.extend( preds.get(&n).into_iter().flatten() );
+                    }
+                }
+            }
+        }
+        Structurer { blocks, ipdom, loops, names, uint, emitted : BTreeSet::new(), gotos : BTreeSet::new(), active : Vec::new(),
+                     lines : Vec::new() }
+    }
+
+    fn label(&self, addr : u64) -> String {
+        self.names.get(&addr).cloned().unwrap_or_else(|| format!("label_{:08x}", addr))
+    }
+
+    fn push(&mut self, depth : usize, line : String) {
+        self.lines.push( format!("{}{}", "    ".repeat(depth), line) );
+    }
+
+    ///statement which passes control to block from inside of loops, if block is not emitted there
+    fn jump(&mut self, to : u64) -> Option<String> {
+        if let Some( l ) = self.active.last() {
+            if l.header == to {
+                return Some( String::from("continue;") );
+            }
+            if l.follow == Some( to ) {
+                return Some( String::from("break;") );
+            }
+        }
+        let outer = self.active.iter().any(|l| l.header == to || l.follow == Some( to ));
+        if self.emitted.contains(&to) || outer || !self.blocks.contains_key(&to) {
+            self.gotos.insert(to);
+            return Some( format!("goto {};", self.label(to)) );
+        }
+        None
+    }
+
+    ///blocks from start until stop block, which is emitted by caller
+    fn region(&mut self, start : u64, stop : Option<u64>, depth : usize) {
+        let mut current = Some( start );
+        while let Some( b ) = current {
+            if Some( b ) == stop {
+                return;
+            }
+            if let Some( j ) = self.jump(b) {
+                self.push(depth, j);
+                return;
+            }
+            current = match self.loops.get(&b).cloned() {
+                Some( body ) if !self.active.iter().any(|l| l.header == b) => self.emit_loop(b, body, depth),
+                _ => self.emit_block(b, stop, depth),
+            };
+        }
+    }
+
+    ///loop with its body, returns block after it
+    fn emit_loop(&mut self, header : u64, body : BTreeSet<u64>, depth : usize) -> Option<u64> {
+        let exits : BTreeSet<u64> = body.iter().flat_map(|b| successors(&self.blocks[b].term))
+            .filter(|s| !body.contains(s)).collect();
+        let follow = exits.iter().next().cloned();
+        self.active.push( Loop { header, follow, body } );
+        let b = &self.blocks[&header];
+        //loop which checks condition before body
+        let head = match &b.term {
+            Term::Branch { cond, a, b : c, taken, fall } if b.lines.is_empty() && Some( *fall ) == follow =>
+                Some( (self.condition(*cond, a, c), *taken) ),
+            Term::Branch { cond, a, b : c, taken, fall } if b.lines.is_empty() && Some( *taken ) == follow =>
+                Some( (self.condition(negate(*cond), a, c), *fall) ),
+            _ => None,
+        };
+        match head {
+            Some( (cond, first) ) => {
+                self.emitted.insert(header);
+                self.push(depth, format!("LABEL {:x}", header));
+                self.push(depth, format!("while ({}) {{", cond));
+                self.region(first, Some( header ), depth + 1);
+            },
+            None => {
+                self.push(depth, String::from("while (1) {"));
+                self.emit_block_region(header, depth + 1);
+            },
+        }
+        if self.lines.last().is_some_and(|l| l.trim() == "continue;") {
+            self.lines.pop();
+        }
+        self.push(depth, String::from("}"));
+        self.active.pop();
+        follow
+    }
+
+    fn emit_block_region(&mut self, header : u64, depth : usize) {
+        let next = self.emit_block(header, Some( header ), depth);
+        if let Some( n ) = next {
+            self.region(n, Some( header ), depth);
+        }
+    }
+
+    fn condition(&self, cond : Cond, a : &str, b : &str) -> String {
+        match cond {
+            Cond::Eq => format!("{} == {}", a, b),
+            Cond::Ne => format!("{} != {}", a, b),
+            Cond::Lt => format!("{} < {}", a, b),
+            Cond::Ge => format!("{} >= {}", a, b),
+            Cond::Ltu => format!("({}){} < ({}){}", self.uint, a, self.uint, b),
+            Cond::Geu => format!("({}){} >= ({}){}", self.uint, a, self.uint, b),
+        }
+    }
+
+    ///statements of block and of blocks which it chooses between, returns block after them
+    fn emit_block(&mut self, b : u64, stop : Option<u64>, depth : usize) -> Option<u64> {
+        self.emitted.insert(b);
+        self.push(0, format!("LABEL {:x}", b));
+        let block = &self.blocks[&b];
+        for l in &block.lines {
+            self.push(depth, l.clone());
+        }
+        match block.term.clone() {
+            Term::Goto( t ) => Some( t ),
+            Term::End( s ) => {
+                self.push(depth, s);
+                None
+            },
+            Term::Leave => None,
+            Term::Switch( t, targets ) => {
+                let list : Vec<String> = targets.iter().map(|a| self.label(*a)).collect();
+                self.gotos.extend( targets.iter() );
+                self.push(depth, format!("goto *{}; /* {} */", t, list.join(", ")));
+                None
+            },
+            Term::Branch { cond, a, b : c, taken, fall } => {
+                //join of both paths, if it is inside of current loop
+                let join = self.ipdom.get(&b).cloned()
+                    .filter(|j| self.active.last().is_none_or(|l| l.body.contains(j)));
+                let join = join.or(stop.filter(|s| Some( *s ) == self.active.last().map(|l| l.header)));
+                if Some( taken ) == join {
+                    self.push(depth, format!("if ({}) {{", self.condition(negate(cond), &a, &c)));
+                    self.region(fall, join, depth + 1);
+                    self.push(depth, String::from("}"));
+                    return join;
+                }
+                self.push(depth, format!("if ({}) {{", self.condition(cond, &a, &c)));
+                self.region(taken, join, depth + 1);
+                match join {
+                    Some( j ) if j == fall => {
+                        self.push(depth, String::from("}"));
+                    },
+                    Some( _ ) => {
+                        self.push(depth, String::from("} else {"));
+                        self.region(fall, join, depth + 1);
+                        self.push(depth, String::from("}"));
+                    },
+                    None => {
+                        self.push(depth, String::from("}"));
+                        return Some( fall );
+                    },
+                }
+                join
+            },
+        }
+    }
+}
+
+///pseudo-C of function
+fn function(cfg : &Cfg, xlen : Xlen, names : &BTreeMap<u64, String>) -> Vec<String> {
+    let live = liveness(cfg);
+    let mut t = Translator::new(names, xlen);
+    let mut lifter = Lifter::new(xlen);
+    let keys : BTreeSet<u64> = cfg.blocks.keys().cloned().collect();
+    t.frame.insert(SP, 0);
+
+    //blocks are translated in order of their first reach, with frame of block which reaches them
+    let mut frames = BTreeMap::new();
+    frames.insert(cfg.function.start, t.frame.clone());
+    let mut order = vec![cfg.function.start];
+    let mut blocks = BTreeMap::new();
+    while let Some( a ) = order.pop() {
+        if blocks.contains_key(&a) {
+            continue;
+        }
+        let b = &cfg.blocks[&a];
+        let next = b.edges.iter().find(|e| e.kind == EdgeKind::Fallthrough).and_then(|e| e.to);
+        t.frame = frames.get(&a).cloned().unwrap_or_default();
+        let block = t.block(&mut lifter, &b.insns, next, &keys, &live);
+        for s in successors(&block.term).into_iter().rev() {
+            frames.entry(s).or_insert_with(|| t.frame.clone());
+            order.push(s);
+        }
+        blocks.insert(a, block);
+    }
+    //blocks reached only by unknown indirect jumps, blocks behind branches of constants are dead
+    let mut reached = BTreeSet::new();
+    let mut work = vec![cfg.function.start];
+    while let Some( a ) = work.pop() {
+        if reached.insert(a) {
+            work.extend( cfg.blocks.get(&a).into_iter().flat_map(|b| b.edges.iter())
+                .filter(|e| e.kind != EdgeKind::Call).filter_map(|e| e.to) );
+        }
+    }
+    for (a, b) in &cfg.blocks {
+        if !blocks.contains_key(a) && !reached.contains(a) {
+            let next = b.edges.iter().find(|e| e.kind == EdgeKind::Fallthrough).and_then(|e| e.to);
+            t.frame.clear();
+            let block = t.block(&mut lifter, &b.insns, next, &keys, &live);
+            blocks.insert(*a, block);
+        }
+    }
+
+    let mut s = Structurer::new(&blocks, cfg.function.start, names, t.uint);
+    s.region(cfg.function.start, None, 1);
+    for a in blocks.keys() {
+        if !s.emitted.contains(a) {
+            s.gotos.insert(*a);
+            s.region(*a, None, 1);
+        }
+    }
+
+    //arguments are argument registers read before they are written
+    let reads : BTreeSet<Loc> = cfg.blocks.values().flat_map(|b| b.insns.iter()).flat_map(|i| effects(i).reads).collect();
+    let entry = &live.live_in[&cfg.function.start];
+    let last = ARGUMENTS.iter().rev().find(|r| entry.contains(&Loc::X( **r )) && reads.contains(&Loc::X( **r )));
+    let params : Vec<String> = match last {
+        Some( last ) => (ARGUMENTS[0]..=*last).map(|r| format!("{} {}", t.int, reg(r))).collect(),
+        None => vec![String::from("void")],
+    };
+    let mut lines = vec![format!("{} {}({})", if t.returns { t.int } else { "void" }, cfg.function.name, params.join(", ")),
+                         String::from("{")];
+    for l in s.lines {
+        match l.trim().strip_prefix("LABEL ") {
+            Some( a ) => {
+                let a = u64::from_str_radix(a, 16).unwrap_or(0);
+                if s.gotos.contains(&a) && a != cfg.function.start {
+                    lines.push( format!("{}:", s.names.get(&a).cloned().unwrap_or_else(|| format!("label_{:08x}", a))) );
+                }
+            },
+            None if t.returns && l.trim() == "return;" => lines.push( l.replace("return;", "return a0;") ),
+            None => lines.push( l ),
+        }
+    }
+    lines.push( String::from("}") );
+    lines
+}
+
+///pseudo-C of functions
+pub fn decompile(cfgs : &[Cfg], xlen : Xlen, names : &BTreeMap<u64, String>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for g in cfgs {
+        lines.extend( function(g, xlen, names) );
+        lines.push( String::new() );
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::with_cfgs;
+
+    fn decompiled(code : &[u8]) -> Vec<String> {
+        with_cfgs("rv32gc", code, 0, &[0], |t, g| function(&g[0], t.xlen, &BTreeMap::new()))
+    }
+
+    #[test]
+    fn condition() {
+        //blt a0, a1, 0x8; slli a0, a1, 1; 0x8: ret
+        let code = [0x63, 0x44, 0xb5, 0x00, 0x13, 0x95, 0x15, 0x00, 0x67, 0x80, 0x00, 0x00];
+        assert_eq!(decompiled(&code), vec!["int32_t sub_00000000(int32_t a0, int32_t a1)", "{",
+                                           "    if (a0 >= a1) {", "        a0 = a1 << 1;", "    }",
+                                           "    return a0;", "}"]);
+    }
+
+    #[test]
+    fn constant_condition() {
+        //li a0, 5; bnez a0, 0xc; li a1, 1; 0xc: mv a0, a1; ret
+        let code = [0x13, 0x05, 0x50, 0x00, 0x63, 0x14, 0x05, 0x00, 0x93, 0x05, 0x10, 0x00, 0x13, 0x85, 0x05, 0x00,
+                    0x67, 0x80, 0x00, 0x00];
+        assert_eq!(decompiled(&code), vec!["int32_t sub_00000000(int32_t a0, int32_t a1)", "{",
+                                           "    a0 = a1;", "    return a0;", "}"]);
+    }
+
+    #[test]
+    fn frame_and_loop() {
+        //addi sp, sp, -16; sw ra, 12(sp); li a1, 0; 0xc: beqz a0, 0x1c; add a1, a1, a0; addi a0, a0, -1; j 0xc;
+        //0x1c: sw a1, 8(sp); lw a0, 8(sp); lw ra, 12(sp); addi sp, sp, 16; ret
+        let code = [0x13, 0x01, 0x01, 0xff, 0x23, 0x26, 0x11, 0x00, 0x93, 0x05, 0x00, 0x00, 0x63, 0x08, 0x05, 0x00,
+                    0xb3, 0x85, 0xa5, 0x00, 0x13, 0x05, 0xf5, 0xff, 0x6f, 0xf0, 0x5f, 0xff, 0x23, 0x24, 0xb1, 0x00,
+                    0x03, 0x25, 0x81, 0x00, 0x83, 0x20, 0xc1, 0x00, 0x13, 0x01, 0x01, 0x01, 0x67, 0x80, 0x00, 0x00];
+        assert_eq!(decompiled(&code), vec!["int32_t sub_00000000(int32_t a0)", "{",
+                                           "    a1 = 0;", "    while (a0 != 0) {", "        a1 = a1 + a0;",
+                                           "        a0 = a0 - 1;", "    }", "    local_8 = a1;", "    a0 = local_8;",
+                                           "    return a0;", "}"]);
+    }
+}
@@ -0,0 +1,415 @@
+//! Registers, CSRs and memory read and written by instructions, including implicit ones like ra of
+//! c.jal or sp of c.addi16sp, and liveness of registers over blocks of function
+
+use crate::analysis::traverse::Insn;
+use crate::analysis::flow::{Flow, RA, SP};
+use crate::analysis::cfg::{Cfg, EdgeKind};
+use crate::isa::rlist_registers;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+///a0-a7, arguments of calls
+pub const ARGUMENTS : [u8; 8] = [10, 11, 12, 13, 14, 15, 16, 17];
+///a0, a1, return values
+pub const RESULTS : [u8; 2] = [10, 11];
+///sp, gp, tp and s0-s11, which are the same after call
+pub const PRESERVED : [u8; 15] = [2, 3, 4, 8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+///ra, t0-t6 and a0-a7, which could be changed by call
+pub const CLOBBERED : [u8; 16] = [1, 5, 6, 7, 10, 11, 12, 13, 14, 15, 16, 17, 28, 29, 30, 31];
+///fs0-fs11 are preserved by calls, other f registers are not
+pub const PRESERVED_F : [u8; 12] = [8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+
+///names of x registers in calling convention
+pub const ABI_NAMES : [&str; 32] = ["zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3",
+                                    "a4", "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11",
+                                    "t3", "t4", "t5", "t6"];
+
+const FFLAGS : u16 = 0x001;
+const FRM : u16 = 0x002;
+const JVT : u16 = 0x017;
+const VL : u16 = 0xc20;
+const VTYPE : u16 = 0xc21;
+
+///floating point instructions without rounding mode which raise exceptions, compares and min/max signal
+///invalid operation on NaNs. Names are without format suffix
+const RAISE_FLAGS : [&str; 10] = ["fmin", "fmax", "fminm", "fmaxm", "feq", "flt", "fle", "fleq", "fltq", "fcvtmod"];
+
+///rd of these compressed instructions is only written, of other ones it is source too
+const WRITE_ONLY : [&str; 8] = ["c.li", "c.lui", "c.mv", "c.lwsp", "c.ldsp", "c.lqsp", "c.addi4spn", "c.nop"];
+const SP_LOADS_STORES : [&str; 10] = ["c.lwsp", "c.ldsp", "c.lqsp", "c.flwsp", "c.fldsp", "c.swsp", "c.sdsp", "c.sqsp",
+                                      "c.fswsp", "c.fsdsp"];
+const C_LOADS : [&str; 13] = ["c.lw", "c.ld", "c.lq", "c.flw", "c.fld", "c.lwsp", "c.ldsp", "c.lqsp", "c.flwsp",
+                              "c.fldsp", "c.lbu", "c.lhu", "c.lh"];
+const C_STORES : [&str; 12] = ["c.sw", "c.sd", "c.sq", "c.fsw", "c.fsd", "c.swsp", "c.sdsp", "c.sqsp", "c.fswsp",
+                               "c.fsdsp", "c.sb", "c.sh"];
+
+///place which holds value: x, f or v register, CSR or memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Loc {
+    X(u8),
+    F(u8),
+    V(u8),
+    Csr(u16),
+    Memory,
+}
+
+impl Loc {
+    pub fn is_register(&self) -> bool {
+        matches!(self, Loc::X( _ ) | Loc::F( _ ) | Loc::V( _ ))
+    }
+}
+
+impl fmt::Display for Loc {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Loc::X( r ) => write!(f, "r{}", r),
+            Loc::F( r ) => write!(f, "f{}", r),
+            Loc::V( r ) => write!(f, "v{}", r),
+            Loc::Csr( c ) => write!(f, "csr {:#x}", c),
+            Loc::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+///locations read and written by instruction. Vector register groups are given by their first register
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Effects {
+    pub reads : BTreeSet<Loc>,
+    pub writes : BTreeSet<Loc>,
+}
+
+impl Effects {
+    fn read(&mut self, l : Loc) {
+        if l != Loc::X( 0 ) {
+            self.reads.insert(l);
+        }
+    }
+
+    fn write(&mut self, l : Loc) {
+        if l != Loc::X( 0 ) {
+            self.writes.insert(l);
+        }
+    }
+}
+
+///x register of s0-s11 by its number, like s0-s7 encoding of cm.mvsa01 and cm.mva01s
+fn sregister(v : u64) -> Loc {
+    match v {
+        0 | 1 => Loc::X( v as u8 + 8 ),
+        _ => Loc::X( v as u8 + 16 ),
+    }
+}
+
+///registers saved or restored by cm.push and cm.pop: ra and s0-s11
+fn rlist(v : u64) -> Vec<Loc> {
+    rlist_registers(v as u32).into_iter().map(Loc::X).collect()
+}
+
+///locations read and written by instruction
+pub fn effects(insn : &Insn) -> Effects {
+    let d = &insn.decoded;
+    let m = &d.mnemonic[..];
+    let mut e = Effects::default();
+    for (name, v, _) in &d.fields {
+        let r = *v as u8;
+        match &name[..] {
+            "rd" => e.write(Loc::X( r )),
+            "rdp" => e.write(Loc::X( r + 8 )),
+            "rs1" | "rs2" | "rs3" => e.read(Loc::X( r )),
+            "rs1p" | "rs2p" => e.read(Loc::X( r + 8 )),
+            "frd" => e.write(Loc::F( r )),
+            "frdp" => e.write(Loc::F( r + 8 )),
+            "frs1" | "frs2" | "frs3" => e.read(Loc::F( r )),
+            "frs2p" => e.read(Loc::F( r + 8 )),
+            "vd" => e.write(Loc::V( r )),
+            "vs1" | "vs2" | "vs3" => e.read(Loc::V( r )),
+            "vm" if *v == 0 => e.read(Loc::V( 0 )),
+            "rm" if *v == 7 => e.read(Loc::Csr( FRM )),
+            _ => {},
+        }
+    }
+    if d.value("rm").is_some() || m.split('.').next().is_some_and(|b| RAISE_FLAGS.contains(&b)) {
+        e.write(Loc::Csr( FFLAGS ));
+    }
+
+    //compressed instructions with the same source and destination
+    if m.starts_with("c.") && !WRITE_ONLY.contains(&m) && d.value("rs1").is_none() && d.value("rs1p").is_none() {
+        if let Some( r ) = d.register("rd") {
+            e.read(Loc::X( r as u8 ));
+        }
+    }
+    //vector multiply-add instructions accumulate into destination
+    if m.starts_with('v') && ["macc", "madd", "msac", "msub"].iter().any(|s| m.contains(s)) {
+        if let Some( r ) = d.value("vd") {
+            e.read(Loc::V( r as u8 ));
+        }
+    }
+    if d.fields.iter().any(|(n, _, _)| n.starts_with('v')) {
+        e.read(Loc::Csr( VL ));
+        e.read(Loc::Csr( VTYPE ));
+    }
+
+    match m {
+        "c.addi16sp" => {
+            e.read(Loc::X( SP ));
+            e.write(Loc::X( SP ));
+        },
+        "c.addi4spn" => e.read(Loc::X( SP )),
+        _ if SP_LOADS_STORES.contains(&m) => e.read(Loc::X( SP )),
+        "c.jal" | "c.jalr" => e.write(Loc::X( RA )),
+        "cm.push" => {
+            e.read(Loc::X( SP ));
+            e.write(Loc::X( SP ));
+            for r in rlist(d.value("rlist").unwrap_or(0)) {
+                e.read(r);
+            }
+            e.write(Loc::Memory);
+        },
+        "cm.pop" | "cm.popret" | "cm.popretz" => {
+            e.read(Loc::X( SP ));
+            e.write(Loc::X( SP ));
+            for r in rlist(d.value("rlist").unwrap_or(0)) {
+                e.write(r);
+            }
+            if m == "cm.popretz" {
+                e.write(Loc::X( 10 ));
+            }
+            e.read(Loc::Memory);
+        },
+        "cm.mvsa01" => {
+            e.read(Loc::X( 10 ));
+            e.read(Loc::X( 11 ));
+            e.write(sregister(d.value("r1s").unwrap_or(0)));
+            e.write(sregister(d.value("r2s").unwrap_or(0)));
+        },
+        "cm.mva01s" => {
+            e.read(sregister(d.value("r1s").unwrap_or(0)));
+            e.read(sregister(d.value("r2s").unwrap_or(0)));
+            e.write(Loc::X( 10 ));
+            e.write(Loc::X( 11 ));
+        },
+        "cm.jt" | "cm.jalt" => {
+            e.read(Loc::Csr( JVT ));
+            e.read(Loc::Memory);
+            if m == "cm.jalt" {
+                e.write(Loc::X( RA ));
+            }
+        },
+        "vsetvli" | "vsetivli" | "vsetvl" => {
+            e.reads.remove(&Loc::Csr( VL ));
+            e.reads.remove(&Loc::Csr( VTYPE ));
+            e.write(Loc::Csr( VL ));
+            e.write(Loc::Csr( VTYPE ));
+        },
+        _ => {},
+    }
+
+    //csrrw does not read CSR if rd is x0, csrrs and csrrc do not write it if source is zero
+    if let Some( csr ) = d.value("csr") {
+        let csr = Loc::Csr( csr as u16 );
+        let source = d.value("rs1").or_else(|| d.value("imm")).unwrap_or(0);
+        match m {
+            "csrrw" | "csrrwi" => {
+                e.write(csr);
+                if d.value("rd") != Some( 0 ) {
+                    e.read(csr);
+                }
+            },
+            _ => {
+                e.read(csr);
+                if source != 0 {
+                    e.write(csr);
+                }
+            },
+        }
+    }
+
+    //memory access by major opcode, vector loads and stores are in LOAD-FP and STORE-FP
+    match insn.len {
+        2 if C_LOADS.contains(&m) => e.read(Loc::Memory),
+        2 if C_STORES.contains(&m) => e.write(Loc::Memory),
+        4 => match insn.word & 0x7f {
+            0x03 | 0x07 => e.read(Loc::Memory),
+            0x23 | 0x27 => e.write(Loc::Memory),
+            0x2f if m.starts_with("lr.") => e.read(Loc::Memory),
+            0x2f if m.starts_with("sc.") => e.write(Loc::Memory),
+            0x2f => {
+                e.read(Loc::Memory);
+                e.write(Loc::Memory);
+            },
+            _ => {},
+        },
+        _ => {},
+    }
+    e
+}
+
+fn set(x : &[u8], f : &[u8]) -> BTreeSet<Loc> {
+    x.iter().map(|r| Loc::X( *r )).chain(f.iter().map(|r| Loc::F( *r ))).collect()
+}
+
+///registers used by caller after return: results and preserved registers
+fn at_return() -> BTreeSet<Loc> {
+    let mut result = set(&RESULTS, &RESULTS);
+    result.extend( set(&PRESERVED, &PRESERVED_F) );
+    result
+}
+
+///registers used after jump out of function, which is tail call: arguments, return address and preserved registers
+fn at_tail() -> BTreeSet<Loc> {
+    let mut result = set(&ARGUMENTS, &ARGUMENTS);
+    result.extend( set(&PRESERVED, &PRESERVED_F) );
+    result.insert(Loc::X( RA ));
+    result
+}
+
+///all x and f registers, environment could use any of them
+fn everything() -> BTreeSet<Loc> {
+    let all : Vec<u8> = (0..32).collect();
+    set(&all[1..], &all)
+}
+
+///live registers before instruction, given live ones after it. Calls read arguments and change registers
+///which are not preserved
+fn transfer(insn : &Insn, after : &BTreeSet<Loc>) -> BTreeSet<Loc> {
+    let e = effects(insn);
+    let mut live : BTreeSet<Loc> = after.difference(&e.writes).cloned().collect();
+    if matches!(insn.flow, Flow::Call( _ ) | Flow::IndirectCall) {
+        let clobbered = set(&CLOBBERED, &(0..32).filter(|r| !PRESERVED_F.contains(r)).collect::<Vec<u8>>());
+        live = live.difference(&clobbered).cloned().collect();
+        live.extend( set(&ARGUMENTS, &ARGUMENTS) );
+    }
+    live.extend( e.reads.into_iter().filter(|l| l.is_register()) );
+    live
+}
+
+///registers live at starts and ends of blocks, and after each instruction
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Liveness {
+    pub live_in : BTreeMap<u64, BTreeSet<Loc>>,
+    pub live_out : BTreeMap<u64, BTreeSet<Loc>>,
+    pub after : BTreeMap<u64, BTreeSet<Loc>>,
+}
+
+///liveness of x, f and v registers in function by backward dataflow over blocks. Registers of calling
+///convention are live at returns and tail jumps, all registers are live at ecall and trap returns
+pub fn liveness(cfg : &Cfg) -> Liveness {
+    //registers live at exit of block to outside of function
+    let exits : BTreeMap<u64, BTreeSet<Loc>> = cfg.blocks.values().map(|b| {
+        let last = b.insns.last().map(|i| i.flow);
+        let mut exit = match last {
+            Some( Flow::Return ) => at_return(),
+            Some( Flow::Stop ) => everything(),
+            _ => BTreeSet::new(),
+        };
+        let leaves = b.edges.iter()
+            .filter(|e| e.kind != EdgeKind::Call)
+            .any(|e| e.to.is_none_or(|t| !cfg.blocks.contains_key(&t)));
+        let ends = b.edges.iter().all(|e| e.kind == EdgeKind::Call)
+            && last.is_some_and(|f| f.falls_through());
+        if leaves || ends {
+            exit.extend( at_tail() );
+        }
+        (b.start, exit)
+    }).collect();
+
+    let mut result = Liveness::default();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in cfg.blocks.values().rev() {
+            let mut live = exits[&b.start].clone();
+            for e in b.edges.iter().filter(|e| e.kind != EdgeKind::Call) {
+                if let Some( l ) = e.to.and_then(|t| result.live_in.get(&t)) {
+                    live.extend( l.iter().cloned() );
+                }
+            }
+            result.live_out.insert(b.start, live.clone());
+            for i in b.insns.iter().rev() {
+                result.after.insert(i.addr, live.clone());
+                live = transfer(i, &live);
+            }
+            if result.live_in.get(&b.start) != Some( &live ) {
+                result.live_in.insert(b.start, live);
+                changed = true;
+            }
+        }
+    }
+    result
+}
+
+///locations separated by spaces, - if there are none
+pub fn show(set : &BTreeSet<Loc>) -> String {
+    if set.is_empty() {
+        return String::from("-");
+    }
+    set.iter().map(|l| l.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::{with_tables, with_cfgs};
+
+    fn decoded(code : &[u8]) -> Vec<Effects> {
+        with_tables(|t| {
+            let mut offset = 0;
+            let mut result = Vec::new();
+            while offset < code.len() {
+                let insn = t.decode_at(code, offset, offset as u64).unwrap();
+                offset += insn.len;
+                result.push( effects(&insn) );
+            }
+            result
+        })
+    }
+
+    fn locs(list : &[Loc]) -> BTreeSet<Loc> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn implicit() {
+        //c.jal 0x10; c.addi16sp sp, 32; c.addi a0, 1; c.lwsp a1, 4(sp); sw a1, 0(a0);
+        //csrrs a0, mstatus, x0; csrrw x0, mtvec, a1; fadd.s fa0, fa1, fa2; amoadd.w a0, a1, (a2)
+        let code = [0x01, 0x28, 0x05, 0x61, 0x05, 0x05, 0x92, 0x45, 0x23, 0x20, 0xb5, 0x00,
+                    0x73, 0x25, 0x00, 0x30, 0x73, 0x90, 0x55, 0x30, 0x53, 0xf5, 0xc5, 0x00,
+                    0x2f, 0x25, 0xb6, 0x00];
+        let e = decoded(&code);
+        assert_eq!(e[0].writes, locs(&[Loc::X( 1 )]));
+        assert_eq!((e[1].reads.clone(), e[1].writes.clone()), (locs(&[Loc::X( 2 )]), locs(&[Loc::X( 2 )])));
+        assert_eq!(e[2].reads, locs(&[Loc::X( 10 )]));
+        assert_eq!((e[3].reads.clone(), e[3].writes.clone()), (locs(&[Loc::X( 2 ), Loc::Memory]), locs(&[Loc::X( 11 )])));
+        assert_eq!((e[4].reads.clone(), e[4].writes.clone()), (locs(&[Loc::X( 10 ), Loc::X( 11 )]), locs(&[Loc::Memory])));
+        assert_eq!((e[5].reads.clone(), e[5].writes.clone()), (locs(&[Loc::Csr( 0x300 )]), locs(&[Loc::X( 10 )])));
+        assert_eq!((e[6].reads.clone(), e[6].writes.clone()), (locs(&[Loc::X( 11 )]), locs(&[Loc::Csr( 0x305 )])));
+        assert_eq!(e[7].reads, locs(&[Loc::F( 11 ), Loc::F( 12 ), Loc::Csr( 0x002 )]));
+        assert_eq!(e[7].writes, locs(&[Loc::F( 10 ), Loc::Csr( 0x001 )]));
+        assert_eq!(e[8].reads, locs(&[Loc::X( 11 ), Loc::X( 12 ), Loc::Memory]));
+        assert_eq!(e[8].writes, locs(&[Loc::X( 10 ), Loc::Memory]));
+    }
+
+    #[test]
+    fn flags() {
+        //fmin.s fa0, fa1, fa2; flt.s a0, fa1, fa2; fsgnj.s fa0, fa1, fa2
+        let code = [0x53, 0x85, 0xc5, 0x28, 0x53, 0x95, 0xc5, 0xa0, 0x53, 0x85, 0xc5, 0x20];
+        let e = decoded(&code);
+        assert_eq!(e[0].writes, locs(&[Loc::F( 10 ), Loc::Csr( 0x001 )]));
+        assert_eq!(e[1].writes, locs(&[Loc::X( 10 ), Loc::Csr( 0x001 )]));
+        assert_eq!(e[2].writes, locs(&[Loc::F( 10 )]));
+    }
+
+    #[test]
+    fn live() {
+        //0x0: c.beqz a1, 0x6; c.li a2, 5; c.j 0x8; 0x6: c.li a2, 7; 0x8: add a0, a0, a2; c.jr ra
+        let code = [0x99, 0xc1, 0x15, 0x46, 0x11, 0xa0, 0x1d, 0x46, 0x33, 0x05, 0xc5, 0x00, 0x82, 0x80];
+        let l = with_cfgs("rv32gc", &code, 0, &[0], |_, g| liveness(&g[0]));
+        let live_in = &l.live_in[&0];
+        assert!(live_in.contains(&Loc::X( 10 )) && live_in.contains(&Loc::X( 11 )) && live_in.contains(&Loc::X( 1 )));
+        assert!(!live_in.contains(&Loc::X( 12 )) && !live_in.contains(&Loc::X( 13 )));
+        assert!(l.live_in[&8].contains(&Loc::X( 12 )));
+        assert!(!l.after[&8].contains(&Loc::X( 12 )) && l.after[&8].contains(&Loc::X( 10 )));
+        assert_eq!(l.live_out[&8], at_return());
+    }
+}
@@ -0,0 +1,100 @@
+//! Control flow effect of decoded instructions
+
+use crate::decoder::Decoded;
+
+///what happens after instruction: it falls through to the next one, transfers control to
+///known target, or to unknown one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    Jump(u64),
+    ///conditional branch, falls through if not taken
+    Branch(u64),
+    ///call is assumed to return, so it falls through
+    Call(u64),
+    IndirectJump,
+    IndirectCall,
+    Return,
+    ///ecall, ebreak and returns from trap, control is passed to environment
+    Stop,
+}
+
+impl Flow {
+    ///true if next instruction could be executed after this one
+    pub fn falls_through(&self) -> bool {
+        matches!(self, Flow::Next | Flow::Branch( _ ) | Flow::Call( _ ) | Flow::IndirectCall)
+    }
+
+    ///known target of control transfer
+    pub fn target(&self) -> Option<u64> {
+        match self {
+            Flow::Jump( t ) | Flow::Branch( t ) | Flow::Call( t ) => Some( *t ),
+            _ => None,
+        }
+    }
+}
+
+const BRANCHES : [&str; 8] = ["beq", "bne", "blt", "bge", "bltu", "bgeu", "c.beqz", "c.bnez"];
+const STOPS : [&str; 8] = ["ecall", "ebreak", "c.ebreak", "mret", "sret", "mnret", "dret", "uret"];
+///ra register, jumps through it are returns
+pub const RA : u8 = 1;
+///sp register
+pub const SP : u8 = 2;
+///t0 register, alternate link register
+pub const T0 : u8 = 5;
+
+///control flow effect of instruction at given address
+pub fn flow(addr : u64, d : &Decoded) -> Flow {
+    let target = || addr.wrapping_add(d.signed("imm").unwrap_or(0) as u64);
+    let m = &d.mnemonic[..];
+    match m {
+        "jal" if d.value("rd") == Some( 0 ) => Flow::Jump( target() ),
+        "jal" | "c.jal" => Flow::Call( target() ),
+        "c.j" => Flow::Jump( target() ),
+        _ if BRANCHES.contains(&m) => Flow::Branch( target() ),
+        "jalr" if d.value("rd") != Some( 0 ) => Flow::IndirectCall,
+        "jalr" if is_link(d) && d.value("imm") == Some( 0 ) => Flow::Return,
+        "c.jr" if is_link(d) => Flow::Return,
+        "jalr" | "c.jr" | "cm.jt" => Flow::IndirectJump,
+        "c.jalr" | "cm.jalt" => Flow::IndirectCall,
+        "cm.popret" | "cm.popretz" => Flow::Return,
+        _ if STOPS.contains(&m) => Flow::Stop,
+        _ => Flow::Next,
+    }
+}
+
+///true if rs1 is one of link registers, jump through it without offset is return
+fn is_link(d : &Decoded) -> bool {
+    matches!(d.value("rs1"), Some( r ) if r == RA as u64 || r == T0 as u64)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::{ISARV32C, ISARV32IMA};
+    use crate::decoder::decode_details;
+
+    #[test]
+    fn flows() {
+        let isa = ISARV32IMA::new();
+        let f = |v : u32| flow(0x1000, &decode_details(v, &isa).unwrap());
+        assert_eq!(f(0x0100006f), Flow::Jump( 0x1010 ));
+        assert_eq!(f(0xff9ff0ef), Flow::Call( 0xff8 ));
+        assert_eq!(f(0x00029863), Flow::Branch( 0x1010 ));
+        assert_eq!(f(0x00008067), Flow::Return);
+        assert_eq!(f(0x00028067), Flow::Return);
+        assert_eq!(f(0x00408067), Flow::IndirectJump);
+        assert_eq!(f(0x00050067), Flow::IndirectJump);
+        assert_eq!(f(0x000280e7), Flow::IndirectCall);
+        assert_eq!(f(0x00000073), Flow::Stop);
+        assert_eq!(f(0x00500513), Flow::Next);
+
+        let isa = ISARV32C::new();
+        let f = |v : u16| flow(0x1000, &decode_details(v, &isa).unwrap());
+        assert_eq!(f(0xbfe5), Flow::Jump( 0xff8 ));
+        assert_eq!(f(0x8082), Flow::Return);
+        assert_eq!(f(0x9282), Flow::IndirectCall);
+        assert!(Flow::Branch( 0 ).falls_through() && !Flow::Jump( 0 ).falls_through());
+    }
+}
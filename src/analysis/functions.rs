@@ -0,0 +1,205 @@
+//! Function boundaries: starts are found from entry points, call targets and prologues,
+//! ends and frame sizes from instructions reachable inside of function
+
+use crate::analysis::traverse::{traverse, Insn, Tables};
+use crate::analysis::flow::{Flow, RA, SP};
+use crate::analysis::image::Image;
+use crate::analysis::jumptable::jump_tables;
+use crate::isa::{Xlen, stack_adj};
+use std::collections::{BTreeMap, BTreeSet};
+
+///function found by analysis, end is the end of its part which starts at start address,
+///parts placed after other functions (like shared tails) are not counted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name : String,
+    pub start : u64,
+    pub end : u64,
+    ///size of stack frame allocated by prologue
+    pub frame : Option<u64>,
+    ///ra is saved on stack, so function calls other ones
+    pub saves_ra : bool,
+    pub returns : bool,
+}
+
+///decrement of sp by addi sp, sp, -N, c.addi16sp, c.addi sp, -N or cm.push
+pub fn sp_decrement(insn : &Insn, xlen : Xlen) -> Option<u64> {
+    let d = &insn.decoded;
+    if d.mnemonic == "cm.push" {
+        return Some( stack_adj(d.value("rlist")? as u32, d.value("spimm")? as u32, xlen) as u64 );
+    }
+    let adjusts = match &d.mnemonic[..] {
+        "addi" => d.value("rd") == Some( SP as u64 ) && d.value("rs1") == Some( SP as u64 ),
+        "c.addi" => d.value("rd") == Some( SP as u64 ),
+        "c.addi16sp" => true,
+        _ => false,
+    };
+    match d.signed("imm") {
+        Some( imm ) if adjusts && imm < 0 => Some( imm.unsigned_abs() ),
+        _ => None,
+    }
+}
+
+///ra is stored on stack by sw, sd, c.swsp, c.sdsp or cm.push, whose register list always has ra
+pub fn saves_ra(insn : &Insn) -> bool {
+    let d = &insn.decoded;
+    match &d.mnemonic[..] {
+        "sw" | "sd" => d.value("rs2") == Some( RA as u64 ) && d.value("rs1") == Some( SP as u64 ),
+        "c.swsp" | "c.sdsp" => d.value("rs2") == Some( RA as u64 ),
+        "cm.push" => true,
+        _ => false,
+    }
+}
+
+///sp decrement followed by ra save, or cm.push which does both
+fn is_prologue(insn : &Insn, next : Option<&Insn>, xlen : Xlen) -> bool {
+    sp_decrement(insn, xlen).is_some() && (saves_ra(insn) || next.is_some_and(saves_ra))
+}
+
+///addresses of prologues in bytes not reached by traversal: sp decrement followed by ra save
+fn prologues(code : &[u8], base : u64, found : &BTreeMap<u64, Insn>, tables : &Tables) -> Vec<u64> {
+    let mut result = Vec::new();
+    let end = base + code.len() as u64;
+    let mut gaps = Vec::new();
+    let mut from = base;
+    for insn in found.values() {
+        gaps.push( (from, insn.addr) );
+        from = insn.addr + insn.len as u64;
+    }
+    gaps.push( (from, end) );
+
+    for (from, to) in gaps {
+        let decode = |addr : u64| tables.decode_at(code, (addr - base) as usize, addr)
+            .filter(|i| addr + i.len as u64 <= to);
+        let mut addr = from + from % 2;
+        while addr < to {
+            if let Some( first ) = decode(addr).filter(|i| sp_decrement(i, tables.xlen).is_some()) {
+                if is_prologue(&first, decode(addr + first.len as u64).as_ref(), tables.xlen) {
+                    result.push( addr );
+                }
+            }
+            addr += 2;
+        }
+    }
+    result
+}
+
+///traverse code from entry points, then from prologues found in bytes which were not reached and
+///from targets of jump tables read from image, until nothing new is found
+pub fn discover(code : &[u8], base : u64, entries : &[u64], tables : &Tables, image : &Image) -> BTreeMap<u64, Insn> {
+    let mut entries = entries.to_vec();
+    let mut indirect = BTreeMap::new();
+    loop {
+        let found = traverse(code, base, &entries, &indirect, tables);
+        let more = prologues(code, base, &found, tables);
+        let mut changed = !more.is_empty();
+        entries.extend(more);
+        let f = functions(&found, &entries, &BTreeMap::new(), tables.xlen);
+        for t in jump_tables(&found, &f, image, tables.xlen) {
+            if t.targets.iter().all(|a| *a >= base && *a < base + code.len() as u64) {
+                changed |= indirect.insert(t.jump, t.targets).is_none();
+            }
+        }
+        if !changed {
+            return found;
+        }
+    }
+}
+
+///functions of found instructions. Starts are entry points, call targets and prologues which are not
+///reached by falling through. Given names are used for starts, other functions are named sub_<address>
+pub fn functions(insns : &BTreeMap<u64, Insn>, entries : &[u64], names : &BTreeMap<u64, String>, xlen : Xlen) -> Vec<Function> {
+    let mut starts : BTreeSet<u64> = entries.iter().cloned().filter(|e| insns.contains_key(e)).collect();
+    let mut prev : Option<&Insn> = None;
+    for insn in insns.values() {
+        if let Flow::Call( target ) = insn.flow {
+            if insns.contains_key(&target) {
+                starts.insert(target);
+            }
+        }
+        let falls = prev.is_some_and(|p| p.addr + p.len as u64 == insn.addr && p.flow.falls_through());
+        let next = insns.get(&(insn.addr + insn.len as u64));
+        if !falls && is_prologue(insn, next, xlen) {
+            starts.insert(insn.addr);
+        }
+        prev = Some( insn );
+    }
+
+    starts.iter().map(|start| {
+        let limit = starts.range(start + 1..).next().cloned().unwrap_or(u64::MAX);
+        let body = body(insns, *start, &starts);
+        let end = body.iter().filter(|i| i.addr < limit).map(|i| i.addr + i.len as u64).max().unwrap_or(*start);
+        let name = names.get(start).cloned().unwrap_or_else(|| format!("sub_{:08x}", start));
+        Function {
+            name,
+            start : *start,
+            end,
+            frame : body.iter().find_map(|i| sp_decrement(i, xlen)),
+            saves_ra : body.iter().any(|i| saves_ra(i)),
+            returns : body.iter().any(|i| i.flow == Flow::Return),
+        }
+    }).collect()
+}
+
+///instructions reachable from start without calls, including targets of indirect jumps. Jumps to
+///other functions are tail calls and are not followed. Instructions are sorted by address
+pub fn body<'a>(insns : &'a BTreeMap<u64, Insn>, start : u64, starts : &BTreeSet<u64>) -> Vec<&'a Insn> {
+    let mut visited = BTreeMap::new();
+    let mut work = vec![start];
+    while let Some( addr ) = work.pop() {
+        if visited.contains_key(&addr) || (addr != start && starts.contains(&addr)) {
+            continue;
+        }
+        let insn = match insns.get(&addr) {
+            None => continue,
+            Some( insn ) => insn,
+        };
+        if insn.flow.falls_through() {
+            work.push( addr + insn.len as u64 );
+        }
+        if let Flow::Jump( t ) | Flow::Branch( t ) = insn.flow {
+            work.push( t );
+        }
+        work.extend( insn.targets.iter() );
+        visited.insert(addr, insn);
+    }
+    visited.into_values().collect()
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::analysis::traverse::test::{with_tables, with_cfgs};
+
+    ///0x0: jal ra, 0x8; 0x4: ecall;
+    ///0x8: c.addi16sp -16; c.swsp ra, 12; c.beqz a0, 0x10; c.j 0x2c; 0x10: c.lwsp ra, 12; c.addi16sp 16; c.jr ra;
+    ///0x16: .half 0; 0x18: addi sp, sp, -32; sw ra, 28(sp); lw ra, 28(sp); addi sp, sp, 32; ret; 0x2c: c.jr ra
+    pub const CODE : [u8; 46] = [0xef, 0x00, 0x80, 0x00, 0x73, 0x00, 0x00, 0x00, 0x7d, 0x71, 0x06, 0xc6,
+                             0x11, 0xc1, 0x39, 0xa8, 0xb2, 0x40, 0x41, 0x61, 0x82, 0x80, 0x00, 0x00,
+                             0x13, 0x01, 0x01, 0xfe, 0x23, 0x2e, 0x11, 0x00, 0x83, 0x20, 0xc1, 0x01,
+                             0x13, 0x01, 0x01, 0x02, 0x67, 0x80, 0x00, 0x00, 0x82, 0x80];
+
+    #[test]
+    fn stripped() {
+        let insns = with_tables(|t| discover(&CODE, 0, &[0], t, &Image::new(0, &CODE)));
+        assert!(insns.contains_key(&0x18) && insns.contains_key(&0x28));
+        assert!(!insns.contains_key(&0x16));
+
+        let mut names = BTreeMap::new();
+        names.insert(0, String::from("_start"));
+        let f = functions(&insns, &[0], &names, Xlen::X32);
+        assert_eq!(f.len(), 3);
+        assert_eq!(f[0], Function { name : String::from("_start"), start : 0, end : 8, frame : None, saves_ra : false, returns : false });
+        assert_eq!(f[1], Function { name : String::from("sub_00000008"), start : 8, end : 0x16, frame : Some( 16 ), saves_ra : true, returns : true });
+        assert_eq!(f[2], Function { name : String::from("sub_00000018"), start : 0x18, end : 0x2c, frame : Some( 32 ), saves_ra : true, returns : true });
+    }
+
+    #[test]
+    fn push_pop() {
+        //0x0: cm.push {ra, s0-s1}, -32; c.mv s0, a0; c.add a0, s0; cm.popret {ra, s0-s1}, 32
+        let code = [0x66, 0xb8, 0x2a, 0x84, 0x22, 0x95, 0x66, 0xbe];
+        let f : Vec<Function> = with_cfgs("rv32im_zca_zcmp", &code, 0, &[], |_, g| g.iter().map(|g| g.function.clone()).collect());
+        assert_eq!(f, vec![Function { name : String::from("sub_00000000"), start : 0, end : 8, frame : Some( 32 ), saves_ra : true, returns : true }]);
+    }
+}
@@ -0,0 +1,48 @@
+//! Memory image of loaded sections, data referenced by code is read from it
+
+///parts of memory with their addresses
+#[derive(Debug, Clone, Default)]
+pub struct Image<'a> {
+    parts : Vec<(u64, &'a [u8])>,
+}
+
+impl<'a> Image<'a> {
+    ///image of single part, like raw binary
+    pub fn new(addr : u64, bytes : &'a [u8]) -> Image<'a> {
+        Image { parts : vec![(addr, bytes)] }
+    }
+
+    pub fn add(&mut self, addr : u64, bytes : &'a [u8]) {
+        self.parts.push( (addr, bytes) );
+    }
+
+    ///bytes at address, they should be inside of one part
+    pub fn read(&self, addr : u64, len : usize) -> Option<&'a [u8]> {
+        self.parts.iter().find_map(|(a, bytes)| {
+            let offset = addr.checked_sub(*a)? as usize;
+            bytes.get(offset..offset.checked_add(len)?)
+        })
+    }
+
+    ///little-endian number of len bytes, up to 8
+    pub fn read_uint(&self, addr : u64, len : usize) -> Option<u64> {
+        let bytes = self.read(addr, len.min(8))?;
+        Some( bytes.iter().rev().fold(0, |v, b| (v << 8) | *b as u64) )
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let mut image = Image::new(0x1000, &[1, 2, 3, 4]);
+        image.add(0x2000, &[5, 6]);
+        assert_eq!(image.read_uint(0x1001, 2), Some( 0x0302 ));
+        assert_eq!(image.read_uint(0x2000, 2), Some( 0x0605 ));
+        assert_eq!(image.read(0x1003, 2), None);
+        assert_eq!(image.read(0xfff, 1), None);
+    }
+}
@@ -0,0 +1,410 @@
+//! Lifting of instructions into small IR, by semantics given in instruction tables. Results of operations
+//! are temporaries which are assigned once, x registers are read and written explicitly. Instructions
+//! without described semantics are intrinsics with locations they read and write
+//!
+//! Values are x registers only: F, D and V instructions, including their loads, stores and moves
+//! to and from x registers, are intrinsics too
+
+use crate::primitives::{Sem, Op, Unary, Cond, CsrOp, Operand};
+use crate::analysis::traverse::Insn;
+use crate::analysis::flow::Flow;
+use crate::analysis::defuse::{effects, show, Loc};
+use crate::analysis::flow::SP;
+use crate::analysis::cfg::Cfg;
+use crate::isa::{Xlen, stack_adj, rlist_registers};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+///operand of IR statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Reg(u8),
+    Temp(u32),
+    Const(u64),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Reg( r ) => write!(f, "r{}", r),
+            Value::Temp( t ) => write!(f, "t{}", t),
+            Value::Const( c ) => write!(f, "{:#x}", c),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt {
+    ///dst = a op b, word operation works on low 32 bits and sign extends result
+    Assign { dst : u32, op : Op, a : Value, b : Value, word : bool },
+    ///dst = op a, word operation works on low 32 bits
+    Unary { dst : u32, op : Unary, a : Value, word : bool },
+    ///dst = low bits of a, sign or zero extended
+    Extend { dst : u32, a : Value, bits : u8, signed : bool },
+    Load { dst : u32, addr : Value, bytes : u8, signed : bool },
+    Store { addr : Value, value : Value, bytes : u8 },
+    ///write of x register
+    Set { reg : u8, value : Value },
+    Branch { cond : Cond, a : Value, b : Value, target : u64 },
+    Jump { target : Value },
+    Call { target : Value },
+    Return,
+    CsrRead { dst : u32, csr : u16 },
+    CsrWrite { csr : u16, value : Value },
+    ///reservation of address for store conditional
+    Reserve { addr : Value },
+    ///value is stored if address is reserved, dst is 0 on success
+    StoreConditional { dst : u32, addr : Value, value : Value, bytes : u8 },
+    ///instruction without described semantics
+    Intrinsic { name : String, reads : BTreeSet<Loc>, writes : BTreeSet<Loc> },
+}
+
+impl fmt::Display for Stmt {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stmt::Assign { dst, op, a, b, word } => write!(f, "t{} = {}{} {}, {}", dst, op, if *word { "w" } else { "" }, a, b),
+            Stmt::Unary { dst, op, a, word } => write!(f, "t{} = {}{} {}", dst, op, if *word { "w" } else { "" }, a),
+            Stmt::Extend { dst, a, bits, signed } => write!(f, "t{} = {}ext{} {}", dst, if *signed { "s" } else { "z" }, bits, a),
+            Stmt::Load { dst, addr, bytes, signed } =>
+                write!(f, "t{} = load.{}{} [{}]", dst, if *signed { "s" } else { "u" }, *bytes as u32 * 8, addr),
+            Stmt::Store { addr, value, bytes } => write!(f, "store.{} [{}], {}", *bytes as u32 * 8, addr, value),
+            Stmt::Set { reg, value } => write!(f, "r{} = {}", reg, value),
+            Stmt::Branch { cond, a, b, target } => write!(f, "if {} {} {} goto {:#x}", a, cond, b, target),
+            Stmt::Jump { target } => write!(f, "goto {}", target),
+            Stmt::Call { target } => write!(f, "call {}", target),
+            Stmt::Return => write!(f, "return"),
+            Stmt::CsrRead { dst, csr } => write!(f, "t{} = csr[{:#x}]", dst, csr),
+            Stmt::CsrWrite { csr, value } => write!(f, "csr[{:#x}] = {}", csr, value),
+            Stmt::Reserve { addr } => write!(f, "reserve [{}]", addr),
+            Stmt::StoreConditional { dst, addr, value, bytes } =>
+                write!(f, "t{} = store.cond.{} [{}], {}", dst, *bytes as u32 * 8, addr, value),
+            Stmt::Intrinsic { name, reads, writes } => write!(f, "intrinsic {} reads: {}; writes: {}", name, show(reads), show(writes)),
+        }
+    }
+}
+
+///lifts instructions of function, temporaries are numbered through all of them
+pub struct Lifter {
+    temps : u32,
+    mask : u64,
+    xlen : Xlen,
+}
+
+impl Lifter {
+    pub fn new(xlen : Xlen) -> Lifter {
+        Lifter { temps : 0, mask : xlen.mask(), xlen }
+    }
+
+    fn temp(&mut self) -> u32 {
+        self.temps += 1;
+        self.temps - 1
+    }
+
+    ///value of operand, x0 is constant
+    fn value(&self, insn : &Insn, o : Operand) -> Value {
+        let d = &insn.decoded;
+        let reg = |r : Option<u64>| match r {
+            Some( r ) if r != 0 => Value::Reg( r as u8 ),
+            _ => Value::Const( 0 ),
+        };
+        match o {
+            Operand::Reg( name ) => reg(d.register(name)),
+            Operand::X( r ) => reg(Some( r as u64 )),
+            Operand::Imm( name ) => Value::Const( d.signed(name).unwrap_or(0) as u64 & self.mask ),
+            Operand::Uimm( name ) => Value::Const( d.value(name).unwrap_or(0) ),
+            Operand::Const( c ) => Value::Const( c as u64 & self.mask ),
+            Operand::Pc => Value::Const( insn.addr ),
+        }
+    }
+
+    ///write of register of operand, writes of x0 are dropped
+    fn set(&self, out : &mut Vec<Stmt>, insn : &Insn, o : Operand, value : Value) {
+        if let Value::Reg( reg ) = self.value(insn, o) {
+            out.push( Stmt::Set { reg, value } );
+        }
+    }
+
+    fn assign(&mut self, out : &mut Vec<Stmt>, op : Op, a : Value, b : Value) -> Value {
+        let dst = self.temp();
+        out.push( Stmt::Assign { dst, op, a, b, word : false } );
+        Value::Temp( dst )
+    }
+
+    ///base + offset, base alone if offset is zero
+    fn address(&mut self, out : &mut Vec<Stmt>, insn : &Insn, base : Operand, offset : Operand) -> Value {
+        let base = self.value(insn, base);
+        match self.value(insn, offset) {
+            Value::Const( 0 ) => base,
+            offset => self.assign(out, Op::Add, base, offset),
+        }
+    }
+
+    ///statements of instruction
+    pub fn lift(&mut self, insn : &Insn) -> Vec<Stmt> {
+        let mut out = Vec::new();
+        let rd = Operand::Reg("rd");
+        let rs1 = Operand::Reg("rs1");
+        let Some( sem ) = insn.decoded.sem else {
+            let e = effects(insn);
+            out.push( Stmt::Intrinsic { name : insn.decoded.mnemonic.clone(), reads : e.reads, writes : e.writes } );
+            if insn.flow == Flow::Return {
+                out.push( Stmt::Return );
+            }
+            return out;
+        };
+        match sem {
+            Sem::Nop => {},
+            Sem::Op { op, dst, a, b, word } => {
+                let (a, b) = (self.value(insn, a), self.value(insn, b));
+                let t = self.temp();
+                out.push( Stmt::Assign { dst : t, op, a, b, word } );
+                self.set(&mut out, insn, dst, Value::Temp( t ));
+            },
+            Sem::Unary { op, dst, a, word } => {
+                let a = self.value(insn, a);
+                let t = self.temp();
+                out.push( Stmt::Unary { dst : t, op, a, word } );
+                self.set(&mut out, insn, dst, Value::Temp( t ));
+            },
+            Sem::Extend { dst, a, bits, signed } => {
+                let a = self.value(insn, a);
+                let t = self.temp();
+                out.push( Stmt::Extend { dst : t, a, bits, signed } );
+                self.set(&mut out, insn, dst, Value::Temp( t ));
+            },
+            Sem::Load { dst, base, offset, bytes, signed } => {
+                let addr = self.address(&mut out, insn, base, offset);
+                let t = self.temp();
+                out.push( Stmt::Load { dst : t, addr, bytes, signed } );
+                self.set(&mut out, insn, dst, Value::Temp( t ));
+            },
+            Sem::Store { src, base, offset, bytes } => {
+                let addr = self.address(&mut out, insn, base, offset);
+                out.push( Stmt::Store { addr, value : self.value(insn, src), bytes } );
+            },
+            Sem::Branch { cond, a, b } => {
+                let (a, b) = (self.value(insn, a), self.value(insn, b));
+                if let Some( target ) = insn.flow.target() {
+                    out.push( Stmt::Branch { cond, a, b, target } );
+                }
+            },
+            Sem::Jal { link } => {
+                self.set(&mut out, insn, link, Value::Const( insn.addr + insn.len as u64 ));
+                match insn.flow {
+                    Flow::Call( t ) => out.push( Stmt::Call { target : Value::Const( t ) } ),
+                    Flow::Jump( t ) => out.push( Stmt::Jump { target : Value::Const( t ) } ),
+                    _ => {},
+                }
+            },
+            Sem::Jalr { link, base, offset } => {
+                if insn.flow == Flow::Return {
+                    out.push( Stmt::Return );
+                    return out;
+                }
+                //target is computed before link is written, base could be the same register,
+                //target found by traversal (like of auipc and jalr pair) is used as it is
+                let target = match insn.flow.target() {
+                    Some( t ) => Value::Const( t ),
+                    None => {
+                        let addr = self.address(&mut out, insn, base, offset);
+                        self.assign(&mut out, Op::And, addr, Value::Const( !1 & self.mask ))
+                    },
+                };
+                self.set(&mut out, insn, link, Value::Const( insn.addr + insn.len as u64 ));
+                match insn.flow {
+                    Flow::IndirectCall | Flow::Call( _ ) => out.push( Stmt::Call { target } ),
+                    _ => out.push( Stmt::Jump { target } ),
+                }
+            },
+            Sem::Amo { op, bytes } => {
+                let addr = self.value(insn, rs1);
+                let old = self.temp();
+                out.push( Stmt::Load { dst : old, addr, bytes, signed : true } );
+                let new = self.assign(&mut out, op, Value::Temp( old ), self.value(insn, Operand::Reg("rs2")));
+                out.push( Stmt::Store { addr, value : new, bytes } );
+                self.set(&mut out, insn, rd, Value::Temp( old ));
+            },
+            Sem::LoadReserved { bytes } => {
+                let addr = self.value(insn, rs1);
+                let t = self.temp();
+                out.push( Stmt::Load { dst : t, addr, bytes, signed : true } );
+                out.push( Stmt::Reserve { addr } );
+                self.set(&mut out, insn, rd, Value::Temp( t ));
+            },
+            Sem::StoreConditional { bytes } => {
+                let (addr, value) = (self.value(insn, rs1), self.value(insn, Operand::Reg("rs2")));
+                let t = self.temp();
+                out.push( Stmt::StoreConditional { dst : t, addr, value, bytes } );
+                self.set(&mut out, insn, rd, Value::Temp( t ));
+            },
+            Sem::Push | Sem::Pop { .. } => {
+                let d = &insn.decoded;
+                let rlist = d.value("rlist").unwrap_or(0) as u32;
+                let adj = stack_adj(rlist, d.value("spimm").unwrap_or(0) as u32, self.xlen) as u64;
+                let bytes = match self.xlen { Xlen::X32 => 4, Xlen::X64 => 8 };
+                //registers are below sp before push and after pop, the first one is the highest
+                let top = match sem { Sem::Push => 0, _ => adj };
+                for (n, r) in rlist_registers(rlist).into_iter().enumerate() {
+                    let offset = top.wrapping_sub((n as u64 + 1) * bytes as u64) & self.mask;
+                    let addr = self.assign(&mut out, Op::Add, Value::Reg( SP ), Value::Const( offset ));
+                    match sem {
+                        Sem::Push => out.push( Stmt::Store { addr, value : Value::Reg( r ), bytes } ),
+                        _ => {
+                            let t = self.temp();
+                            out.push( Stmt::Load { dst : t, addr, bytes, signed : true } );
+                            out.push( Stmt::Set { reg : r, value : Value::Temp( t ) } );
+                        },
+                    }
+                }
+                let adj = match sem { Sem::Push => adj.wrapping_neg() & self.mask, _ => adj };
+                let sp = self.assign(&mut out, Op::Add, Value::Reg( SP ), Value::Const( adj ));
+                out.push( Stmt::Set { reg : SP, value : sp } );
+                if let Sem::Pop { ret, zero } = sem {
+                    if zero {
+                        out.push( Stmt::Set { reg : 10, value : Value::Const( 0 ) } );
+                    }
+                    if ret {
+                        out.push( Stmt::Return );
+                    }
+                }
+            },
+            Sem::Csr { op, src } => {
+                //csrrw does not read CSR if rd is x0, csrrs and csrrc do not write it if source is zero
+                let csr = insn.decoded.value("csr").unwrap_or(0) as u16;
+                let src = self.value(insn, src);
+                let old = match op == CsrOp::Write && self.value(insn, rd) == Value::Const( 0 ) {
+                    true => None,
+                    false => {
+                        let t = self.temp();
+                        out.push( Stmt::CsrRead { dst : t, csr } );
+                        Some( Value::Temp( t ) )
+                    },
+                };
+                let new = match (op, old) {
+                    (CsrOp::Write, _) => Some( src ),
+                    (_, _) if src == Value::Const( 0 ) => None,
+                    (CsrOp::Set, Some( old )) => Some( self.assign(&mut out, Op::Or, old, src) ),
+                    (CsrOp::Clear, Some( old )) => {
+                        let mask = match src {
+                            Value::Const( c ) => Value::Const( !c & self.mask ),
+                            v => self.assign(&mut out, Op::Xor, v, Value::Const( self.mask )),
+                        };
+                        Some( self.assign(&mut out, Op::And, old, mask) )
+                    },
+                    _ => None,
+                };
+                if let Some( value ) = new {
+                    out.push( Stmt::CsrWrite { csr, value } );
+                }
+                if let Some( old ) = old {
+                    self.set(&mut out, insn, rd, old);
+                }
+            },
+        }
+        out
+    }
+}
+
+///text of functions lifted into IR, statements follow instructions of blocks
+pub fn text(cfgs : &[Cfg], xlen : Xlen, names : &BTreeMap<u64, String>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for g in cfgs {
+        lines.push( format!("{:#010X} <{}>:", g.function.start, g.function.name) );
+        let mut lifter = Lifter::new(xlen);
+        for b in g.blocks.values() {
+            match names.get(&b.start) {
+                Some( name ) if b.start != g.function.start => lines.push( format!("  block {:#010X} <{}>", b.start, name) ),
+                _ => lines.push( format!("  block {:#010X}", b.start) ),
+            }
+            for i in &b.insns {
+                lines.push( format!("    {:#010X} {}", i.addr, i.decoded.text) );
+                for s in lifter.lift(i) {
+                    lines.push( format!("        {}", s) );
+                }
+            }
+        }
+        lines.push( String::new() );
+    }
+    lines
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::with_march;
+
+    fn lifted(code : &[u8]) -> Vec<String> {
+        lifted_march("rv32gc", code)
+    }
+
+    fn lifted_march(march : &str, code : &[u8]) -> Vec<String> {
+        with_march(march, |t| {
+            let mut lifter = Lifter::new(t.xlen);
+            let mut offset = 0;
+            let mut result = Vec::new();
+            while offset < code.len() {
+                let insn = t.decode_at(code, offset, 0x1000 + offset as u64).unwrap();
+                offset += insn.len;
+                result.extend( lifter.lift(&insn).iter().map(|s| s.to_string()) );
+            }
+            result
+        })
+    }
+
+    #[test]
+    fn integer() {
+        //addi a0, a0, -1; lui a1, 0x80000; c.lw a2, 4(a0); sw a2, 8(sp); bltu a0, a1, -8; c.slli a0, 2;
+        //addi x0, a0, 1
+        let code = [0x13, 0x05, 0xf5, 0xff, 0xb7, 0x05, 0x00, 0x80, 0x50, 0x41, 0x23, 0x24, 0xc1, 0x00,
+                    0xe3, 0x6c, 0xb5, 0xfe, 0x0a, 0x05, 0x13, 0x00, 0x15, 0x00];
+        assert_eq!(lifted(&code), vec!["t0 = add r10, 0xffffffff", "r10 = t0",
+                                       "t1 = add 0x0, 0x80000000", "r11 = t1",
+                                       "t2 = add r10, 0x4", "t3 = load.s32 [t2]", "r12 = t3",
+                                       "t4 = add r2, 0x8", "store.32 [t4], r12",
+                                       "if r10 <u r11 goto 0x1006",
+                                       "t5 = sll r10, 0x2", "r10 = t5",
+                                       "t6 = add r10, 0x1"]);
+    }
+
+    #[test]
+    fn control_and_system() {
+        //jal ra, 0x100; jalr t0, 4(a5); csrrs a0, mstatus, a1; csrrci x0, mstatus, 8; amoadd.w a0, a1, (a2);
+        //fadd.s fa0, fa1, fa2; c.jr ra
+        let code = [0xef, 0x00, 0x00, 0x10, 0xe7, 0x82, 0x47, 0x00, 0x73, 0xa5, 0x05, 0x30, 0x73, 0x70, 0x04, 0x30,
+                    0x2f, 0x25, 0xb6, 0x00, 0x53, 0xf5, 0xc5, 0x00, 0x82, 0x80];
+        assert_eq!(lifted(&code), vec!["r1 = 0x1004", "call 0x1100",
+                                       "t0 = add r15, 0x4", "t1 = and t0, 0xfffffffe", "r5 = 0x1008", "call t1",
+                                       "t2 = csr[0x300]", "t3 = or t2, r11", "csr[0x300] = t3", "r10 = t2",
+                                       "t4 = csr[0x300]", "t5 = and t4, 0xfffffff7", "csr[0x300] = t5",
+                                       "t6 = load.s32 [r12]", "t7 = add t6, r11", "store.32 [r12], t7", "r10 = t6",
+                                       "intrinsic fadd.s reads: f11 f12 csr 0x2; writes: f10 csr 0x1",
+                                       "return"]);
+    }
+
+    #[test]
+    fn bit_manipulation() {
+        //sh1add a0, a1, a2; clz a0, a1; sext.b a0, a1; rori a0, a1, 3; bseti a0, a1, 5; andn a0, a1, a2
+        let code = [0x33, 0xa5, 0xc5, 0x20, 0x13, 0x95, 0x05, 0x60, 0x13, 0x95, 0x45, 0x60, 0x13, 0xd5, 0x35, 0x60,
+                    0x13, 0x95, 0x55, 0x28, 0x33, 0xf5, 0xc5, 0x40];
+        assert_eq!(lifted_march("rv32gc_zba_zbb_zbs", &code), vec!["t0 = sh1add r11, r12", "r10 = t0",
+                                                                   "t1 = clz r11", "r10 = t1",
+                                                                   "t2 = sext8 r11", "r10 = t2",
+                                                                   "t3 = ror r11, 0x3", "r10 = t3",
+                                                                   "t4 = bset r11, 0x5", "r10 = t4",
+                                                                   "t5 = andn r11, r12", "r10 = t5"]);
+        //rows shared by Zbkb and Zbb have the same semantics, whichever extension is first
+        assert_eq!(lifted_march("rv32i_zbkb_zbb", &code[12..16]), vec!["t0 = ror r11, 0x3", "r10 = t0"]);
+        assert_eq!(lifted_march("rv32i_zbkb", &code[20..24]), vec!["t0 = andn r11, r12", "r10 = t0"]);
+    }
+
+    #[test]
+    fn push_offsets() {
+        //cm.push {ra, s0-s2}, -16: s2 is stored at sp-4, ra at sp-16
+        assert_eq!(lifted_march("rv32im_zca_zcmp", &[0x72, 0xb8]), vec!["t0 = add r2, 0xfffffffc", "store.32 [t0], r18",
+                                                                        "t1 = add r2, 0xfffffff8", "store.32 [t1], r9",
+                                                                        "t2 = add r2, 0xfffffff4", "store.32 [t2], r8",
+                                                                        "t3 = add r2, 0xfffffff0", "store.32 [t3], r1",
+                                                                        "t4 = add r2, 0xfffffff0", "r2 = t4"]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Recovery of jump tables: indirect jump to address loaded from table, indexed by register which
+//! is checked against bound by bgeu or bltu before. Entries are addresses, or offsets from table base
+//! which are added to loaded value before jump
+
+use crate::analysis::traverse::Insn;
+use crate::analysis::flow::Flow;
+use crate::analysis::functions::Function;
+use crate::analysis::cfg::{cfgs, Block, Cfg, EdgeKind};
+use crate::analysis::consts::{entry_states, Regs};
+use crate::analysis::image::Image;
+use crate::isa::Xlen;
+use std::collections::BTreeMap;
+
+///bigger tables are not recovered, bound is probably wrong
+const MAX_ENTRIES : u64 = 4096;
+
+///jump table used by indirect jump, targets are in the order of entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpTable {
+    pub jump : u64,
+    pub table : u64,
+    pub targets : Vec<u64>,
+}
+
+///last instruction which writes register, with its index
+fn last_def<'a>(insns : &[&'a Insn], r : u64) -> Option<(usize, &'a Insn)> {
+    insns.iter().enumerate().rev().find(|(_, i)| i.decoded.register("rd") == Some( r )).map(|(k, i)| (k, *i))
+}
+
+///operands of add and c.add
+fn add_operands(insn : &Insn) -> Option<(u64, u64)> {
+    let d = &insn.decoded;
+    match &d.mnemonic[..] {
+        "add" => Some( (d.register("rs1")?, d.register("rs2")?) ),
+        "c.add" => Some( (d.register("rd")?, d.register("rs2")?) ),
+        _ => None,
+    }
+}
+
+///size of entry read by load
+fn load_size(insn : &Insn) -> Option<u64> {
+    match &insn.decoded.mnemonic[..] {
+        "lw" | "c.lw" | "lwu" => Some( 4 ),
+        "ld" | "c.ld" => Some( 8 ),
+        _ => None,
+    }
+}
+
+///number of entries, from bound check of index by branch which passes control to block
+fn bound(cfg : &Cfg, b : &Block, states : &BTreeMap<u64, Regs>, index : u64) -> Option<u64> {
+    let mut result = None;
+    for p in cfg.blocks.values() {
+        let edges : Vec<EdgeKind> = p.edges.iter().filter(|e| e.to == Some( b.start )).map(|e| e.kind).collect();
+        if edges.is_empty() {
+            continue;
+        }
+        let (last, rest) = p.insns.split_last()?;
+        if edges.len() != 1 || !matches!(last.flow, Flow::Branch( _ )) {
+            return None;
+        }
+        let mut regs = states.get(&p.start)?.clone();
+        for i in rest {
+            regs.step(i);
+        }
+        let d = &last.decoded;
+        let (rs1, rs2) = (d.register("rs1")?, d.register("rs2")?);
+        let taken = edges[0] == EdgeKind::Taken;
+        //index < n, or n >= index on the path to block
+        let n = match (&d.mnemonic[..], taken) {
+            ("bgeu", false) | ("bltu", true) if rs1 == index => regs.get(rs2)?,
+            ("bltu", false) | ("bgeu", true) if rs2 == index => regs.get(rs1)? + 1,
+            _ => return None,
+        };
+        if result.is_some_and(|r| r != n) {
+            return None;
+        }
+        result = Some( n );
+    }
+    result
+}
+
+///table used by indirect jump which ends block
+fn recognise(cfg : &Cfg, b : &Block, states : &BTreeMap<u64, Regs>, image : &Image, xlen : Xlen) -> Option<JumpTable> {
+    let (jump, insns) = b.insns.split_last()?;
+    let d = &jump.decoded;
+    if jump.flow != Flow::IndirectJump || !(d.mnemonic == "c.jr" || d.mnemonic == "jalr" && d.value("imm") == Some( 0 )) {
+        return None;
+    }
+    let mut regs = states.get(&b.start)?.clone();
+    let mut before = Vec::new();
+    for i in insns {
+        before.push( regs.clone() );
+        regs.step(i);
+    }
+
+    //target is loaded, or loaded offset is added to base
+    let (k, def) = last_def(insns, d.register("rs1")?)?;
+    let (k, load, relative) = match add_operands(def) {
+        Some( (x, y) ) => [(x, y), (y, x)].iter().find_map(|(x, y)| {
+            let base = before[k].get(*y)?;
+            let (k, load) = last_def(&insns[..k], *x)?;
+            load_size(load).map(|_| (k, load, Some( base )))
+        })?,
+        None => (k, def, None),
+    };
+    let size = load_size(load)?;
+
+    //address of entry is base plus index shifted by log2 of entry size
+    let shift = size.trailing_zeros() as i64;
+    let (k, addr) = last_def(&insns[..k], load.decoded.register("rs1")?)?;
+    let (base, index, k) = match (&addr.decoded.mnemonic[..], size) {
+        ("sh2add", 4) | ("sh3add", 8) => (before[k].get(addr.decoded.register("rs2")?)?, addr.decoded.register("rs1")?, k),
+        _ => {
+            let (x, y) = add_operands(addr)?;
+            [(x, y), (y, x)].iter().find_map(|(x, y)| {
+                let base = before[k].get(*y)?;
+                let (k, slli) = last_def(&insns[..k], *x)?;
+                let s = &slli.decoded;
+                let source = match &s.mnemonic[..] {
+                    "slli" => s.register("rs1")?,
+                    "c.slli" => s.register("rd")?,
+                    _ => return None,
+                };
+                (s.signed("imm") == Some( shift )).then_some( (base, source, k) )
+            })?
+        },
+    };
+    //bound is checked before block, so index should not be changed in it
+    if last_def(&insns[..k], index).is_some() {
+        return None;
+    }
+    let count = bound(cfg, b, states, index).filter(|n| *n > 0 && *n <= MAX_ENTRIES)?;
+
+    let mask = xlen.mask();
+    let table = base.wrapping_add(load.decoded.signed("imm").unwrap_or(0) as u64) & mask;
+    let mut targets = Vec::new();
+    for n in 0..count {
+        let mut entry = image.read_uint(table + n * size, size as usize)?;
+        if size == 4 && load.decoded.mnemonic != "lwu" {
+            entry = entry as u32 as i32 as i64 as u64;
+        }
+        let target = relative.map_or(entry, |r| r.wrapping_add(entry)) & mask;
+        if target & 1 != 0 {
+            return None;
+        }
+        targets.push( target );
+    }
+    Some( JumpTable { jump : jump.addr, table, targets } )
+}
+
+///jump tables used by indirect jumps of given functions of found instructions
+pub fn jump_tables(insns : &BTreeMap<u64, Insn>, found : &[Function], image : &Image, xlen : Xlen) -> Vec<JumpTable> {
+    let mut result = BTreeMap::new();
+    for g in cfgs(insns, found) {
+        let states = entry_states(&g, xlen);
+        for b in g.blocks.values() {
+            if let Some( t ) = recognise(&g, b, &states, image, xlen) {
+                result.insert(t.jump, t);
+            }
+        }
+    }
+    result.into_values().collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::with_tables;
+    use crate::analysis::functions::{discover, functions};
+
+    ///0x0: li a5, 3; bltu a5, a0, 0x38; slli a0, a0, 2; lui a5, 0x2; addi a5, a5, 16; add a0, a0, a5;
+    ///lw a5, 0(a0); jr a5; 0x20, 0x28, 0x30: li a0, N; ret; 0x38: li a0, 0; ret
+    const ABSOLUTE : [u8; 64] = [0x93, 0x07, 0x30, 0x00, 0x63, 0xea, 0xa7, 0x02, 0x13, 0x15, 0x25, 0x00,
+                                 0xb7, 0x27, 0x00, 0x00, 0x93, 0x87, 0x07, 0x01, 0x33, 0x05, 0xf5, 0x00,
+                                 0x83, 0x27, 0x05, 0x00, 0x67, 0x80, 0x07, 0x00, 0x13, 0x05, 0xa0, 0x00,
+                                 0x67, 0x80, 0x00, 0x00, 0x13, 0x05, 0xb0, 0x00, 0x67, 0x80, 0x00, 0x00,
+                                 0x13, 0x05, 0xc0, 0x00, 0x67, 0x80, 0x00, 0x00, 0x13, 0x05, 0x00, 0x00,
+                                 0x67, 0x80, 0x00, 0x00];
+    ///table at 0x2010
+    const TABLE : [u8; 16] = [0x20, 0, 0, 0, 0x28, 0, 0, 0, 0x30, 0, 0, 0, 0x38, 0, 0, 0];
+
+    ///0x0: c.li a1, 3; bgeu a0, a1, 0x20; c.slli a0, 2; auipc a1, 0; addi a1, a1, 0x1c; c.add a0, a1;
+    ///c.lw a0, 0(a0); c.add a0, a1; c.jr a0; 0x18, 0x1c: c.li a0, N; c.jr ra; 0x20: c.li a0, 0; c.jr ra;
+    ///0x24: table of offsets from it
+    const RELATIVE : [u8; 48] = [0x8d, 0x45, 0x63, 0x7f, 0xb5, 0x00, 0x0a, 0x05, 0x97, 0x05, 0x00, 0x00,
+                                 0x93, 0x85, 0xc5, 0x01, 0x2e, 0x95, 0x08, 0x41, 0x2e, 0x95, 0x02, 0x85,
+                                 0x29, 0x45, 0x82, 0x80, 0x2d, 0x45, 0x82, 0x80, 0x01, 0x45, 0x82, 0x80,
+                                 0xf4, 0xff, 0xff, 0xff, 0xf8, 0xff, 0xff, 0xff, 0xf4, 0xff, 0xff, 0xff];
+
+    #[test]
+    fn absolute() {
+        let mut image = Image::new(0, &ABSOLUTE);
+        image.add(0x2010, &TABLE);
+        with_tables(|t| {
+            let insns = discover(&ABSOLUTE, 0, &[0], t, &image);
+            assert!(insns.contains_key(&0x20) && insns.contains_key(&0x28) && insns.contains_key(&0x30));
+            assert_eq!(insns[&0x1c].targets, vec![0x20, 0x28, 0x30, 0x38]);
+            let f = functions(&insns, &[0], &BTreeMap::new(), t.xlen);
+            let tables = jump_tables(&insns, &f, &image, t.xlen);
+            assert_eq!(tables, vec![JumpTable { jump : 0x1c, table : 0x2010, targets : vec![0x20, 0x28, 0x30, 0x38] }]);
+
+            let g = cfgs(&insns, &f);
+            assert_eq!(g.len(), 1);
+            assert_eq!(g[0].blocks[&0x8].edges.len(), 4);
+            assert!(g[0].blocks[&0x8].edges.iter().all(|e| e.kind == EdgeKind::Indirect));
+        });
+    }
+
+    #[test]
+    fn relative() {
+        let image = Image::new(0x8000_0000, &RELATIVE);
+        with_tables(|t| {
+            let insns = discover(&RELATIVE, 0x8000_0000, &[0x8000_0000], t, &image);
+            assert_eq!(insns[&0x8000_0016].targets, vec![0x8000_0018, 0x8000_001c, 0x8000_0018]);
+            assert!(!insns.contains_key(&0x8000_0024));
+        });
+        //without bound check entries are not known
+        let mut code = RELATIVE;
+        code[2..6].copy_from_slice(&[0x01, 0x00, 0x01, 0x00]);
+        let image = Image::new(0, &code);
+        with_tables(|t| assert!(discover(&code, 0, &[0], t, &image)[&0x16].targets.is_empty()));
+    }
+}
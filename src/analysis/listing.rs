@@ -0,0 +1,109 @@
+//! Text lines of disassembly listing
+
+use crate::analysis::traverse::Insn;
+use std::collections::BTreeMap;
+
+///line of listing: address, text and encoding of given length in bytes
+pub fn line(addr : u64, text : &str, len : usize, word : u64) -> String {
+    match len {
+        1 => format!("{:#010X} {:40}       {:#04X}", addr, text, word),
+        2 => format!("{:#010X} {:40}     {:#06X}", addr, text, word),
+        4 => format!("{:#010X} {:40} {:#010X}  ", addr, text, word),
+        6 => format!("{:#010X} {:40} {:#014X}", addr, text, word),
+        8 => format!("{:#010X} {:40} {:#018X}", addr, text, word),
+        _ => format!("{:#010X} {:40}", addr, text),
+    }
+}
+
+///lines of data: aligned words, then half-words, then bytes
+pub fn data(code : &[u8], base : u64, from : u64, to : u64) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut addr = from;
+    while addr < to {
+        let len = match (addr % 4, to - addr) {
+            (0, n) if n >= 4 => 4,
+            (0, n) | (2, n) if n >= 2 => 2,
+            _ => 1,
+        };
+        let offset = (addr - base) as usize;
+        let word = code[offset..offset + len].iter().rev().fold(0, |v, b| (v << 8) | *b as u64);
+        let text = match len {
+            4 => format!(".word {:#010x}", word),
+            2 => format!(".half {:#06x}", word),
+            _ => format!(".byte {:#04x}", word),
+        };
+        lines.push( line(addr, &text, len, word) );
+        addr += len as u64;
+    }
+    lines
+}
+
+///listing of code with found instructions, bytes between them are shown as data.
+///Labels are shown before their addresses, comments after text of their instructions
+pub fn listing(code : &[u8], base : u64, insns : &BTreeMap<u64, Insn>, labels : &BTreeMap<u64, String>,
+               comments : &BTreeMap<u64, String>) -> Vec<String> {
+    let mut lines = Vec::new();
+    let end = base + code.len() as u64;
+    let mut addr = base;
+    let label = |lines : &mut Vec<String>, from : u64, to : u64| {
+        for (a, name) in labels.range(from..to) {
+            lines.push( format!("{:#010X} <{}>:", a, name) );
+        }
+    };
+    for insn in insns.values() {
+        //data is split at labels
+        let mut from = addr;
+        for a in labels.range(addr..insn.addr).map(|(a, _)| *a).chain(std::iter::once(insn.addr)) {
+            lines.extend( data(code, base, from, a) );
+            label(&mut lines, a, a + 1);
+            from = a;
+        }
+        let text = match comments.get(&insn.addr) {
+            Some( c ) => format!("{} {}", insn.decoded.text, c),
+            None => insn.decoded.text.clone(),
+        };
+        lines.push( line(insn.addr, &text, insn.len, insn.word) );
+        label(&mut lines, insn.addr + 1, insn.addr + insn.len as u64);
+        addr = insn.addr + insn.len as u64;
+    }
+    let mut from = addr;
+    for a in labels.range(addr..end).map(|(a, _)| *a).chain(std::iter::once(end)) {
+        lines.extend( data(code, base, from, a) );
+        if a < end {
+            label(&mut lines, a, a + 1);
+        }
+        from = a;
+    }
+    lines
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::{traverse, test::{with_tables, CODE}};
+
+    #[test]
+    fn data_lines() {
+        let code = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        let lines = data(&code, 0x1000, 0x1001, 0x1009);
+        let texts : Vec<&str> = lines.iter().map(|l| l[11..].split_whitespace().take(2).last().unwrap()).collect();
+        assert_eq!(texts, vec!["0x02", "0x0403", "0x08070605", "0x09"]);
+        assert!(lines[2].starts_with("0x00001004 .word 0x08070605"));
+    }
+
+    #[test]
+    fn code_and_data() {
+        let found = with_tables(|t| traverse(&CODE, 0x1000, &[0x1000], &BTreeMap::new(), t));
+        let mut labels = BTreeMap::new();
+        labels.insert(0x1000, String::from("_start"));
+        labels.insert(0x1016, String::from("func"));
+        let mut comments = BTreeMap::new();
+        comments.insert(0x1008, String::from("# 0x1016 <func>"));
+        let lines = listing(&CODE, 0x1000, &found, &labels, &comments);
+        let texts : Vec<String> = lines.iter().map(|l| l[11..].split("  ").next().unwrap().to_string()).collect();
+        assert_eq!(texts, vec!["<_start>:", "c.j 0x8", ".half 0x5673", ".word 0xffff1234",
+                               "jal r1, 0xE # 0x1016 <func>", "c.beqz r10, 0x4", "c.li r10, 0x1", "ecall", ".half 0x1234",
+                               "<func>:", "c.jr r1", ".byte 0x01"]);
+    }
+}
@@ -0,0 +1,25 @@
+mod flow;
+mod traverse;
+mod listing;
+mod functions;
+mod cfg;
+mod consts;
+mod xref;
+mod image;
+mod jumptable;
+mod defuse;
+mod ir;
+mod decompile;
+mod abi;
+
+pub use traverse::{Tables, Insn};
+pub use listing::{line, listing};
+pub use functions::{discover, functions, Function};
+pub use image::Image;
+pub use jumptable::jump_tables;
+pub use cfg::{cfgs, dot, text as cfg_text};
+pub use consts::annotations;
+pub use ir::text as ir_text;
+pub use decompile::decompile;
+pub use abi::{Convention, text as abi_text};
+pub use xref::{Xrefs, json, call_graph_text, text as xref_text};
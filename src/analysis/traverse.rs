@@ -0,0 +1,186 @@
+//! Recursive traversal of code: instructions are decoded only at addresses reachable from
+//! entry points, the rest of bytes is data
+
+use crate::primitives::*;
+use crate::isa::{ISA, IsaSet, Xlen};
+use crate::decoder::{decode_details, decode_details_set, instruction_length, Decoded};
+use crate::analysis::flow::{flow, Flow};
+use crate::analysis::consts::{Access, Regs};
+use std::collections::BTreeMap;
+
+///instruction tables of all lengths, and register width they are decoded for
+pub struct Tables<'a> {
+    pub isa16 : &'a ISA<CompactType>,
+    pub set32 : &'a IsaSet<RV32Type>,
+    pub isa48 : &'a ISA<Long48Type>,
+    pub isa64 : &'a ISA<Long64Type>,
+    pub xlen : Xlen,
+}
+
+///instruction found by traversal, word keeps bytes of instruction in little-endian order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Insn {
+    pub addr : u64,
+    pub len : usize,
+    pub word : u64,
+    pub decoded : Decoded,
+    pub flow : Flow,
+    ///known targets of indirect jump, like ones recovered from jump table
+    pub targets : Vec<u64>,
+}
+
+impl<'a> Tables<'a> {
+    ///decode instruction at given offset of code. Unknown, reserved and illegal encodings are not instructions
+    pub fn decode_at(&self, code : &[u8], offset : usize, addr : u64) -> Option<Insn> {
+        let first = code.get(offset..offset + 2)?;
+        let len = instruction_length( u16::from_le_bytes([first[0], first[1]]) )?;
+        let bytes = code.get(offset..offset + len)?;
+        let mut word = [0; 8];
+        let n = std::cmp::min(len, 8);
+        word[..n].copy_from_slice(&bytes[..n]);
+        let word = u64::from_le_bytes(word);
+        let decoded = match len {
+            2 => decode_details(word as u16, self.isa16),
+            4 => decode_details_set(word as u32, self.set32),
+            6 => decode_details(word, self.isa48),
+            8 => decode_details(word, self.isa64),
+            _ => None,
+        }?;
+        if decoded.class == Class::Reserved || decoded.mnemonic == "<illegal>" {
+            return None;
+        }
+        let flow = flow(addr, &decoded);
+        Some( Insn { addr, len, word, decoded, flow, targets : Vec::new() } )
+    }
+}
+
+///flow of jalr whose base register is set by previous instruction, like auipc ra, hi; jalr ra, lo(ra).
+///Target is found by the same constant propagation which finds references of functions
+fn linked(prev : &Insn, insn : &Insn, xlen : Xlen) -> Flow {
+    if !matches!(insn.flow, Flow::IndirectCall | Flow::IndirectJump) || !insn.targets.is_empty() {
+        return insn.flow;
+    }
+    let mut regs = Regs::new(xlen);
+    regs.step(prev);
+    match regs.step(insn) {
+        Some( r ) if r.access == Access::Call => Flow::Call( r.addr & !1 ),
+        Some( r ) if r.access == Access::Jump => Flow::Jump( r.addr & !1 ),
+        _ => insn.flow,
+    }
+}
+
+///decode instructions reachable from entry points, following jumps, branches and calls, and given
+///targets of indirect jumps. Code is placed at given base address, targets outside of it are not followed.
+///Instruction which would overlap already found one is not taken
+pub fn traverse(code : &[u8], base : u64, entries : &[u64], indirect : &BTreeMap<u64, Vec<u64>>, tables : &Tables)
+    -> BTreeMap<u64, Insn> {
+    let mut found = BTreeMap::<u64, Insn>::new();
+    let mut work : Vec<u64> = entries.iter().rev().cloned().collect();
+    let end = base + code.len() as u64;
+
+    while let Some( addr ) = work.pop() {
+        if addr < base || addr >= end || addr % 2 != 0 || found.contains_key(&addr) {
+            continue;
+        }
+        if let Some( (_, prev) ) = found.range(..addr).next_back() {
+            if prev.addr + prev.len as u64 > addr {
+                continue;
+            }
+        }
+        let mut insn = match tables.decode_at(code, (addr - base) as usize, addr) {
+            None => continue,
+            Some( insn ) => insn,
+        };
+        if let Some( targets ) = indirect.get(&addr) {
+            insn.targets = targets.clone();
+        }
+        if let Some( (_, prev) ) = found.range(..addr).next_back() {
+            if prev.addr + prev.len as u64 == addr && prev.flow.falls_through() {
+                insn.flow = linked(prev, &insn, tables.xlen);
+            }
+        }
+        let next = addr + insn.len as u64;
+        if found.range(addr..next).next().is_some() {
+            continue;
+        }
+        if insn.flow.falls_through() {
+            work.push( next );
+        }
+        if let Some( target ) = insn.flow.target() {
+            work.push( target );
+        }
+        work.extend( insn.targets.iter().rev() );
+        found.insert(addr, insn);
+    }
+    found
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::isa::March;
+    use crate::analysis::image::Image;
+    use crate::analysis::functions::{discover, functions};
+    use crate::analysis::cfg::{Cfg, cfgs};
+
+    ///tables of default RV32GC ISA, for tests of analysis
+    pub fn with_tables<R>(f : impl FnOnce(&Tables) -> R) -> R {
+        with_march("rv32gc", f)
+    }
+
+    ///tables of given ISA string, for tests of analysis
+    pub fn with_march<R>(march : &str, f : impl FnOnce(&Tables) -> R) -> R {
+        let march = March::parse(march).unwrap();
+        let isa16 = march.isa16();
+        let set32 = march.isa32().unwrap();
+        let isa48 = ISA::<Long48Type>::default();
+        let isa64 = ISA::<Long64Type>::default();
+        f( &Tables { isa16 : &isa16, set32 : &set32, isa48 : &isa48, isa64 : &isa64, xlen : march.xlen } )
+    }
+
+    ///graphs of functions found in code at base from entries, by tables of given ISA string
+    pub fn with_cfgs<R>(march : &str, code : &[u8], base : u64, entries : &[u64], f : impl FnOnce(&Tables, &[Cfg]) -> R) -> R {
+        with_march(march, |t| {
+            let insns = discover(code, base, entries, t, &Image::new(base, code));
+            let found = functions(&insns, entries, &BTreeMap::new(), t.xlen);
+            f(t, &cfgs(&insns, &found))
+        })
+    }
+
+    ///0x1000: c.j 0x1008; 0x1002: .word 0x12345673; 0x1006: .half 0xffff;
+    ///0x1008: jal ra, 0x1016; 0x100c: c.beqz a0, 0x1010; 0x100e: c.li a0, 1; 0x1010: ecall;
+    ///0x1014: .half 0x1234; 0x1016: c.jr ra; 0x1018: .byte 0x01
+    pub const CODE : [u8; 25] = [0x21, 0xa0, 0x73, 0x56, 0x34, 0x12, 0xff, 0xff,
+                                 0xef, 0x00, 0xe0, 0x00, 0x11, 0xc1, 0x05, 0x45,
+                                 0x73, 0x00, 0x00, 0x00, 0x34, 0x12, 0x82, 0x80, 0x01];
+
+    #[test]
+    fn literal_pool() {
+        let found = with_tables(|t| traverse(&CODE, 0x1000, &[0x1000], &BTreeMap::new(), t));
+        let addrs : Vec<u64> = found.keys().cloned().collect();
+        assert_eq!(addrs, vec![0x1000, 0x1008, 0x100c, 0x100e, 0x1010, 0x1016]);
+        assert_eq!(found[&0x1008].flow, Flow::Call( 0x1016 ));
+        assert_eq!(found[&0x1010].decoded.text, "ecall");
+        assert_eq!(found[&0x1016].flow, Flow::Return);
+    }
+
+    #[test]
+    fn overlap() {
+        //entry at 0x1002 is inside of instruction found from 0x1000
+        let code = [0x13, 0x05, 0x50, 0x00, 0x82, 0x80];
+        let found = with_tables(|t| traverse(&code, 0x1000, &[0x1000, 0x1002, 0x1001, 0x2000], &BTreeMap::new(), t));
+        assert_eq!(found.keys().cloned().collect::<Vec<u64>>(), vec![0x1000, 0x1004]);
+        assert_eq!(found[&0x1004].decoded.text, "c.jr r1");
+    }
+
+    #[test]
+    fn linked_call() {
+        //0x0: auipc ra, 0; 0x4: jalr ra, 16(ra); 0x8: ecall; 0xc: .word 0; 0x10: c.jr ra
+        let code = [0x97, 0x00, 0x00, 0x00, 0xe7, 0x80, 0x00, 0x01, 0x73, 0x00, 0x00, 0x00,
+                    0x00, 0x00, 0x00, 0x00, 0x82, 0x80];
+        let found = with_tables(|t| traverse(&code, 0, &[0], &BTreeMap::new(), t));
+        assert_eq!(found[&4].flow, Flow::Call( 0x10 ));
+        assert_eq!(found.keys().cloned().collect::<Vec<u64>>(), vec![0, 4, 8, 0x10]);
+    }
+}
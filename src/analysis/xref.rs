@@ -0,0 +1,197 @@
+//! Cross-references between instructions and addresses they use: calls, tail calls, branches and
+//! data references, and call graph built from them
+
+use crate::analysis::cfg::Cfg;
+use crate::analysis::flow::Flow;
+use crate::analysis::consts::{Access, propagate};
+use crate::isa::Xlen;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum XrefKind {
+    Call,
+    ///jump to start of other function
+    TailCall,
+    ///branch or jump inside of function
+    Branch,
+    ///address computed by lui or auipc and addi
+    Address,
+    Read,
+    Write,
+}
+
+impl fmt::Display for XrefKind {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            XrefKind::Call => "call",
+            XrefKind::TailCall => "tail call",
+            XrefKind::Branch => "branch",
+            XrefKind::Address => "address",
+            XrefKind::Read => "read",
+            XrefKind::Write => "write",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+///reference from instruction to address, function is the start of function of instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Xref {
+    pub to : u64,
+    pub from : u64,
+    pub kind : XrefKind,
+    pub function : u64,
+}
+
+///all references of image, sorted by target address
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Xrefs {
+    pub list : Vec<Xref>,
+}
+
+impl Xrefs {
+    ///references from instructions of graphs. Data references are found by constant propagation
+    pub fn build(cfgs : &[Cfg], xlen : Xlen) -> Xrefs {
+        let starts : BTreeSet<u64> = cfgs.iter().map(|g| g.function.start).collect();
+        let mut set = BTreeSet::new();
+        for g in cfgs {
+            let function = g.function.start;
+            let jump = |to : u64| match starts.contains(&to) && to != function {
+                true => XrefKind::TailCall,
+                false => XrefKind::Branch,
+            };
+            let references = propagate(g, xlen);
+            for b in g.blocks.values() {
+                for i in &b.insns {
+                    let kind = match i.flow {
+                        Flow::Call( t ) => Some( (t, XrefKind::Call) ),
+                        Flow::Jump( t ) => Some( (t, jump(t)) ),
+                        Flow::Branch( t ) => Some( (t, XrefKind::Branch) ),
+                        _ => None,
+                    };
+                    let data = references.get(&i.addr).map(|r| (r.addr, match r.access {
+                        Access::Address => XrefKind::Address,
+                        Access::Read => XrefKind::Read,
+                        Access::Write => XrefKind::Write,
+                        Access::Jump => jump(r.addr),
+                        Access::Call => XrefKind::Call,
+                    }));
+                    for (to, kind) in kind.into_iter().chain(data) {
+                        set.insert( Xref { to, from : i.addr, kind, function } );
+                    }
+                }
+            }
+        }
+        Xrefs { list : set.into_iter().collect() }
+    }
+
+    ///references to given address
+    pub fn to(&self, addr : u64) -> impl Iterator<Item = &Xref> {
+        let first = self.list.partition_point(|x| x.to < addr);
+        self.list[first..].iter().take_while(move |x| x.to == addr)
+    }
+
+    ///callees of each function, by calls and tail calls
+    pub fn call_graph(&self) -> BTreeMap<u64, BTreeSet<u64>> {
+        let mut graph = BTreeMap::<u64, BTreeSet<u64>>::new();
+        for x in self.list.iter().filter(|x| matches!(x.kind, XrefKind::Call | XrefKind::TailCall)) {
+            graph.entry(x.function).or_default().insert(x.to);
+        }
+        graph
+    }
+}
+
+///address with its name if it is known
+fn named(addr : u64, names : &BTreeMap<u64, String>) -> String {
+    match names.get(&addr) {
+        Some( name ) => format!("{:#010X} <{}>", addr, name),
+        None => format!("{:#010X}", addr),
+    }
+}
+
+///text lines of references, caller is shown as function with offset
+pub fn text<'a>(xrefs : impl Iterator<Item = &'a Xref>, names : &BTreeMap<u64, String>) -> Vec<String> {
+    xrefs.map(|x| {
+        let function = names.get(&x.function).cloned().unwrap_or_else(|| format!("{:#x}", x.function));
+        format!("{} <- {:#010X} <{}+{:#x}> {}", named(x.to, names), x.from, function, x.from - x.function, x.kind)
+    }).collect()
+}
+
+///text lines of call graph: function and functions called by it
+pub fn call_graph_text(xrefs : &Xrefs, names : &BTreeMap<u64, String>) -> Vec<String> {
+    xrefs.call_graph().iter().map(|(f, callees)| {
+        let list : Vec<String> = callees.iter().map(|c| named(*c, names)).collect();
+        format!("{} -> {}", named(*f, names), list.join(", "))
+    }).collect()
+}
+
+fn escape(s : &str) -> String {
+    let mut result = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+///name of address as JSON value, null if it is not known
+fn json_name(addr : u64, names : &BTreeMap<u64, String>) -> String {
+    names.get(&addr).map_or(String::from("null"), |n| format!("\"{}\"", escape(n)))
+}
+
+///JSON object with references and call graph, addresses are numbers
+pub fn json(xrefs : &Xrefs, names : &BTreeMap<u64, String>) -> String {
+    let refs : Vec<String> = xrefs.list.iter().map(|x| format!(
+        "    {{\"from\": {}, \"to\": {}, \"kind\": \"{}\", \"function\": {}, \"target\": {}}}",
+        x.from, x.to, x.kind, json_name(x.function, names), json_name(x.to, names))).collect();
+    let calls : Vec<String> = xrefs.call_graph().iter().map(|(f, callees)| {
+        let list : Vec<String> = callees.iter().map(|c| c.to_string()).collect();
+        format!("    {{\"function\": {}, \"name\": {}, \"calls\": [{}]}}", f, json_name(*f, names), list.join(", "))
+    }).collect();
+    let array = |items : Vec<String>| match items.is_empty() {
+        true => String::from("[]"),
+        false => format!("[\n{}\n  ]", items.join(",\n")),
+    };
+    format!("{{\n  \"xrefs\": {},\n  \"call_graph\": {}\n}}", array(refs), array(calls))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::traverse::test::with_cfgs;
+    use crate::analysis::functions::test::CODE;
+
+    #[test]
+    fn references() {
+        let xrefs = with_cfgs("rv32gc", &CODE, 0, &[0], |t, g| Xrefs::build(g, t.xlen));
+        assert_eq!(xrefs.list, vec![Xref { to : 8, from : 0, kind : XrefKind::Call, function : 0 },
+                                    Xref { to : 0x10, from : 0xc, kind : XrefKind::Branch, function : 8 },
+                                    Xref { to : 0x2c, from : 0xe, kind : XrefKind::Branch, function : 8 }]);
+        assert_eq!(xrefs.to(0x10).count(), 1);
+        assert_eq!(xrefs.to(0x12).count(), 0);
+
+        let mut names = BTreeMap::new();
+        names.insert(8, String::from("f\"1"));
+        assert_eq!(text(xrefs.to(8), &names), vec!["0x00000008 <f\"1> <- 0x00000000 <0x0+0x0> call"]);
+        assert_eq!(call_graph_text(&xrefs, &names), vec!["0x00000000 -> 0x00000008 <f\"1>"]);
+        let json = json(&xrefs, &names);
+        assert!(json.contains("{\"from\": 0, \"to\": 8, \"kind\": \"call\", \"function\": null, \"target\": \"f\\\"1\"}"));
+        assert!(json.contains("{\"function\": 0, \"name\": null, \"calls\": [8]}"));
+    }
+
+    #[test]
+    fn tail_and_data() {
+        //0x0: lui a0, 0x10002; lw a1, 16(a0); c.j 0xa; 0xa: c.jr ra, which is function of its own
+        let code = [0x37, 0x25, 0x00, 0x10, 0x83, 0x25, 0x05, 0x01, 0x09, 0xa0, 0x82, 0x80];
+        let xrefs = with_cfgs("rv32gc", &code, 0, &[0, 0xa], |t, g| Xrefs::build(g, t.xlen));
+        assert_eq!(xrefs.list, vec![Xref { to : 0xa, from : 8, kind : XrefKind::TailCall, function : 0 },
+                                    Xref { to : 0x1000_2010, from : 4, kind : XrefKind::Read, function : 0 }]);
+        assert_eq!(xrefs.call_graph()[&0], [0xa].iter().cloned().collect());
+    }
+}
@@ -1,14 +1,134 @@
 use crate::primitives::*;
 use crate::isa::*;
+use num_traits::ToPrimitive;
 
-/// decode given word(u16) using instruction list, returns text description of instruction
+/// decode given word(u16) using instruction list, returns text description of instruction.
+/// Hint and reserved encodings are marked with comment
 pub fn decode<T:Num>(v : T::IType, isa : &ISA::<T>) -> String {
-    for i in &isa.list {
-        if let Some( s ) = try_instruction::<T>(v, &i, &isa.show_dict)  {
-            return s;
-        }
+    try_decode(v, isa).unwrap_or_else(|| format!("{}. Not found!", T::type_name()))
+}
+
+/// decode given word, returns None if there is no matching instruction
+pub fn try_decode<T:Num>(v : T::IType, isa : &ISA::<T>) -> Option<String> {
+    let i = find_instruction(v, isa)?;
+    Some( show(v, i, isa) )
+}
+
+/// decode given word using all fragments of set, returns text description of instruction
+pub fn decode_set<T:Num>(v : T::IType, set : &IsaSet::<T>) -> String {
+    try_decode_set(v, set).unwrap_or_else(|| format!("{}. Not found!", T::type_name()))
+}
+
+/// decode given word using all fragments of set, the most specific instruction wins,
+/// between equally specific instructions the one from first fragment wins.
+/// Instruction is shown with show functions of its own fragment
+pub fn try_decode_set<T:Num>(v : T::IType, set : &IsaSet::<T>) -> Option<String> {
+    let (i, isa) = find_instruction_set(v, set)?;
+    Some( show(v, i, isa) )
+}
+
+/// find instruction in fragments of set, returns it with ISA of its fragment
+fn find_instruction_set<T:Num>(v : T::IType, set : &IsaSet::<T>) -> Option<(&Instruction::<T>, &ISA::<T>)> {
+    set.fragments.iter()
+        .filter_map(|f| find_instruction(v, &f.isa).map(|i| (i, &f.isa)))
+        .rev()
+        .max_by_key(|(i, _)| i.specificity())
+}
+
+/// decoded instruction for analysis: text, mnemonic and values of idents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    pub text : String,
+    pub mnemonic : String,
+    pub class : Class,
+    /// tuples of (ident, value, width of value in bits)
+    pub fields : Vec<(String, u64, u32)>,
+    /// what instruction does, if it is described in instruction table
+    pub sem : Option<Sem>,
+}
+
+impl Decoded {
+    /// value of ident, as it is extracted
+    pub fn value(&self, name : &str) -> Option<u64> {
+        self.fields.iter().find(|(n, _, _)| n == name).map(|(_, v, _)| *v)
     }
-    format!("{}. Not found!", T::type_name())
+
+    /// value of ident sign extended from its highest bit
+    pub fn signed(&self, name : &str) -> Option<i64> {
+        let (_, v, width) = self.fields.iter().find(|(n, _, _)| n == name)?;
+        Some( sign_extend(*v, (*width).max(1)) )
+    }
+
+    /// number of x register of ident, or of its compressed form (with p suffix) which encodes x8..x15
+    pub fn register(&self, name : &str) -> Option<u64> {
+        self.value(name).or_else(|| self.value(&format!("{}p", name)).map(|v| v + 8))
+    }
+}
+
+/// details of matched instruction
+fn details<T:Num>(v : T::IType, i : &Instruction::<T>, isa : &ISA::<T>) -> Decoded {
+    let fields = extract_idents(v, &i.bin).into_iter()
+        .map(|(name, value, _)| {
+            let width = ident_width(&i.bin.list, &name);
+            (name, value.to_u64().unwrap_or(0), width)
+        })
+        .collect();
+    Decoded { text : show(v, i, isa), mnemonic : String::from(i.text.mnemonic()), class : class(v, i), fields, sem : i.sem }
+}
+
+/// decode given word for analysis, returns None if there is no matching instruction
+pub fn decode_details<T:Num>(v : T::IType, isa : &ISA::<T>) -> Option<Decoded> {
+    find_instruction(v, isa).map(|i| details(v, i, isa))
+}
+
+/// decode given word for analysis using all fragments of set
+pub fn decode_details_set<T:Num>(v : T::IType, set : &IsaSet::<T>) -> Option<Decoded> {
+    find_instruction_set(v, set).map(|(i, isa)| details(v, i, isa))
+}
+
+/// text of matched instruction, hint and reserved encodings are marked with comment
+fn show<T:Num>(v : T::IType, i : &Instruction::<T>, isa : &ISA::<T>) -> String {
+    let s = try_instruction::<T>(v, i, isa).expect("Found instruction should match!");
+    match class(v, i) {
+        Class::Normal => s,
+        Class::Hint => s + " # hint",
+        Class::Reserved => s + " # reserved",
+    }
+}
+
+/// raw instruction of unknown encoding in .insn form, bytes are given in little-endian order
+pub fn raw_insn(bytes : &[u8]) -> String {
+    let hex : String = bytes.iter().rev().map(|b| format!("{:02x}", b)).collect();
+    format!(".insn {}, 0x{}", bytes.len(), hex)
+}
+
+/// find instruction which matches given word. Some instructions are carved out of encodings
+/// of more general ones (like pause out of fence), so the most specific one wins, that is the
+/// one with more fixed bits, and then the one with more constraints on ident values.
+/// Between equally specific instructions the first in the list wins
+pub fn find_instruction<T:Num>(v : T::IType, isa : &ISA::<T>) -> Option<&Instruction::<T>> {
+    isa.list.iter()
+        .filter(|i| v & i.mask() == i.pattern() && satisfies(v, i))
+        .rev()
+        .max_by_key(|i| i.specificity())
+}
+
+/// class of matched encoding, instruction could mark some of its encodings as hint or reserved
+fn class<T:Num>(v : T::IType, i : &Instruction::<T>) -> Class {
+    if i.marks.is_empty() {
+        return i.class;
+    }
+    let vars = extract_idents(v, &i.bin);
+    i.class_of(&|name| vars.iter().find(|(n, _, _)| n == name).and_then(|(_, v, _)| v.to_u64()))
+}
+
+/// check constraints of instruction on ident values of given word
+fn satisfies<T:Num>(v : T::IType, i : &Instruction::<T>) -> bool {
+    if i.constraints.is_empty() {
+        return true;
+    }
+    let vars = extract_idents(v, &i.bin);
+    i.satisfies(&|name| vars.iter().find(|(n, _, _)| n == name).and_then(|(_, v, _)| v.to_u64()))
 }
 
 /// extract from given instruction bit for idents and return tuples of (ident, val, start_bit)
@@ -38,7 +158,14 @@ fn extract_idents<T:Num>( val : T::IType, instr : &BinaryInstruction::<T>) -> Ve
                      }
                      current_bit -= 1;
                 }
-                result.push((name.clone(), v, *m));
+                //ident could be split to several parts, like imm of store instructions
+                match result.iter_mut().find(|(n, _, _)| n == name) {
+                    Some( (_, pv, ps) ) => {
+                        *pv = *pv | v;
+                        *ps = std::cmp::min(*ps, *m);
+                    },
+                    None => result.push((name.clone(), v, *m)),
+                }
             }
         }
     }
@@ -47,8 +174,8 @@ fn extract_idents<T:Num>( val : T::IType, instr : &BinaryInstruction::<T>) -> Ve
 
 /// try to find corespondence between given word and given instruction,
 /// if found return text description of instruction, otherwise None
-pub fn try_instruction<T:Num>( v : T::IType, i : &Instruction::<T>, show_dict : &ShowDict::<T> ) -> Option<String> {
-    if ! ( v & i.mask() == i.pattern() ) {
+pub fn try_instruction<T:Num>( v : T::IType, i : &Instruction::<T>, isa : &ISA::<T> ) -> Option<String> {
+    if v & i.mask() != i.pattern() || !satisfies(v, i) {
         return None;
     }
 
@@ -57,18 +184,28 @@ pub fn try_instruction<T:Num>( v : T::IType, i : &Instruction::<T>, show_dict :
     let mut str = String::new();
     for t in &i.text.list {
         match t {
-            TextInstructionPart::Text( s ) => str = str + &s[..],
-            TextInstructionPart::TextIdent( s1, ident ) => {
+            TextInstructionPart::Text( s ) => str += &s[..],
+            TextInstructionPart::TextIdent( s1, ident ) | TextInstructionPart::TypedIdent( s1, ident, _ ) => {
                 let attr = match vars.iter().find(|(n, _, _)| n == ident) {
                     None => String::from("****"),
                     Some((_n, v, _s)) => {
-                        match show_dict.get(ident) {
-                            None => format!("{:#X}", *v),
-                            Some( f ) => f(*v),
+                        match (isa.show_fields_dict.get(ident), i.text.kind(ident), isa.show_dict.get(ident)) {
+                            (Some( f ), _, _) => f(*v, &vars),
+                            (None, Some( kind ), _) => {
+                                let width = kind.width().unwrap_or_else(|| ident_width(&i.bin.list, ident));
+                                show_operand(kind, v.to_u64().unwrap_or(0), width)
+                            },
+                            (None, None, Some( f )) => f(*v),
+                            (None, None, None) => format!("{:#X}", *v),
                         }
                     },
                 };
-                str = str + &s1[..] + &attr[..];
+                if attr.is_empty() {
+                    //optional operand (like vector mask) is omitted together with its separator
+                    str += s1.trim_end_matches([',', ' ']);
+                } else {
+                    str = str + &s1[..] + &attr[..];
+                }
             },
         }
     }
@@ -95,4 +232,59 @@ mod test {
         assert_eq!(*v, 0xAAA); //Note: value not shifted by start bit
         assert_eq!(*start, 1);
     }
+
+    #[test]
+    fn most_specific() {
+        let list = vec![ asdis::instruction32!("fence imm", imm[11:0], 00000000000000001111),
+                         asdis::instruction32!("pause", 00000001000000000000000000001111),
+                         asdis::instruction32!("fence.same", imm[11:0], 00000000000000001111) ];
+        let isa = ISA::<RV32Type> { list, ..Default::default() };
+        assert_eq!(decode(0x0100000f, &isa), "pause");
+        assert_eq!(decode(0x0ff0000f, &isa), "fence 0xFF");
+    }
+
+    #[test]
+    fn long() {
+        let list = vec![ asdis::instruction48!("acc.mac rd, rs1, imm", imm[27:0], rs1[4:0], 000, rd[4:0], 0011111) ];
+        let isa = ISA::<Long48Type> { list, ..Default::default() };
+        assert_eq!(decode(0x1234_5672_059f, &isa), "acc.mac 0xB, 0x4, 0x1234567");
+        assert_eq!(try_decode(0x1234_5672_05bf, &isa), None);
+        assert_eq!(raw_insn(&[0x3f, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06]), ".insn 8, 0x060504030201003f");
+    }
+
+    #[test]
+    fn constraints() {
+        let list = vec![ asdis::instruction32!("addi rd, rs1, imm", imm[11:0], rs1[4:0], 000 ,rd[4:0], 0010011),
+                         asdis::instruction32!("addi rd, rs1, imm", imm[11:0], rs1[4:0], 000 ,rd[4:0], 0010011)
+                             .when(Constraint::eq("rd", 0)).hint() ];
+        let isa = ISA::<RV32Type> { list, ..Default::default() };
+        assert_eq!(decode(0x00508093, &isa), "addi 0x1, 0x1, 0x5");
+        assert_eq!(decode(0x00508013, &isa), "addi 0x0, 0x1, 0x5 # hint");
+
+        //predicates given in instruction description
+        let list = vec![ asdis::instruction32!("lui rd, imm", imm[31:12], rd[4:0], 0110111, rd != {0, 2}),
+                         asdis::instruction32!("lui rd, imm", imm[31:12], rd[4:0], 0110111, rd == {0, 2}, imm == 0).reserved() ];
+        let isa = ISA::<RV32Type> { list, ..Default::default() };
+        assert_eq!(isa.list[0].constraints, vec![ Constraint::ne("rd", 0), Constraint::ne("rd", 2) ]);
+        assert_eq!(decode(0x000010b7, &isa), "lui 0x1, 0x1000");
+        assert_eq!(decode(0x00000137, &isa), "lui 0x2, 0x0 # reserved");
+        assert_eq!(try_decode(0x00001137, &isa), None);
+    }
+
+    #[test]
+    fn marks() {
+        //the first satisfied mark wins
+        let list = vec![ asdis::instruction16!("c.lui rd, imm", 011, imm[17], rd[4:0], imm[16:12], 01, reserved if imm == 0, hint if rd == 0) ];
+        let isa = ISA::<CompactType> { list, ..Default::default() };
+        assert_eq!(isa.list[0].marks, vec![ (Constraint::eq("imm", 0), Class::Reserved), (Constraint::eq("rd", 0), Class::Hint) ]);
+        assert_eq!(decode(0x6185, &isa), "c.lui 0x3, 0x1000");
+        assert_eq!(decode(0x6005, &isa), "c.lui 0x0, 0x1000 # hint");
+        assert_eq!(decode(0x6001, &isa), "c.lui 0x0, 0x0 # reserved");
+        assert_eq!(decode_details(0x6005, &isa).map(|d| d.class), Some( Class::Hint ));
+
+        let list = vec![ asdis::instruction16!("c.srli rdp, imm", 100, imm[5], 00, rdp[2:0], imm[4:0], 01, reserved if imm >= 32) ];
+        let isa = ISA::<CompactType> { list, ..Default::default() };
+        assert_eq!(decode(0x8105, &isa), "c.srli 0x2, 0x1");
+        assert_eq!(decode(0x9105, &isa), "c.srli 0x2, 0x21 # reserved");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,38 @@
+/// length of instruction in bytes, given by its lowest 16 bits. Formats of 192 bits and longer
+/// are reserved, None is returned for them
+pub fn instruction_length(v : u16) -> Option<usize> {
+    if v & 0b11 != 0b11 {
+        Some( 2 )
+    } else if v & 0b11100 != 0b11100 {
+        Some( 4 )
+    } else if v & 0b111111 == 0b011111 {
+        Some( 6 )
+    } else if v & 0b1111111 == 0b0111111 {
+        Some( 8 )
+    } else if v & 0b1111111 == 0b1111111 {
+        //(80 + 16*nnn)-bit formats, nnn=111 is reserved
+        match (v >> 12) & 0b111 {
+            0b111 => None,
+            n => Some( 10 + 2 * n as usize ),
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lengths() {
+        assert_eq!(instruction_length(0x0001), Some( 2 ));
+        assert_eq!(instruction_length(0x0013), Some( 4 ));
+        assert_eq!(instruction_length(0x001f), Some( 6 ));
+        assert_eq!(instruction_length(0x005f), Some( 6 ));
+        assert_eq!(instruction_length(0x003f), Some( 8 ));
+        assert_eq!(instruction_length(0x007f), Some( 10 ));
+        assert_eq!(instruction_length(0x607f), Some( 22 ));
+        assert_eq!(instruction_length(0x707f), None);
+    }
+}
@@ -1,4 +1,5 @@
 mod decode;
+mod length;
 
-pub use decode::decode;
-
+pub use decode::{decode, try_decode, decode_set, raw_insn, decode_details, decode_details_set, Decoded};
+pub use length::instruction_length;
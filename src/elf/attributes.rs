@@ -0,0 +1,188 @@
+//! Build attributes of .riscv.attributes section
+
+use std::fmt;
+
+const TAG_FILE : u8 = 1;
+pub const TAG_RISCV_STACK_ALIGN : u64 = 4;
+pub const TAG_RISCV_ARCH : u64 = 5;
+
+///names of known RISC-V attribute tags
+const TAG_NAMES : [(u64, &str); 8] = [
+    (4, "Tag_RISCV_stack_align"),
+    (5, "Tag_RISCV_arch"),
+    (6, "Tag_RISCV_unaligned_access"),
+    (8, "Tag_RISCV_priv_spec"),
+    (10, "Tag_RISCV_priv_spec_minor"),
+    (12, "Tag_RISCV_priv_spec_revision"),
+    (14, "Tag_RISCV_atomic_abi"),
+    (16, "Tag_RISCV_x3_reg_usage"),
+];
+
+///value of attribute, odd tags have string values, even ones have integer values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(u64),
+    Str(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub tag : u64,
+    pub value : Value,
+}
+
+///attributes of whole file, attributes of sections and symbols are skipped
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Attributes {
+    pub list : Vec<Attribute>,
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match TAG_NAMES.iter().find(|(t, _)| *t == self.tag) {
+            Some( (_, name) ) => write!(f, "{}: ", name)?,
+            None => write!(f, "Tag_unknown_{}: ", self.tag)?,
+        }
+        match &self.value {
+            Value::Int( v ) => write!(f, "{}", v),
+            Value::Str( s ) => write!(f, "{}", s),
+        }
+    }
+}
+
+///reader of attribute section contents
+struct Reader<'a> {
+    data : &'a [u8],
+    pos : usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let b = *self.data.get(self.pos).ok_or("Unexpected end of attributes")?;
+        self.pos += 1;
+        Ok( b )
+    }
+
+    fn u32(&mut self) -> Result<usize, String> {
+        let mut v = 0;
+        for n in 0..4 {
+            v |= (self.byte()? as usize) << (8 * n);
+        }
+        Ok( v )
+    }
+
+    fn uleb128(&mut self) -> Result<u64, String> {
+        let mut v = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = self.byte()?;
+            if shift < 64 {
+                v |= ((b & 0x7F) as u64) << shift;
+            }
+            shift += 7;
+            if b & 0x80 == 0 {
+                return Ok( v );
+            }
+        }
+    }
+
+    fn ntbs(&mut self) -> Result<String, String> {
+        let tail = self.data.get(self.pos..).unwrap_or(&[]);
+        let end = tail.iter().position(|b| *b == 0).ok_or("String is not terminated")?;
+        self.pos += end + 1;
+        Ok( String::from_utf8_lossy(&tail[..end]).into_owned() )
+    }
+}
+
+impl Attributes {
+    ///parse section contents: format version 'A', then subsections of vendors, only "riscv" one is used
+    pub fn parse(data : &[u8]) -> Result<Attributes, String> {
+        let mut attributes = Attributes::default();
+        if data.is_empty() {
+            return Ok( attributes );
+        }
+        let mut r = Reader { data, pos : 0 };
+        if r.byte()? != b'A' {
+            return Err( String::from("Unknown format version of attributes") );
+        }
+        while r.pos < data.len() {
+            let start = r.pos;
+            let end = start.checked_add(r.u32()?).ok_or("Wrong length of attributes subsection")?;
+            if end <= start || end > data.len() {
+                return Err( String::from("Wrong length of attributes subsection") );
+            }
+            if r.ntbs()? == "riscv" {
+                while r.pos < end {
+                    let sub_start = r.pos;
+                    let tag = r.byte()?;
+                    let sub_end = sub_start.checked_add(r.u32()?).ok_or("Wrong length of attributes subsection")?;
+                    if sub_end <= sub_start || sub_end > end {
+                        return Err( String::from("Wrong length of attributes subsection") );
+                    }
+                    while tag == TAG_FILE && r.pos < sub_end {
+                        let tag = r.uleb128()?;
+                        let value = match tag % 2 {
+                            0 => Value::Int( r.uleb128()? ),
+                            _ => Value::Str( r.ntbs()? ),
+                        };
+                        attributes.list.push( Attribute { tag, value } );
+                    }
+                    r.pos = sub_end;
+                }
+            }
+            r.pos = end;
+        }
+        Ok( attributes )
+    }
+
+    ///ISA string of Tag_RISCV_arch
+    pub fn arch(&self) -> Option<&str> {
+        self.list.iter().find_map(|a| match &a.value {
+            Value::Str( s ) if a.tag == TAG_RISCV_ARCH => Some( s.as_str() ),
+            _ => None,
+        })
+    }
+
+    ///stack alignment in bytes of Tag_RISCV_stack_align
+    pub fn stack_align(&self) -> Option<u64> {
+        self.list.iter().find_map(|a| match a.value {
+            Value::Int( v ) if a.tag == TAG_RISCV_STACK_ALIGN => Some( v ),
+            _ => None,
+        })
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    ///attributes section with arch, stack_align=16 and unaligned_access=0
+    pub fn section(arch : &str) -> Vec<u8> {
+        let mut attrs = vec![5u8];
+        attrs.extend_from_slice(arch.as_bytes());
+        attrs.extend_from_slice(&[0, 4, 16, 6, 0]);
+        let mut sub = vec![TAG_FILE];
+        sub.extend_from_slice(&(attrs.len() as u32 + 5).to_le_bytes());
+        sub.extend_from_slice(&attrs);
+        let mut data = vec![b'A'];
+        data.extend_from_slice(&(sub.len() as u32 + 10).to_le_bytes());
+        data.extend_from_slice(b"riscv\0");
+        data.extend_from_slice(&sub);
+        data
+    }
+
+    #[test]
+    fn parse() {
+        let a = Attributes::parse(&section("rv32i2p1_m2p0_c2p0_zicsr2p0")).unwrap();
+        assert_eq!(a.list.len(), 3);
+        assert_eq!(a.arch(), Some( "rv32i2p1_m2p0_c2p0_zicsr2p0" ));
+        assert_eq!(a.stack_align(), Some( 16 ));
+        assert_eq!(a.list[1].to_string(), "Tag_RISCV_stack_align: 16");
+        assert_eq!(a.list[2].to_string(), "Tag_RISCV_unaligned_access: 0");
+        assert!(Attributes::parse(b"B").is_err());
+        assert!(Attributes::parse(b"A\x40\0\0\0riscv\0").is_err());
+        assert!(Attributes::parse(b"A\xff\xff\xff\xffriscv\0").is_err());
+        assert!(Attributes::parse(b"A\x15\0\0\0riscv\0\x01\xff\xff\xff\xff\x05rv\0").is_err());
+    }
+}
@@ -0,0 +1,380 @@
+//! Minimal reader of little-endian RISC-V ELF files, only section headers are used
+
+use crate::isa::Xlen;
+use crate::elf::attributes::Attributes;
+
+pub const EM_RISCV : u16 = 243;
+pub const SHT_RISCV_ATTRIBUTES : u32 = 0x7000_0003;
+pub const SHF_EXECINSTR : u64 = 0x4;
+const SHF_ALLOC : u64 = 0x2;
+const SHT_SYMTAB : u32 = 2;
+const SHT_NOBITS : u32 = 8;
+pub const STT_FUNC : u8 = 2;
+pub const STT_FILE : u8 = 4;
+pub const SHN_UNDEF : u16 = 0;
+
+const EF_RISCV_RVC : u32 = 0x1;
+const EF_RISCV_FLOAT_ABI : u32 = 0x6;
+const EF_RISCV_RVE : u32 = 0x8;
+const EF_RISCV_TSO : u32 = 0x10;
+
+///section header, name is resolved from section names table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name : String,
+    pub sh_type : u32,
+    pub flags : u64,
+    pub addr : u64,
+    pub offset : u64,
+    pub size : u64,
+    ///index of linked section, like string table of symbol table
+    pub link : u32,
+}
+
+///symbol of symbol table, kind is the type from st_info, like STT_FUNC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name : String,
+    pub value : u64,
+    pub size : u64,
+    pub kind : u8,
+    ///index of section the symbol is defined in
+    pub shndx : u16,
+}
+
+impl Symbol {
+    ///true if symbol names an address of this file: it is defined, and is neither a source file name
+    ///nor a mapping symbol
+    pub fn is_label(&self) -> bool {
+        !self.name.is_empty() && !self.name.starts_with('$') && self.kind != STT_FILE && self.shndx != SHN_UNDEF
+    }
+}
+
+///ELF file, data is borrowed from file contents
+#[derive(Debug)]
+pub struct Elf<'a> {
+    pub xlen : Xlen,
+    pub flags : u32,
+    pub entry : u64,
+    pub sections : Vec<Section>,
+    data : &'a [u8],
+}
+
+///sum of offsets read from file, overflow means the file is corrupt
+fn add(a : u64, b : u64) -> Result<u64, String> {
+    a.checked_add(b).ok_or_else(|| format!("Offset {:#X} + {:#X} is out of range", a, b))
+}
+
+///product of count and size read from file, overflow means the file is corrupt
+fn mul(a : u64, b : u64) -> Result<u64, String> {
+    a.checked_mul(b).ok_or_else(|| format!("Offset {:#X} * {:#X} is out of range", a, b))
+}
+
+///read little-endian integer of N bytes at given offset
+fn read<const N : usize>(data : &[u8], offset : u64) -> Result<u64, String> {
+    let end = add(offset, N as u64)?;
+    let bytes = data.get(offset as usize..end as usize).ok_or_else(|| format!("Offset {:#X} is out of file", offset))?;
+    Ok( bytes.iter().rev().fold(0, |v, b| (v << 8) | *b as u64) )
+}
+
+///read address sized field, 4 bytes for ELF32 and 8 bytes for ELF64
+fn read_word(data : &[u8], offset : u64, xlen : Xlen) -> Result<u64, String> {
+    match xlen {
+        Xlen::X32 => read::<4>(data, offset),
+        Xlen::X64 => read::<8>(data, offset),
+    }
+}
+
+///zero terminated string at given offset
+fn read_str(data : &[u8], offset : u64) -> Result<String, String> {
+    let tail = data.get(offset as usize..).ok_or_else(|| format!("Offset {:#X} is out of file", offset))?;
+    let end = tail.iter().position(|b| *b == 0).ok_or("String is not terminated")?;
+    Ok( String::from_utf8_lossy(&tail[..end]).into_owned() )
+}
+
+impl<'a> Elf<'a> {
+    ///check ELF magic
+    pub fn is_elf(data : &[u8]) -> bool {
+        data.starts_with(b"\x7fELF")
+    }
+
+    ///parse ELF header and section headers
+    pub fn parse(data : &'a [u8]) -> Result<Elf<'a>, String> {
+        if !Elf::is_elf(data) {
+            return Err( String::from("Not an ELF file") );
+        }
+        let xlen = match read::<1>(data, 4)? {
+            1 => Xlen::X32,
+            2 => Xlen::X64,
+            c => return Err( format!("Unknown ELF class {}", c) ),
+        };
+        if read::<1>(data, 5)? != 1 {
+            return Err( String::from("Only little-endian ELF files are supported") );
+        }
+        let machine = read::<2>(data, 18)? as u16;
+        if machine != EM_RISCV {
+            return Err( format!("Not a RISC-V ELF file, machine is {}", machine) );
+        }
+
+        //offsets of header fields which follow address sized fields
+        let (shoff, flags, shentsize, shnum, shstrndx) = match xlen {
+            Xlen::X32 => (32, 36, 46, 48, 50),
+            Xlen::X64 => (40, 48, 58, 60, 62),
+        };
+        let entry = read_word(data, 24, xlen)?;
+        let shoff = read_word(data, shoff, xlen)?;
+        let flags = read::<4>(data, flags)? as u32;
+        let shentsize = read::<2>(data, shentsize)?;
+        let shnum = read::<2>(data, shnum)?;
+        let shstrndx = read::<2>(data, shstrndx)?;
+
+        let mut headers = Vec::new();
+        for n in 0..shnum {
+            let h = add(shoff, mul(n, shentsize)?)?;
+            let w = match xlen { Xlen::X32 => 4, Xlen::X64 => 8 };
+            let name = read::<4>(data, h)?;
+            let section = Section {
+                name : String::new(),
+                sh_type : read::<4>(data, add(h, 4)?)? as u32,
+                flags : read_word(data, add(h, 8)?, xlen)?,
+                addr : read_word(data, add(h, 8 + w)?, xlen)?,
+                offset : read_word(data, add(h, 8 + 2 * w)?, xlen)?,
+                size : read_word(data, add(h, 8 + 3 * w)?, xlen)?,
+                link : read::<4>(data, add(h, 8 + 4 * w)?)? as u32,
+            };
+            headers.push( (name, section) );
+        }
+
+        let strtab = headers.get(shstrndx as usize).map(|(_, s)| s.offset);
+        let mut sections = Vec::new();
+        for (name, mut section) in headers {
+            if let Some( strtab ) = strtab {
+                section.name = read_str(data, add(strtab, name)?)?;
+            }
+            sections.push( section );
+        }
+
+        Ok( Elf { xlen, flags, entry, sections, data } )
+    }
+
+    ///contents of section
+    pub fn section_data(&self, s : &Section) -> Result<&'a [u8], String> {
+        let end = add(s.offset, s.size)?;
+        self.data.get(s.offset as usize..end as usize)
+            .ok_or_else(|| format!("Section {} is out of file", s.name))
+    }
+
+    ///sections with instructions
+    pub fn executable(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(|s| s.flags & SHF_EXECINSTR != 0)
+    }
+
+    ///sections which are loaded to memory and have contents in file
+    pub fn loaded(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(|s| s.flags & SHF_ALLOC != 0 && s.sh_type != SHT_NOBITS)
+    }
+
+    ///symbols of all symbol tables, the null symbol is skipped
+    pub fn symbols(&self) -> Result<Vec<Symbol>, String> {
+        let mut symbols = Vec::new();
+        for s in self.sections.iter().filter(|s| s.sh_type == SHT_SYMTAB) {
+            let strtab = self.sections.get(s.link as usize).ok_or("Wrong string table of symbols")?;
+            let entsize = match self.xlen { Xlen::X32 => 16, Xlen::X64 => 24 };
+            for n in 1..s.size / entsize {
+                let h = add(s.offset, n * entsize)?;
+                //fields of Elf32_Sym and Elf64_Sym are in different order
+                let (value, size, info, shndx) = match self.xlen {
+                    Xlen::X32 => (read::<4>(self.data, add(h, 4)?)?, read::<4>(self.data, add(h, 8)?)?, read::<1>(self.data, add(h, 12)?)?, read::<2>(self.data, add(h, 14)?)?),
+                    Xlen::X64 => (read::<8>(self.data, add(h, 8)?)?, read::<8>(self.data, add(h, 16)?)?, read::<1>(self.data, add(h, 4)?)?, read::<2>(self.data, add(h, 6)?)?),
+                };
+                let name = read_str(self.data, add(strtab.offset, read::<4>(self.data, h)?)?)?;
+                symbols.push( Symbol { name, value, size, kind : (info & 0xF) as u8, shndx : shndx as u16 } );
+            }
+        }
+        Ok( symbols )
+    }
+
+    ///parsed .riscv.attributes section, if any
+    pub fn attributes(&self) -> Result<Option<Attributes>, String> {
+        match self.sections.iter().find(|s| s.sh_type == SHT_RISCV_ATTRIBUTES) {
+            Some( s ) => Attributes::parse( self.section_data(s)? ).map(Some),
+            None => Ok( None ),
+        }
+    }
+
+    ///ISA string from Tag_RISCV_arch, or guessed from class and flags.
+    ///M and A are not recorded in flags, so they are assumed
+    pub fn march(&self) -> Result<String, String> {
+        if let Some( arch ) = self.attributes()?.as_ref().and_then(|a| a.arch()) {
+            return Ok( String::from(arch) );
+        }
+        let mut s = String::from( match self.xlen { Xlen::X32 => "rv32", Xlen::X64 => "rv64" } );
+        s.push( if self.flags & EF_RISCV_RVE != 0 { 'e' } else { 'i' } );
+        s.push_str("ma");
+        s.push_str( match (self.flags & EF_RISCV_FLOAT_ABI) >> 1 {
+            0 => "",
+            1 => "f",
+            _ => "fd",
+        });
+        if self.flags & EF_RISCV_RVC != 0 {
+            s.push('c');
+        }
+        s.push_str("_zicsr_zifencei");
+        Ok( s )
+    }
+
+    ///human readable flags
+    pub fn describe_flags(&self) -> String {
+        let mut list = Vec::new();
+        if self.flags & EF_RISCV_RVC != 0 {
+            list.push("RVC");
+        }
+        list.push( match (self.flags & EF_RISCV_FLOAT_ABI) >> 1 {
+            0 => "soft-float ABI",
+            1 => "single-float ABI",
+            2 => "double-float ABI",
+            _ => "quad-float ABI",
+        });
+        if self.flags & EF_RISCV_RVE != 0 {
+            list.push("RVE");
+        }
+        if self.flags & EF_RISCV_TSO != 0 {
+            list.push("TSO");
+        }
+        format!("{:#X}, {}", self.flags, list.join(", "))
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    ///minimal ELF32 file with .text, .riscv.attributes and .shstrtab sections
+    pub fn build(text : &[u8], attributes : &[u8], flags : u32) -> Vec<u8> {
+        build_with_symbols(text, attributes, flags, &[])
+    }
+
+    ///minimal ELF32 file, entry is at start of .text at 0x1000. If symbols are given, given as tuples
+    ///of (name, value, st_info), .symtab and .strtab sections are added
+    pub fn build_with_symbols(text : &[u8], attributes : &[u8], flags : u32, symbols : &[(&str, u32, u8)]) -> Vec<u8> {
+        let mut names = b"\0.text\0.riscv.attributes\0.shstrtab\0".to_vec();
+        let mut strtab = vec![0u8];
+        let mut symtab = vec![0u8; 16];
+        for (name, value, info) in symbols {
+            symtab.extend_from_slice(&(strtab.len() as u32).to_le_bytes());
+            symtab.extend_from_slice(&value.to_le_bytes());
+            symtab.extend_from_slice(&0u32.to_le_bytes());
+            symtab.extend_from_slice(&[*info, 0, 1, 0]);
+            strtab.extend_from_slice(name.as_bytes());
+            strtab.push(0);
+        }
+        if !symbols.is_empty() {
+            names.extend_from_slice(b".symtab\0.strtab\0");
+        }
+
+        let mut data = vec![0u8; 52];
+        data[..6].copy_from_slice(b"\x7fELF\x01\x01");
+        data[18..20].copy_from_slice(&EM_RISCV.to_le_bytes());
+        data[24..28].copy_from_slice(&0x1000u32.to_le_bytes());
+        data[36..40].copy_from_slice(&flags.to_le_bytes());
+
+        //tuples of (name, type, flags, addr, contents, link)
+        let mut sections = vec![(1u32, 1u32, 6u32, 0x1000u32, text, 0u32), (7, SHT_RISCV_ATTRIBUTES, 0, 0, attributes, 0), (25, 3, 0, 0, &names[..], 0)];
+        if !symbols.is_empty() {
+            sections.push( (35, SHT_SYMTAB, 0, 0, &symtab[..], 5) );
+            sections.push( (43, 3, 0, 0, &strtab[..], 0) );
+        }
+        let mut offsets = Vec::new();
+        for (_, _, _, _, d, _) in &sections {
+            offsets.push( data.len() as u32 );
+            data.extend_from_slice(d);
+        }
+        let shoff = data.len() as u32;
+        data[32..36].copy_from_slice(&shoff.to_le_bytes());
+        data[46..48].copy_from_slice(&40u16.to_le_bytes());
+        data[48..50].copy_from_slice(&(sections.len() as u16 + 1).to_le_bytes());
+        data[50..52].copy_from_slice(&3u16.to_le_bytes());
+
+        data.extend_from_slice(&[0; 40]);
+        for ((name, sh_type, flags, addr, d, link), offset) in sections.iter().zip(offsets) {
+            let mut h = [0u8; 40];
+            for (n, v) in [*name, *sh_type, *flags, *addr, offset, d.len() as u32, *link].iter().enumerate() {
+                h[n * 4..n * 4 + 4].copy_from_slice(&v.to_le_bytes());
+            }
+            data.extend_from_slice(&h);
+        }
+        data
+    }
+
+    #[test]
+    fn sections() {
+        let data = build(&[0x13, 0, 0, 0], b"", 0x5);
+        let elf = Elf::parse(&data).unwrap();
+        assert_eq!(elf.xlen, Xlen::X32);
+        assert_eq!(elf.sections.len(), 4);
+        let text : Vec<&Section> = elf.executable().collect();
+        assert_eq!(text.len(), 1);
+        assert_eq!(text[0].name, ".text");
+        assert_eq!(text[0].addr, 0x1000);
+        assert_eq!(elf.section_data(text[0]).unwrap(), &[0x13, 0, 0, 0]);
+        assert_eq!(elf.describe_flags(), "0x5, RVC, double-float ABI");
+        assert!(Elf::parse(b"\x7fELF\x01\x02").is_err());
+    }
+
+    #[test]
+    fn symbols() {
+        let data = build_with_symbols(&[0x13, 0, 0, 0], b"", 0x5, &[("_start", 0x1000, STT_FUNC), ("$x", 0x1000, 0)]);
+        let elf = Elf::parse(&data).unwrap();
+        assert_eq!(elf.entry, 0x1000);
+        let symbols = elf.symbols().unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0], Symbol { name : String::from("_start"), value : 0x1000, size : 0, kind : STT_FUNC, shndx : 1 });
+        assert_eq!(symbols[1].name, "$x");
+        let loaded : Vec<&str> = elf.loaded().map(|s| &s.name[..]).collect();
+        assert_eq!(loaded, vec![".text"]);
+        assert!(Elf::parse(&build(&[], b"", 0)).unwrap().symbols().unwrap().is_empty());
+    }
+
+    #[test]
+    fn labels() {
+        let symbol = |name : &str, kind, shndx| Symbol { name : String::from(name), value : 0, size : 0, kind, shndx };
+        assert!(symbol("_start", STT_FUNC, 1).is_label());
+        assert!(!symbol("$x", 0, 1).is_label());
+        assert!(!symbol("main.c", STT_FILE, 0xfff1).is_label());
+        assert!(!symbol("printf", STT_FUNC, SHN_UNDEF).is_label());
+    }
+
+    #[test]
+    fn march() {
+        let data = build(&[], b"", 0x3);
+        assert_eq!(Elf::parse(&data).unwrap().march().unwrap(), "rv32imafc_zicsr_zifencei");
+        let data = build(&[], &crate::elf::attributes::test::section("rv32i2p1_c2p0"), 0x3);
+        assert_eq!(Elf::parse(&data).unwrap().march().unwrap(), "rv32i2p1_c2p0");
+    }
+
+    #[test]
+    fn corrupt() {
+        //section headers at the end of address space
+        let mut data = build(&[0x13, 0, 0, 0], b"", 0);
+        data[32..36].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        assert!(Elf::parse(&data).is_err());
+
+        //section size which overflows offset
+        let mut data = build(&[0x13, 0, 0, 0], b"", 0);
+        let shoff = read::<4>(&data, 32).unwrap() as usize;
+        data[shoff + 40 + 16..shoff + 40 + 20].copy_from_slice(&0xFFFF_FFFFu32.to_le_bytes());
+        let elf = Elf::parse(&data).unwrap();
+        let text = elf.executable().next().unwrap();
+        assert!(elf.section_data(text).is_err());
+
+        //section header offset of ELF64 near the end of 64-bit address space
+        let mut data = vec![0u8; 64];
+        data[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        data[18..20].copy_from_slice(&EM_RISCV.to_le_bytes());
+        data[40..48].copy_from_slice(&u64::MAX.to_le_bytes());
+        data[58..60].copy_from_slice(&64u16.to_le_bytes());
+        data[60..62].copy_from_slice(&2u16.to_le_bytes());
+        assert!(Elf::parse(&data).is_err());
+    }
+}
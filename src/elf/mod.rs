@@ -0,0 +1,4 @@
+mod elf;
+mod attributes;
+
+pub use elf::{Elf, STT_FUNC, SHN_UNDEF};
@@ -0,0 +1,239 @@
+use crate::primitives::*;
+use crate::isa::*;
+use num_traits::{One, Zero, NumCast, PrimInt, ToPrimitive};
+
+/// encode given text description of instruction using instruction list, returns binary word
+pub fn encode<T:Num>(text : &str, isa : &ISA::<T>) -> Option<T::IType> {
+    for i in &isa.list {
+        if let Some( v ) = try_instruction::<T>(text, i, isa)  {
+            return Some( v );
+        }
+    }
+    None
+}
+
+/// encode given text using all fragments of set, fragments are tried in order they were added
+pub fn encode_set<T:Num>(text : &str, set : &IsaSet::<T>) -> Option<T::IType> {
+    set.fragments.iter().find_map(|f| encode(text, &f.isa))
+}
+
+/// where token of operand ends: at given character, at whitespace, or at the end of the line
+enum TokenEnd {
+    Char(char),
+    Space,
+    Line,
+}
+
+/// find end of operand token, characters inside of (), {} and [] are not checked against terminator
+fn token_end(text : &str, end : &TokenEnd) -> usize {
+    let mut depth = 0;
+    for (n, c) in text.char_indices() {
+        if depth == 0 {
+            match end {
+                TokenEnd::Char( t ) if c == *t => return n,
+                TokenEnd::Space if c.is_whitespace() => return n,
+                _ => (),
+            }
+        }
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' if depth > 0 => depth -= 1,
+            _ => (),
+        }
+    }
+    text.len()
+}
+
+/// match given text against textual description of instruction, returns tuples of (ident, token)
+/// whitespaces are not significant, except the one after mnemonic. If text ends before all
+/// operands are found, the rest of operands gets empty tokens (optional operands like vector mask)
+fn match_text<'a, 'b>(text : &'a str, instr : &'b TextInstruction) -> Option<Vec<(&'b str, &'a str)>> {
+    let mut parts : Vec<(&str, Option<&str>)> = instr.list.iter().map(|t| match t {
+        TextInstructionPart::Text( s ) => (&s[..], None),
+        TextInstructionPart::TextIdent( s, ident ) | TextInstructionPart::TypedIdent( s, ident, _ ) => (&s[..], Some( &ident[..] )),
+    }).collect();
+
+    let mnemonic = parts.first()?.0.split_whitespace().next()?;
+    let text = text.trim();
+    let (m, mut rest) = match text.find(char::is_whitespace) {
+        None => (text, ""),
+        Some( n ) => (&text[..n], &text[n..]),
+    };
+    if m != mnemonic {
+        return None;
+    }
+    parts[0].0 = &parts[0].0[mnemonic.len()..];
+
+    let mut result = Vec::new();
+    let mut exhausted = false;
+    for (k, (literal, ident)) in parts.iter().enumerate() {
+        for c in literal.chars().filter(|c| !c.is_whitespace()) {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                exhausted = true;
+            }
+            if exhausted {
+                break;
+            }
+            rest = rest.strip_prefix(c)?;
+        }
+        if let Some( ident ) = ident {
+            rest = rest.trim_start();
+            if exhausted || rest.is_empty() {
+                exhausted = true;
+                result.push( (*ident, "") );
+                continue;
+            }
+            let end = match parts.get(k + 1) {
+                None => TokenEnd::Line,
+                Some( (next, _) ) => match next.chars().find(|c| !c.is_whitespace()) {
+                    None => TokenEnd::Space,
+                    Some( c ) => TokenEnd::Char( c ),
+                },
+            };
+            let n = token_end(rest, &end);
+            result.push( (*ident, rest[..n].trim_end()) );
+            rest = &rest[n..];
+        }
+    }
+
+    if !rest.trim().is_empty() {
+        return None;
+    }
+    Some( result )
+}
+
+/// parse number in decimal or 0x prefixed hexadecimal form, negative numbers are stored as two's complement
+fn parse_number<T:Num>(s : &str) -> Option<T::DType> {
+    let (negative, s) = match s.strip_prefix('-') {
+        None => (false, s),
+        Some( s ) => (true, s),
+    };
+    let v = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        None => s.parse::<u64>().ok()?,
+        Some( h ) => u64::from_str_radix(h, 16).ok()?,
+    };
+    let v = if negative { v.wrapping_neg() } else { v };
+    to_dtype::<T>(v)
+}
+
+/// truncate two's complement value to bits of DType
+fn to_dtype<T:Num>(v : u64) -> Option<T::DType> {
+    let bits = T::d_zero().count_zeros();
+    let v = if bits < 64 { v & ((1_u64 << bits) - 1) } else { v };
+    NumCast::from(v)
+}
+
+/// check that value fits into bits covered by bitspecs: uncovered bits must be zero, or, for
+/// negative values, a sign extension of the highest covered bit
+fn fits<T:Num>(v : T::DType, covered : T::DType) -> bool {
+    let one = T::DType::one();
+    if v & !covered == T::DType::zero() {
+        return true;
+    }
+    if covered == T::DType::zero() {
+        return false;
+    }
+    let max = T::d_zero().count_zeros() - 1 - covered.leading_zeros();
+    let low = (one << max as usize) | ((one << max as usize) - one);
+    let high = !low;
+    (v >> max as usize) & one == one && v & high == high && v & low & !covered == T::DType::zero()
+}
+
+/// put values of idents to bits of word according to bitspecs, returns None if some value does not fit
+fn pack_idents<T:Num>(values : &[(&str, T::DType)], instr : &BinaryInstruction::<T>) -> Option<T::IType> {
+    for (name, v) in values {
+        let mut covered = T::d_zero();
+        for item in &instr.list {
+            if let Item::Ident { name : n, bitspec } = item {
+                if n == name {
+                    for sbit in bitspec {
+                        covered = covered | (T::DType::one() << *sbit as usize);
+                    }
+                }
+            }
+        }
+        if !fits::<T>(*v, covered) {
+            return None;
+        }
+    }
+
+    let mut word = T::i_zero();
+    let mut pos = T::i_max_bit() + 1;
+    for item in &instr.list {
+        match item {
+            Item::Bits { len, val:_ } => pos -= *len as u32,
+            Item::Ident { name, bitspec } => {
+                let v = values.iter().find(|(n, _)| n == name).map_or(T::d_zero(), |(_, v)| *v);
+                for sbit in bitspec {
+                    pos -= 1;
+                    if (v >> *sbit as usize) & T::DType::one() == T::DType::one() {
+                        word = word | (T::i_one() << pos as usize);
+                    }
+                }
+            }
+        }
+    }
+    Some( word )
+}
+
+/// try to encode given text with given instruction,
+/// if text corresponds to instruction return binary word, otherwise None
+pub fn try_instruction<T:Num>( text : &str, i : &Instruction::<T>, isa : &ISA::<T> ) -> Option<T::IType> {
+    let tokens = match_text(text, &i.text)?;
+
+    let mut values = Vec::<(&str, T::DType)>::new();
+    for (ident, token) in tokens {
+        let v = match (isa.parse_fields_dict.get(ident), i.text.kind(ident), isa.parse_dict.get(ident)) {
+            (Some( f ), _, _) => f(token, &values)?,
+            (None, Some( kind ), _) => to_dtype::<T>( parse_operand(kind, token)? )?,
+            (None, None, Some( f )) => f(token)?,
+            (None, None, None) => parse_number::<T>(token)?,
+        };
+        match values.iter().find(|(n, _)| *n == ident) {
+            Some( (_, prev) ) if *prev != v => return None,
+            Some( _ ) => (),
+            None => values.push( (ident, v) ),
+        }
+    }
+
+    if !i.satisfies(&|name| values.iter().find(|(n, _)| *n == name).and_then(|(_, v)| v.to_u64())) {
+        return None;
+    }
+
+    let word = pack_idents::<T>(&values, &i.bin)?;
+    Some( word | i.pattern() )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+
+    #[test]
+    fn match_1() {
+        let text = TextInstruction::from("lw rd, imm (rs1)");
+        let r = match_text("lw  r1,0x10(r2)", &text).unwrap();
+        assert_eq!(r, vec![("rd", "r1"), ("imm", "0x10"), ("rs1", "r2")]);
+        assert!(match_text("lwu r1, 0x10 (r2)", &text).is_none());
+        assert!(match_text("lw r1, 0x10 (r2) r3", &text).is_none());
+    }
+
+    #[test]
+    fn fits_1() {
+        assert!(fits::<RV32Type>(0xFFF, 0xFFF));
+        assert!(fits::<RV32Type>(0xFFFF_FFFF, 0xFFF));
+        assert!(!fits::<RV32Type>(0x1000, 0xFFF));
+        assert!(!fits::<RV32Type>(0x3, 0x1FFE));
+    }
+
+    #[test]
+    fn round_trip() {
+        let isa = ISARV32IMA::new();
+        for v in [0x00a58593_u32, 0xfff58593, 0x0085a503, 0x00b52423, 0xfe0518e3] {
+            let text = decode(v, &isa);
+            assert_eq!(encode(&text, &isa), Some( v ), "{}", text);
+        }
+        assert_eq!(encode("addi r1, r2, 0x1000", &isa), None);
+    }
+}
@@ -0,0 +1,3 @@
+mod encode;
+
+pub use encode::{encode, encode_set};
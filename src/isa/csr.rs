@@ -0,0 +1,136 @@
+//! Control and status registers names database
+
+///CSRs with single address
+const CSRS : [(u32, &str); 125] = [
+    //unprivileged floating point, vector and entropy source CSRs
+    (0x001, "fflags"), (0x002, "frm"), (0x003, "fcsr"),
+    (0x008, "vstart"), (0x009, "vxsat"), (0x00A, "vxrm"), (0x00F, "vcsr"),
+    (0x015, "seed"), (0x017, "jvt"),
+    //unprivileged counters and timers
+    (0xC00, "cycle"), (0xC01, "time"), (0xC02, "instret"),
+    (0xC80, "cycleh"), (0xC81, "timeh"), (0xC82, "instreth"),
+    (0xC20, "vl"), (0xC21, "vtype"), (0xC22, "vlenb"),
+    //supervisor
+    (0x100, "sstatus"), (0x104, "sie"), (0x105, "stvec"), (0x106, "scounteren"), (0x10A, "senvcfg"),
+    (0x120, "scountinhibit"), (0x140, "sscratch"), (0x141, "sepc"), (0x142, "scause"), (0x143, "stval"),
+    (0x144, "sip"), (0x14D, "stimecmp"), (0x15D, "stimecmph"), (0x180, "satp"), (0x5A8, "scontext"),
+    (0xDA0, "scountovf"),
+    //hypervisor and virtual supervisor
+    (0x600, "hstatus"), (0x602, "hedeleg"), (0x603, "hideleg"), (0x604, "hie"), (0x605, "htimedelta"),
+    (0x606, "hcounteren"), (0x607, "hgeie"), (0x60A, "henvcfg"), (0x612, "hedelegh"), (0x615, "htimedeltah"),
+    (0x61A, "henvcfgh"), (0x643, "htval"), (0x644, "hip"), (0x645, "hvip"), (0x64A, "htinst"),
+    (0x680, "hgatp"), (0x6A8, "hcontext"), (0xE12, "hgeip"),
+    (0x200, "vsstatus"), (0x204, "vsie"), (0x205, "vstvec"), (0x240, "vsscratch"), (0x241, "vsepc"),
+    (0x242, "vscause"), (0x243, "vstval"), (0x244, "vsip"), (0x24D, "vstimecmp"), (0x25D, "vstimecmph"),
+    (0x280, "vsatp"),
+    //machine information, trap setup and handling
+    (0xF11, "mvendorid"), (0xF12, "marchid"), (0xF13, "mimpid"), (0xF14, "mhartid"), (0xF15, "mconfigptr"),
+    (0x300, "mstatus"), (0x301, "misa"), (0x302, "medeleg"), (0x303, "mideleg"), (0x304, "mie"),
+    (0x305, "mtvec"), (0x306, "mcounteren"), (0x30A, "menvcfg"), (0x310, "mstatush"), (0x312, "medelegh"),
+    (0x31A, "menvcfgh"), (0x320, "mcountinhibit"), (0x340, "mscratch"), (0x341, "mepc"), (0x342, "mcause"),
+    (0x343, "mtval"), (0x344, "mip"), (0x34A, "mtinst"), (0x34B, "mtval2"),
+    (0x747, "mseccfg"), (0x757, "mseccfgh"),
+    (0x740, "mnscratch"), (0x741, "mnepc"), (0x742, "mncause"), (0x744, "mnstatus"),
+    //machine counters
+    (0xB00, "mcycle"), (0xB02, "minstret"), (0xB80, "mcycleh"), (0xB82, "minstreth"),
+    //debug and trace
+    (0x7A0, "tselect"), (0x7A1, "tdata1"), (0x7A2, "tdata2"), (0x7A3, "tdata3"), (0x7A4, "tinfo"),
+    (0x7A5, "tcontrol"), (0x7A8, "mcontext"), (0x7AA, "mscontext"),
+    (0x7B0, "dcsr"), (0x7B1, "dpc"), (0x7B2, "dscratch0"), (0x7B3, "dscratch1"),
+    //state enable and indirect access
+    (0x30C, "mstateen0"), (0x30D, "mstateen1"), (0x30E, "mstateen2"), (0x30F, "mstateen3"),
+    (0x10C, "sstateen0"), (0x10D, "sstateen1"), (0x10E, "sstateen2"), (0x10F, "sstateen3"),
+    (0x350, "miselect"), (0x351, "mireg"), (0x150, "siselect"), (0x151, "sireg"),
+    (0x35C, "mtopei"), (0xFB0, "mtopi"), (0x15C, "stopei"), (0xDB0, "stopi"),
+];
+
+///CSRs numbered by index: (first address, name prefix, first index, last index, suffix)
+const CSR_RANGES : [(u32, &str, u32, u32, &str); 8] = [
+    (0x3A0, "pmpcfg", 0, 15, ""),
+    (0x3B0, "pmpaddr", 0, 63, ""),
+    (0x323, "mhpmevent", 3, 31, ""),
+    (0x723, "mhpmevent", 3, 31, "h"),
+    (0xB03, "mhpmcounter", 3, 31, ""),
+    (0xB83, "mhpmcounter", 3, 31, "h"),
+    (0xC03, "hpmcounter", 3, 31, ""),
+    (0xC83, "hpmcounter", 3, 31, "h"),
+];
+
+///name of CSR with given address
+pub fn csr_name(v : u32) -> Option<String> {
+    if let Some( (_, name) ) = CSRS.iter().find(|(n, _)| *n == v) {
+        return Some( String::from(*name) );
+    }
+    for (first, prefix, lo, hi, suffix) in CSR_RANGES.iter() {
+        if v >= *first && v <= *first + hi - lo {
+            return Some( format!("{}{}{}", prefix, v - first + lo, suffix) );
+        }
+    }
+    None
+}
+
+///address of CSR with given name
+pub fn csr_number(s : &str) -> Option<u32> {
+    if let Some( (n, _) ) = CSRS.iter().find(|(_, name)| *name == s) {
+        return Some( *n );
+    }
+    for (first, prefix, lo, hi, suffix) in CSR_RANGES.iter() {
+        let index = s.strip_prefix(prefix).and_then(|i| i.strip_suffix(suffix));
+        //leading zeros and signs are not part of CSR names
+        match index {
+            Some( i ) if !i.is_empty() && i.bytes().all(|b| b.is_ascii_digit()) && (i == "0" || !i.starts_with('0')) => {
+                match i.parse::<u32>() {
+                    Ok( i ) if i >= *lo && i <= *hi => return Some( first + i - lo ),
+                    _ => (),
+                }
+            },
+            _ => (),
+        }
+    }
+    None
+}
+
+///show CSR by name, unknown CSRs are shown as number
+pub fn show_csr(v : u32) -> String {
+    csr_name(v).unwrap_or_else(|| format!("{:#X}", v))
+}
+
+///parse CSR given by name or by number
+pub fn parse_csr(s : &str) -> Option<u32> {
+    let v = match csr_number(s) {
+        Some( v ) => v,
+        None => match s.strip_prefix("0x") {
+            Some( h ) => u32::from_str_radix(h, 16).ok()?,
+            None => s.parse::<u32>().ok()?,
+        },
+    };
+    if v < 0x1000 { Some( v ) } else { None }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(show_csr(0x300), "mstatus");
+        assert_eq!(show_csr(0x3A2), "pmpcfg2");
+        assert_eq!(show_csr(0x3EF), "pmpaddr63");
+        assert_eq!(show_csr(0xB9F), "mhpmcounter31h");
+        assert_eq!(show_csr(0x7FF), "0x7FF");
+        for v in 0..0x1000 {
+            if let Some( name ) = csr_name(v) {
+                assert_eq!(csr_number(&name), Some( v ), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_csr("satp"), Some( 0x180 ));
+        assert_eq!(parse_csr("hpmcounter3"), Some( 0xC03 ));
+        assert_eq!(parse_csr("hpmcounter03"), None);
+        assert_eq!(parse_csr("pmpaddr64"), None);
+        assert_eq!(parse_csr("0x7C0"), Some( 0x7C0 ));
+    }
+}
@@ -3,10 +3,233 @@ use std::collections::HashMap;
 
 pub type ShowFun<T> = fn(<T as Num>::DType) -> String;
 pub type ShowDict<T> = HashMap<String, ShowFun<T>>;
+pub type ParseFun<T> = fn(&str) -> Option<<T as Num>::DType>;
+pub type ParseDict<T> = HashMap<String, ParseFun<T>>;
 
-///RV32C instructions subset
+///decoded fields of instruction, tuples of (ident, val, start_bit)
+pub type Fields<T> = [(String, <T as Num>::DType, u32)];
+///show operand which depends on other fields of instruction
+pub type ShowFieldsFun<T> = fn(<T as Num>::DType, &Fields<T>) -> String;
+pub type ShowFieldsDict<T> = HashMap<String, ShowFieldsFun<T>>;
+///parse operand which depends on already parsed operands, given as tuples of (ident, val)
+pub type ParseFieldsFun<T> = fn(&str, &[(&str, <T as Num>::DType)]) -> Option<<T as Num>::DType>;
+pub type ParseFieldsDict<T> = HashMap<String, ParseFieldsFun<T>>;
+
+///helper to show register
+pub fn show_register(v : u32) -> String
+{
+    format!("r{}", v)
+}
+
+///helper to parse register, both r5 and x5 forms are accepted
+pub fn parse_register(s : &str) -> Option<u32>
+{
+    let n = s.strip_prefix('r').or_else(|| s.strip_prefix('x'))?;
+    match n.parse::<u32>() {
+        Ok( v ) if v < 32 => Some( v ),
+        _ => None,
+    }
+}
+
+///helper to show register of compressed instruction, 3 bits encode x8-x15
+pub fn show_cregister(v : u32) -> String
+{
+    show_register(v + 8)
+}
+
+///helper to parse register of compressed instruction, only x8-x15 are allowed
+pub fn parse_cregister(s : &str) -> Option<u32>
+{
+    match parse_register(s) {
+        Some( v ) if (8..16).contains(&v) => Some( v - 8 ),
+        _ => None,
+    }
+}
+
+///helper to show float register
+pub fn show_fregister(v : u32) -> String
+{
+    format!("f{}", v)
+}
+
+///helper to parse float register
+pub fn parse_fregister(s : &str) -> Option<u32>
+{
+    match s.strip_prefix('f')?.parse::<u32>() {
+        Ok( v ) if v < 32 => Some( v ),
+        _ => None,
+    }
+}
+
+///helper to show float register of compressed instruction, 3 bits encode f8-f15
+pub fn show_cfregister(v : u32) -> String
+{
+    show_fregister(v + 8)
+}
+
+///helper to parse float register of compressed instruction, only f8-f15 are allowed
+pub fn parse_cfregister(s : &str) -> Option<u32>
+{
+    match parse_fregister(s) {
+        Some( v ) if (8..16).contains(&v) => Some( v - 8 ),
+        _ => None,
+    }
+}
+
+///rounding modes, values 5 and 6 are reserved
+const ROUNDING_MODES : [&str; 8] = ["rne", "rtz", "rdn", "rup", "rmm", "", "", "dyn"];
+
+///helper to show rounding mode
+pub fn show_rm(v : u32) -> String
+{
+    match ROUNDING_MODES.get(v as usize) {
+        Some( m ) if !m.is_empty() => String::from(*m),
+        _ => format!("{:#X}", v),
+    }
+}
+
+///helper to parse rounding mode
+pub fn parse_rm(s : &str) -> Option<u32>
+{
+    ROUNDING_MODES.iter().position(|m| !m.is_empty() && *m == s).map(|v| v as u32)
+}
+
+///sign extend value of given width
+pub fn sign_extend(v : u64, width : u32) -> i64
+{
+    if width == 0 || width >= 64 {
+        return v as i64;
+    }
+    let shift = 64 - width;
+    ((v << shift) as i64) >> shift
+}
+
+///show operand of given kind, width is number of bits of encoded value
+pub fn show_operand(kind : OperandKind, v : u64, width : u32) -> String
+{
+    match kind {
+        OperandKind::XReg => show_register(v as u32),
+        OperandKind::FReg => show_fregister(v as u32),
+        OperandKind::VReg => format!("v{}", v),
+        OperandKind::CReg => show_cregister(v as u32),
+        OperandKind::CFReg => show_cfregister(v as u32),
+        OperandKind::Simm( _ ) | OperandKind::PcRel => match sign_extend(v, width) {
+            n if n < 0 => format!("-{:#X}", n.unsigned_abs()),
+            n => format!("{:#X}", n),
+        },
+        OperandKind::Uimm( _ ) => format!("{:#X}", v),
+        OperandKind::Rm => show_rm(v as u32),
+        OperandKind::Csr => crate::isa::csr::show_csr(v as u32),
+        OperandKind::VMask if v == 0 => String::from("v0.t"),
+        OperandKind::VMask => String::new(),
+    }
+}
+
+///parse operand of given kind, signed values are returned as two's complement.
+///Simm(N) accepts values from -2^(N-1) to 2^(N-1)-1
+pub fn parse_operand(kind : OperandKind, s : &str) -> Option<u64>
+{
+    let number = |s : &str| -> Option<i128> {
+        let (negative, s) = match s.strip_prefix('-') {
+            None => (false, s),
+            Some( s ) => (true, s),
+        };
+        let v = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            None => s.parse::<u64>().ok()?,
+            Some( h ) => u64::from_str_radix(h, 16).ok()?,
+        } as i128;
+        Some( if negative { -v } else { v } )
+    };
+    match kind {
+        OperandKind::XReg => parse_register(s).map(u64::from),
+        OperandKind::FReg => parse_fregister(s).map(u64::from),
+        OperandKind::VReg => match s.strip_prefix('v')?.parse::<u64>() {
+            Ok( v ) if v < 32 => Some( v ),
+            _ => None,
+        },
+        OperandKind::CReg => parse_cregister(s).map(u64::from),
+        OperandKind::CFReg => parse_cfregister(s).map(u64::from),
+        OperandKind::Simm( w ) => {
+            let v = number(s)?;
+            if v >= -(1_i128 << (w - 1)) && v < (1_i128 << (w - 1)) {
+                Some( v as u64 )
+            } else {
+                None
+            }
+        },
+        OperandKind::Uimm( w ) => {
+            let v = number(s)?;
+            if v >= 0 && v < (1_i128 << w) { Some( v as u64 ) } else { None }
+        },
+        OperandKind::PcRel => number(s).filter(|v| *v >= i64::MIN as i128 && *v <= u64::MAX as i128).map(|v| v as u64),
+        OperandKind::Rm => parse_rm(s).map(u64::from),
+        OperandKind::Csr => crate::isa::csr::parse_csr(s).map(u64::from),
+        OperandKind::VMask => match s {
+            "" => Some( 1 ),
+            "v0.t" => Some( 0 ),
+            _ => None,
+        },
+    }
+}
+
+///Base integer register width, some encodings differ between RV32 and RV64
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Xlen {
+    X32,
+    X64,
+}
+
+impl Xlen {
+    ///width of register in bits
+    pub fn bits(self) -> u32 {
+        match self {
+            Xlen::X32 => 32,
+            Xlen::X64 => 64,
+        }
+    }
+
+    ///mask of bits of register value
+    pub fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.bits())
+    }
+}
+
+///instruction table of one extension, generic over the instruction word type
 #[derive(Debug)]
 pub struct ISA<T:Num> {
     pub list : Vec<Instruction::<T>>,
     pub show_dict : ShowDict::<T>,
+    pub parse_dict : ParseDict::<T>,
+    pub show_fields_dict : ShowFieldsDict::<T>,
+    pub parse_fields_dict : ParseFieldsDict::<T>,
+}
+
+impl<T:Num> Default for ISA<T> {
+    fn default() -> Self {
+        ISA { list : Vec::new(), show_dict : HashMap::new(), parse_dict : HashMap::new(),
+              show_fields_dict : HashMap::new(), parse_fields_dict : HashMap::new() }
+    }
+}
+
+impl<T:Num> ISA<T> {
+    ///append instructions and show functions of other ISA to this one
+    pub fn append(&mut self, other : ISA<T>) {
+        self.list.extend(other.list);
+        self.show_dict.extend(other.show_dict);
+        self.parse_dict.extend(other.parse_dict);
+        self.show_fields_dict.extend(other.show_fields_dict);
+        self.parse_fields_dict.extend(other.parse_fields_dict);
+    }
+
+    ///restrict integer register operands to x0..x15, as in RV32E and RV64E bases
+    pub fn embedded(&mut self) {
+        let low : Vec<u64> = (0..16).collect();
+        for i in &mut self.list {
+            for r in ["rd", "rs1", "rs2", "rs3"] {
+                if i.bin.list.iter().any(|item| matches!(item, Item::Ident { name, .. } if name == r)) {
+                    i.constraints.push( Constraint::is_in(r, &low) );
+                }
+            }
+        }
+    }
 }
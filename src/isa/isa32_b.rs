@@ -0,0 +1,156 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+
+pub type ISARVB = ISA<RV32Type>;
+
+impl ISARVB {
+    ///Zba, address generation
+    pub fn zba(xlen : Xlen) -> ISARVB {
+        let mut list = vec! [
+            asdis::instruction32!("sh1add {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010000, rs2[4:0], rs1[4:0], 010, rd[4:0], 0110011).sem(Sem::reg(Op::ShAdd(1))),
+            asdis::instruction32!("sh2add {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010000, rs2[4:0], rs1[4:0], 100, rd[4:0], 0110011).sem(Sem::reg(Op::ShAdd(2))),
+            asdis::instruction32!("sh3add {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010000, rs2[4:0], rs1[4:0], 110, rd[4:0], 0110011).sem(Sem::reg(Op::ShAdd(3))),
+        ];
+
+        if xlen == Xlen::X64 {
+            list.extend( vec! [
+                asdis::instruction32!("add.uw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000100, rs2[4:0], rs1[4:0], 000, rd[4:0], 0111011).sem(Sem::reg(Op::ShAddUw(0))),
+                asdis::instruction32!("sh1add.uw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010000, rs2[4:0], rs1[4:0], 010, rd[4:0], 0111011).sem(Sem::reg(Op::ShAddUw(1))),
+                asdis::instruction32!("sh2add.uw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010000, rs2[4:0], rs1[4:0], 100, rd[4:0], 0111011).sem(Sem::reg(Op::ShAddUw(2))),
+                asdis::instruction32!("sh3add.uw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010000, rs2[4:0], rs1[4:0], 110, rd[4:0], 0111011).sem(Sem::reg(Op::ShAddUw(3))),
+                asdis::instruction32!("slli.uw {rd:xreg}, {rs1:xreg}, shamt", 000010, shamt[5:0], rs1[4:0], 001, rd[4:0], 0011011).sem(Sem::shamt(Op::SllUw)),
+            ]);
+        }
+
+        ISARVB { list, ..Default::default() }
+    }
+
+    ///Zbb, basic bit-manipulation
+    pub fn zbb(xlen : Xlen) -> ISARVB {
+        let mut list = vec! [
+            asdis::instruction32!("andn {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 111, rd[4:0], 0110011).sem(Sem::reg(Op::Andn)),
+            asdis::instruction32!("orn {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 110, rd[4:0], 0110011).sem(Sem::reg(Op::Orn)),
+            asdis::instruction32!("xnor {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 100, rd[4:0], 0110011).sem(Sem::reg(Op::Xnor)),
+
+            asdis::instruction32!("clz {rd:xreg}, {rs1:xreg}", 0110000, 00000, rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::unary(Unary::Clz)),
+            asdis::instruction32!("ctz {rd:xreg}, {rs1:xreg}", 0110000, 00001, rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::unary(Unary::Ctz)),
+            asdis::instruction32!("cpop {rd:xreg}, {rs1:xreg}", 0110000, 00010, rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::unary(Unary::Cpop)),
+            asdis::instruction32!("sext.b {rd:xreg}, {rs1:xreg}", 0110000, 00100, rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::extend_rs1(8, true)),
+            asdis::instruction32!("sext.h {rd:xreg}, {rs1:xreg}", 0110000, 00101, rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::extend_rs1(16, true)),
+
+            asdis::instruction32!("max {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 110, rd[4:0], 0110011).sem(Sem::reg(Op::Max)),
+            asdis::instruction32!("maxu {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 111, rd[4:0], 0110011).sem(Sem::reg(Op::Maxu)),
+            asdis::instruction32!("min {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 100, rd[4:0], 0110011).sem(Sem::reg(Op::Min)),
+            asdis::instruction32!("minu {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 101, rd[4:0], 0110011).sem(Sem::reg(Op::Minu)),
+
+            asdis::instruction32!("rol {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 001, rd[4:0], 0110011).sem(Sem::reg(Op::Rol)),
+            asdis::instruction32!("ror {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 101, rd[4:0], 0110011).sem(Sem::reg(Op::Ror)),
+
+            asdis::instruction32!("orc.b {rd:xreg}, {rs1:xreg}", 001010000111, rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::unary(Unary::OrcB)),
+        ];
+
+        //zext.h is pack with rs2=x0, rev8 and rori depend on XLEN
+        match xlen {
+            Xlen::X32 => list.extend( vec! [
+                asdis::instruction32!("zext.h {rd:xreg}, {rs1:xreg}", 0000100, 00000, rs1[4:0], 100, rd[4:0], 0110011).sem(Sem::extend_rs1(16, false)),
+                asdis::instruction32!("rev8 {rd:xreg}, {rs1:xreg}", 011010011000, rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::unary(Unary::Rev8)),
+                asdis::instruction32!("rori {rd:xreg}, {rs1:xreg}, shamt", 0110000, shamt[4:0], rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::shamt(Op::Ror)),
+            ]),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction32!("zext.h {rd:xreg}, {rs1:xreg}", 0000100, 00000, rs1[4:0], 100, rd[4:0], 0111011).sem(Sem::extend_rs1(16, false)),
+                asdis::instruction32!("rev8 {rd:xreg}, {rs1:xreg}", 011010111000, rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::unary(Unary::Rev8)),
+                asdis::instruction32!("rori {rd:xreg}, {rs1:xreg}, shamt", 011000, shamt[5:0], rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::shamt(Op::Ror)),
+
+                asdis::instruction32!("clzw {rd:xreg}, {rs1:xreg}", 0110000, 00000, rs1[4:0], 001, rd[4:0], 0011011).sem(Sem::unary(Unary::Clz).word()),
+                asdis::instruction32!("ctzw {rd:xreg}, {rs1:xreg}", 0110000, 00001, rs1[4:0], 001, rd[4:0], 0011011).sem(Sem::unary(Unary::Ctz).word()),
+                asdis::instruction32!("cpopw {rd:xreg}, {rs1:xreg}", 0110000, 00010, rs1[4:0], 001, rd[4:0], 0011011).sem(Sem::unary(Unary::Cpop).word()),
+                asdis::instruction32!("rolw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 001, rd[4:0], 0111011).sem(Sem::reg(Op::Rol).word()),
+                asdis::instruction32!("rorw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 101, rd[4:0], 0111011).sem(Sem::reg(Op::Ror).word()),
+                asdis::instruction32!("roriw {rd:xreg}, {rs1:xreg}, shamt", 0110000, shamt[4:0], rs1[4:0], 101, rd[4:0], 0011011).sem(Sem::shamt(Op::Ror).word()),
+            ]),
+        };
+
+        ISARVB { list, ..Default::default() }
+    }
+
+    ///Zbc, carry-less multiplication
+    pub fn zbc() -> ISARVB {
+        let list = vec! [
+            asdis::instruction32!("clmul {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 001, rd[4:0], 0110011),
+            asdis::instruction32!("clmulh {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 011, rd[4:0], 0110011),
+            asdis::instruction32!("clmulr {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 010, rd[4:0], 0110011),
+        ];
+
+        ISARVB { list, ..Default::default() }
+    }
+
+    ///Zbs, single-bit instructions
+    pub fn zbs(xlen : Xlen) -> ISARVB {
+        let mut list = vec! [
+            asdis::instruction32!("bclr {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100100, rs2[4:0], rs1[4:0], 001, rd[4:0], 0110011).sem(Sem::reg(Op::Bclr)),
+            asdis::instruction32!("bext {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100100, rs2[4:0], rs1[4:0], 101, rd[4:0], 0110011).sem(Sem::reg(Op::Bext)),
+            asdis::instruction32!("binv {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110100, rs2[4:0], rs1[4:0], 001, rd[4:0], 0110011).sem(Sem::reg(Op::Binv)),
+            asdis::instruction32!("bset {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010100, rs2[4:0], rs1[4:0], 001, rd[4:0], 0110011).sem(Sem::reg(Op::Bset)),
+        ];
+
+        //shamt[5] is reserved on RV32
+        match xlen {
+            Xlen::X32 => list.extend( vec! [
+                asdis::instruction32!("bclri {rd:xreg}, {rs1:xreg}, shamt", 0100100, shamt[4:0], rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::shamt(Op::Bclr)),
+                asdis::instruction32!("bexti {rd:xreg}, {rs1:xreg}, shamt", 0100100, shamt[4:0], rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::shamt(Op::Bext)),
+                asdis::instruction32!("binvi {rd:xreg}, {rs1:xreg}, shamt", 0110100, shamt[4:0], rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::shamt(Op::Binv)),
+                asdis::instruction32!("bseti {rd:xreg}, {rs1:xreg}, shamt", 0010100, shamt[4:0], rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::shamt(Op::Bset)),
+            ]),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction32!("bclri {rd:xreg}, {rs1:xreg}, shamt", 010010, shamt[5:0], rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::shamt(Op::Bclr)),
+                asdis::instruction32!("bexti {rd:xreg}, {rs1:xreg}, shamt", 010010, shamt[5:0], rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::shamt(Op::Bext)),
+                asdis::instruction32!("binvi {rd:xreg}, {rs1:xreg}, shamt", 011010, shamt[5:0], rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::shamt(Op::Binv)),
+                asdis::instruction32!("bseti {rd:xreg}, {rs1:xreg}, shamt", 001010, shamt[5:0], rs1[4:0], 001, rd[4:0], 0010011).sem(Sem::shamt(Op::Bset)),
+            ]),
+        };
+
+        ISARVB { list, ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn zbb_count() {
+        let isa = ISARVB::zbb(Xlen::X64);
+        assert_eq!(decode(0x6025951b, &isa), "cpopw r10, r11");
+        assert_eq!(encode("clz r10, r11", &isa), Some( 0x60059513 ));
+        assert_eq!(encode("cpopw r10, r11", &ISARVB::zbb(Xlen::X32)), None);
+    }
+
+    #[test]
+    fn zba() {
+        let isa = ISARVB::zba(Xlen::X32);
+        assert_eq!(decode(0x20c5a533, &isa), "sh1add r10, r11, r12");
+        assert_eq!(decode(0x0805053b, &isa), "RV32Type. Not found!");
+        let isa = ISARVB::zba(Xlen::X64);
+        assert_eq!(decode(0x0805053b, &isa), "add.uw r10, r10, r0");
+    }
+
+    #[test]
+    fn zbb_xlen() {
+        let isa = ISARVB::zbb(Xlen::X32);
+        assert_eq!(decode(0x6985d513, &isa), "rev8 r10, r11");
+        assert_eq!(decode(0x0805c533, &isa), "zext.h r10, r11");
+        let isa = ISARVB::zbb(Xlen::X64);
+        assert_eq!(decode(0x6b85d513, &isa), "rev8 r10, r11");
+        assert_eq!(decode(0x0805c53b, &isa), "zext.h r10, r11");
+        assert_eq!(decode(0x6985d513, &isa), "RV32Type. Not found!");
+    }
+
+    #[test]
+    fn zbs() {
+        let isa = ISARVB::zbs(Xlen::X64);
+        assert_eq!(decode(0x2bf59513, &isa), "bseti r10, r11, 0x3F");
+    }
+}
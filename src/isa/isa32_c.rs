@@ -1,57 +1,104 @@
 use crate::primitives::*;
 use crate::isa::isa::*;
-use std::collections::HashMap;
 
 pub type ISARV32C = ISA<CompactType>;
 
-///helper to show register
-fn show_register(v : u32) -> String
-{
-    format!("r{}", v)
+asdis::instruction_enum16! {
+    ///typed compressed instructions, rows of groups are also rows of instruction tables
+    pub enum RvcInstr {
+        ///Zca instructions without unsigned offsets, common to RV32 and RV64
+        zca {
+            Nop = ("c.nop", 000, imm[5], 00000, imm[4:0], 01, imm == 0).sem(Sem::Nop),
+            NopHint = ("c.nop imm", 000, imm[5], 00000, imm[4:0], 01, imm != 0).hint().sem(Sem::Nop),
+            Addi = ("c.addi {rd:xreg}, imm", 000, imm[5], rd[4:0], imm[4:0], 01, rd != 0, hint if imm == 0).sem(Sem::rd(Op::Add, Operand::Imm("imm"))),
+            Li = ("c.li {rd:xreg}, imm", 010, imm[5], rd[4:0], imm[4:0], 01, hint if rd == 0).sem(Sem::add(Operand::Reg("rd"), Operand::X(0), Operand::Imm("imm"))),
+            Addi16sp = ("c.addi16sp imm", 011, imm[9], 00010, imm[4|6|8:7|5], 01, reserved if imm == 0).sem(Sem::add(Operand::X(2), Operand::X(2), Operand::Imm("imm"))),
+            Lui = ("c.lui {rd:xreg}, imm", 011, imm[17], rd[4:0], imm[16:12], 01, rd != 2, reserved if imm == 0, hint if rd == 0).sem(Sem::add(Operand::Reg("rd"), Operand::X(0), Operand::Imm("imm"))),
+            Andi = ("c.andi {rdp:creg}, imm", 100, imm[5], 10, rdp[2:0], imm[4:0], 01).sem(Sem::rd(Op::And, Operand::Imm("imm"))),
+            Sub = ("c.sub {rdp:creg}, {rs2p:creg}", 100011, rdp[2:0], 00, rs2p[2:0], 01).sem(Sem::rd(Op::Sub, Operand::Reg("rs2"))),
+            Xor = ("c.xor {rdp:creg}, {rs2p:creg}", 100011, rdp[2:0], 01, rs2p[2:0], 01).sem(Sem::rd(Op::Xor, Operand::Reg("rs2"))),
+            Or = ("c.or {rdp:creg}, {rs2p:creg}", 100011, rdp[2:0], 10, rs2p[2:0], 01).sem(Sem::rd(Op::Or, Operand::Reg("rs2"))),
+            And = ("c.and {rdp:creg}, {rs2p:creg}", 100011, rdp[2:0], 11, rs2p[2:0], 01).sem(Sem::rd(Op::And, Operand::Reg("rs2"))),
+            J = ("c.j imm", 101, imm[11|4|9:8|10|6|7|3:1|5], 01).sem(Sem::Jal { link : Operand::X(0) }),
+            Beqz = ("c.beqz {rs1p:creg}, imm", 110, imm[8|4:3], rs1p[2:0], imm[7:6|2:1|5], 01).sem(Sem::Branch { cond : Cond::Eq, a : Operand::Reg("rs1"), b : Operand::X(0) }),
+            Bnez = ("c.bnez {rs1p:creg}, imm", 111, imm[8|4:3], rs1p[2:0], imm[7:6|2:1|5], 01).sem(Sem::Branch { cond : Cond::Ne, a : Operand::Reg("rs1"), b : Operand::X(0) }),
+
+            Jr = ("c.jr {rs1:xreg}", 1000, rs1[4:0], 0000010, reserved if rs1 == 0).sem(Sem::Jalr { link : Operand::X(0), base : Operand::Reg("rs1"), offset : Operand::Const(0) }),
+            Mv = ("c.mv {rd:xreg}, {rs2:xreg}", 1000, rd[4:0], rs2[4:0], 10, rs2 != 0, hint if rd == 0).sem(Sem::add(Operand::Reg("rd"), Operand::X(0), Operand::Reg("rs2"))),
+            Ebreak = ("c.ebreak", 1001000000000010),
+            Jalr = ("c.jalr {rs1:xreg}", 1001, rs1[4:0], 0000010, rs1 != 0).sem(Sem::Jalr { link : Operand::X(1), base : Operand::Reg("rs1"), offset : Operand::Const(0) }),
+            Add = ("c.add {rd:xreg}, {rs2:xreg}", 1001, rd[4:0], rs2[4:0], 10, rs2 != 0, hint if rd == 0).sem(Sem::rd(Op::Add, Operand::Reg("rs2"))),
+        },
+    }
 }
 
 impl ISARV32C {
+    ///RV32C integer instructions, same as Zca for RV32
     pub fn new() -> ISARV32C {
+        ISARV32C::zca(Xlen::X32)
+    }
 
-        let list = vec! [
-            asdis::instruction16!("c.addi4spn rd, imm", 000, imm[5:4|9:6|2|3], rd[2:0], 00),
-            asdis::instruction16!("c.lw rd, rs1, imm", 010, imm[5:3], rs1[2:0], imm[2|6], rd[2:0], 00),
-            asdis::instruction16!("c.sw rs1, imm (rs2)", 110, imm[5:3], rs1[2:0], imm[2|6], rs2[2:0], 00),
-            asdis::instruction16!("c.nop", 000, imm[5], 00000, imm[4:0], 01),
-            asdis::instruction16!("c.addi rd, imm", 000, imm[5], rd[4:0], imm[4:0], 01),
-            asdis::instruction16!("c.jal imm", 001, imm[11|4|9:8|10|6|7|3:1|5], 01),
-            asdis::instruction16!("c.li rd, imm", 010, imm[5], rd[4:0], imm[4:0], 01),
-            asdis::instruction16!("c.addi16sp imm", 011, imm[9], 00010, imm[4|6|8:7|5], 01),
-            asdis::instruction16!("c.lui rd, imm", 011, imm[17], rd[4:0], imm[16:12], 01),
-            asdis::instruction16!("c.srli rd, imm", 100100, rd[2:0], imm[4:0], 01),
-            asdis::instruction16!("c.srai rd, imm", 100101, rd[2:0], imm[4:0], 01),
-            asdis::instruction16!("c.andi rd, imm", 100, imm[5], 10, rd[2:0], imm[4:0], 01),
-            asdis::instruction16!("c.sub rd, rs2", 100011, rd[2:0], 00, rs2[2:0], 01),
-            asdis::instruction16!("c.xor rd, rs2", 100011, rd[2:0], 01, rs2[2:0], 01),
-            asdis::instruction16!("c.or rd, rs2", 100011, rd[2:0], 10, rs2[2:0], 01),
-            asdis::instruction16!("c.and rd, rs2", 100011, rd[2:0], 11, rs2[2:0], 01),
-            asdis::instruction16!("c.j imm", 101, imm[11|4|9:8|10|6|7|3:1|5], 01),
-            asdis::instruction16!("c.beqz rs1, imm", 110, imm[8|4:3], rs1[2:0], imm[7:6|2:1|5], 01),
-            asdis::instruction16!("c.bnez rs1, imm", 111, imm[8|4:3], rs1[2:0], imm[7:6|2:1|5], 01),
-
-            asdis::instruction16!("c.slli rd, imm", 000, imm[5], rd[4:0], imm[4:0], 10),
-            asdis::instruction16!("c.lwsp rd, imm", 010, imm[5], rd[4:0], imm[4:2|7:6], 10),
-            asdis::instruction16!("c.jr rs1", 1000, rs1[4:0], 0000010),
-            asdis::instruction16!("c.mv rd, rs2", 1000, rd[4:0], rs2[4:0], 10),
-            asdis::instruction16!("c.ebreak", 1001000000000010),
-            asdis::instruction16!("c.jalr rs1", 1001, rs1[4:0], 0000010),
-            asdis::instruction16!("c.add rd, rs2", 1001, rd[4:0], rs2[4:0], 10),
-            asdis::instruction16!("c.swsp rs2, imm", 110, imm[5:2|7:6], rs2[4:0], 10),
+    ///Zca, compressed integer instructions
+    pub fn zca(xlen : Xlen) -> ISARV32C {
+        let mut list = RvcInstr::zca();
+        list.extend( vec! [
+            asdis::instruction16!("c.addi4spn {rdp:creg}, imm", 000, imm[5:4|9:6|2|3], rdp[2:0], 00, reserved if imm == 0).sem(Sem::add(Operand::Reg("rd"), Operand::X(2), Operand::Uimm("imm"))),
+            asdis::instruction16!("c.lw {rdp:creg}, imm ({rs1p:creg})", 010, imm[5:3], rs1p[2:0], imm[2|6], rdp[2:0], 00).sem(Sem::load(4, true).compressed(Operand::Reg("rs1"))),
+            asdis::instruction16!("c.sw {rs2p:creg}, imm ({rs1p:creg})", 110, imm[5:3], rs1p[2:0], imm[2|6], rs2p[2:0], 00).sem(Sem::store(4).compressed(Operand::Reg("rs1"))),
+
+            asdis::instruction16!("c.lwsp {rd:xreg}, imm", 010, imm[5], rd[4:0], imm[4:2|7:6], 10, reserved if rd == 0).sem(Sem::load(4, true).compressed(Operand::X(2))),
+            asdis::instruction16!("c.swsp {rs2:xreg}, imm", 110, imm[5:2|7:6], rs2[4:0], 10).sem(Sem::store(4).compressed(Operand::X(2))),
 
             asdis::instruction16!("<illegal>", 0000000000000000),
-       ];
+        ]);
 
-       let mut show_dict = HashMap::new();
-       show_dict.insert(String::from("rd"), show_register as ShowFun::<CompactType>);
-       show_dict.insert(String::from("rs1"), show_register as ShowFun::<CompactType>);
-       show_dict.insert(String::from("rs2"), show_register as ShowFun::<CompactType>);
+        //shift amounts with shamt[5]=1 are designated for custom extensions in RV32C
+        match xlen {
+            Xlen::X32 => list.extend( vec! [
+                asdis::instruction16!("c.jal imm", 001, imm[11|4|9:8|10|6|7|3:1|5], 01).sem(Sem::Jal { link : Operand::X(1) }),
+                asdis::instruction16!("c.slli {rd:xreg}, imm", 000, imm[5], rd[4:0], imm[4:0], 10, reserved if imm >= 32, hint if rd == 0, hint if imm == 0).sem(Sem::rd(Op::Sll, Operand::Uimm("imm"))),
+                asdis::instruction16!("c.srli {rdp:creg}, imm", 100, imm[5], 00, rdp[2:0], imm[4:0], 01, reserved if imm >= 32, hint if imm == 0).sem(Sem::rd(Op::Srl, Operand::Uimm("imm"))),
+                asdis::instruction16!("c.srai {rdp:creg}, imm", 100, imm[5], 01, rdp[2:0], imm[4:0], 01, reserved if imm >= 32, hint if imm == 0).sem(Sem::rd(Op::Sra, Operand::Uimm("imm"))),
+            ]),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction16!("c.addiw {rd:xreg}, imm", 001, imm[5], rd[4:0], imm[4:0], 01, reserved if rd == 0).sem(Sem::rd(Op::Add, Operand::Imm("imm")).word()),
+                asdis::instruction16!("c.slli {rd:xreg}, imm", 000, imm[5], rd[4:0], imm[4:0], 10, hint if rd == 0, hint if imm == 0).sem(Sem::rd(Op::Sll, Operand::Uimm("imm"))),
+                asdis::instruction16!("c.srli {rdp:creg}, imm", 100, imm[5], 00, rdp[2:0], imm[4:0], 01, hint if imm == 0).sem(Sem::rd(Op::Srl, Operand::Uimm("imm"))),
+                asdis::instruction16!("c.srai {rdp:creg}, imm", 100, imm[5], 01, rdp[2:0], imm[4:0], 01, hint if imm == 0).sem(Sem::rd(Op::Sra, Operand::Uimm("imm"))),
+                asdis::instruction16!("c.subw {rdp:creg}, {rs2p:creg}", 100111, rdp[2:0], 00, rs2p[2:0], 01).sem(Sem::rd(Op::Sub, Operand::Reg("rs2")).word()),
+                asdis::instruction16!("c.addw {rdp:creg}, {rs2p:creg}", 100111, rdp[2:0], 01, rs2p[2:0], 01).sem(Sem::rd(Op::Add, Operand::Reg("rs2")).word()),
+                asdis::instruction16!("c.ld {rdp:creg}, imm ({rs1p:creg})", 011, imm[5:3], rs1p[2:0], imm[7:6], rdp[2:0], 00).sem(Sem::load(8, true).compressed(Operand::Reg("rs1"))),
+                asdis::instruction16!("c.sd {rs2p:creg}, imm ({rs1p:creg})", 111, imm[5:3], rs1p[2:0], imm[7:6], rs2p[2:0], 00).sem(Sem::store(8).compressed(Operand::Reg("rs1"))),
+                asdis::instruction16!("c.ldsp {rd:xreg}, imm", 011, imm[5], rd[4:0], imm[4:3|8:6], 10, reserved if rd == 0).sem(Sem::load(8, true).compressed(Operand::X(2))),
+                asdis::instruction16!("c.sdsp {rs2:xreg}, imm", 111, imm[5:3|8:6], rs2[4:0], 10).sem(Sem::store(8).compressed(Operand::X(2))),
+            ]),
+        };
 
-        ISARV32C { list, show_dict }
+        ISARV32C { list, ..Default::default() }
+    }
+
+    ///Zcf, compressed single precision loads and stores, RV32 only
+    pub fn zcf() -> ISARV32C {
+        let list = vec! [
+            asdis::instruction16!("c.flw {frdp:cfreg}, imm ({rs1p:creg})", 011, imm[5:3], rs1p[2:0], imm[2|6], frdp[2:0], 00),
+            asdis::instruction16!("c.fsw {frs2p:cfreg}, imm ({rs1p:creg})", 111, imm[5:3], rs1p[2:0], imm[2|6], frs2p[2:0], 00),
+            asdis::instruction16!("c.flwsp {frd:freg}, imm", 011, imm[5], frd[4:0], imm[4:2|7:6], 10),
+            asdis::instruction16!("c.fswsp {frs2:freg}, imm", 111, imm[5:2|7:6], frs2[4:0], 10),
+        ];
+
+        ISARV32C { list, ..Default::default() }
+    }
+
+    ///Zcd, compressed double precision loads and stores
+    pub fn zcd() -> ISARV32C {
+        let list = vec! [
+            asdis::instruction16!("c.fld {frdp:cfreg}, imm ({rs1p:creg})", 001, imm[5:3], rs1p[2:0], imm[7:6], frdp[2:0], 00),
+            asdis::instruction16!("c.fsd {frs2p:cfreg}, imm ({rs1p:creg})", 101, imm[5:3], rs1p[2:0], imm[7:6], frs2p[2:0], 00),
+            asdis::instruction16!("c.fldsp {frd:freg}, imm", 001, imm[5], frd[4:0], imm[4:3|8:6], 10),
+            asdis::instruction16!("c.fsdsp {frs2:freg}, imm", 101, imm[5:3|8:6], frs2[4:0], 10),
+        ];
+
+        ISARV32C { list, ..Default::default() }
     }
 }
 
@@ -61,6 +108,7 @@ mod test {
     use super::*;
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn ok() {
         let isa = ISARV32C::new();
         println!("{:?}", isa);
@@ -81,4 +129,82 @@ mod test {
         assert_eq!(i1.bin, bin);
     }
 
+    #[test]
+    fn hints() {
+        let isa = ISARV32C::new();
+        assert_eq!(crate::decoder::decode(0x0001, &isa), "c.nop");
+        assert_eq!(crate::decoder::decode(0x0005, &isa), "c.nop 0x1 # hint");
+        assert_eq!(crate::decoder::decode(0x4015, &isa), "c.li r0, 0x5 # hint");
+        assert_eq!(crate::decoder::decode(0x6081, &isa), "c.lui r1, 0x0 # reserved");
+        assert_eq!(crate::decoder::decode(0x8002, &isa), "c.jr r0 # reserved");
+        assert_eq!(crate::decoder::decode(0x0004, &isa), "c.addi4spn r9, 0x0 # reserved");
+        assert_eq!(crate::decoder::decode(0x0000, &isa), "<illegal>");
+    }
+
+    #[test]
+    fn cregister() {
+        let isa = ISARV32C::new();
+        assert_eq!(crate::decoder::decode(0x4108, &isa), "c.lw r10, 0x0 (r10)");
+        assert_eq!(crate::encoder::encode("c.sw r9, 0x4 (r8)", &isa), Some( 0xc044 ));
+    }
+
+    #[test]
+    fn rv64() {
+        let mut isa = ISARV32C::zca(Xlen::X64);
+        isa.append( ISARV32C::zcd() );
+        assert_eq!(crate::decoder::decode(0x2505, &isa), "c.addiw r10, 0x1");
+        assert_eq!(crate::decoder::decode(0x6108, &isa), "c.ld r10, 0x0 (r10)");
+        assert_eq!(crate::decoder::decode(0x9101, &isa), "c.srli r10, 0x20");
+        assert_eq!(crate::decoder::decode(0x2108, &isa), "c.fld f10, 0x0 (r10)");
+        assert_eq!(crate::encoder::encode("c.sdsp r1, 0x8", &isa), Some( 0xe406 ));
+        assert_eq!(crate::decoder::decode(0x2505, &ISARV32C::new()), "c.jal 0x620");
+    }
+
+    #[test]
+    fn predicates() {
+        let isa = ISARV32C::new();
+        assert_eq!(crate::decoder::decode(0x8082, &isa), "c.jr r1");
+        assert_eq!(crate::decoder::decode(0x808a, &isa), "c.mv r1, r2");
+        assert_eq!(crate::encoder::encode("c.mv r1, r2", &isa), Some( 0x808a ));
+        assert_eq!(crate::encoder::encode("c.mv r1, r0", &isa), None);
+        assert_eq!(crate::encoder::encode("c.lui r3, 0x1000", &isa), Some( 0x6185 ));
+        assert_eq!(crate::encoder::encode("c.lui r2, 0x1000", &isa), None);
+        assert_eq!(crate::encoder::encode("c.jalr r0", &isa), None);
+    }
+
+    #[test]
+    fn custom_shifts() {
+        let isa = ISARV32C::new();
+        assert_eq!(crate::decoder::decode(0x1082, &isa), "c.slli r1, 0x20 # reserved");
+        assert_eq!(crate::decoder::decode(0x9101, &isa), "c.srli r10, 0x20 # reserved");
+        assert_eq!(crate::decoder::decode(0x9505, &isa), "c.srai r10, 0x21 # reserved");
+        assert_eq!(crate::decoder::decode(0x0006, &isa), "c.slli r0, 0x1 # hint");
+        assert_eq!(crate::decoder::decode(0x0086, &isa), "c.slli r1, 0x1");
+        let isa = ISARV32C::zca(Xlen::X64);
+        assert_eq!(crate::decoder::decode(0x1082, &isa), "c.slli r1, 0x20");
+        assert_eq!(crate::decoder::decode(0x9505, &isa), "c.srai r10, 0x21");
+    }
+
+    #[test]
+    fn typed() {
+        assert_eq!(RvcInstr::decode(0x8082), Some( RvcInstr::Jr { rs1 : Reg(1) } ));
+        assert_eq!(RvcInstr::decode(0x808a), Some( RvcInstr::Mv { rd : Reg(1), rs2 : Reg(2) } ));
+        //reserved encodings and encodings excluded by predicates
+        assert_eq!(RvcInstr::decode(0x8002), None);
+        assert_eq!(RvcInstr::decode(0x6101), None);
+        assert_eq!(RvcInstr::decode(0x6185), Some( RvcInstr::Lui { rd : Reg(3), imm : 0x1000 } ));
+        assert_eq!(RvcInstr::decode(0x6105), Some( RvcInstr::Addi16sp { imm : 0x20 } ));
+        assert_eq!(RvcInstr::Lui { rd : Reg(2), imm : 0x1000 }.encode(), None);
+        assert_eq!(RvcInstr::Lui { rd : Reg(3), imm : 0x1000 }.encode(), Some( 0x6185 ));
+        assert_eq!(RvcInstr::Lui { rd : Reg(3), imm : 0 }.encode(), None);
+        assert_eq!(RvcInstr::Jr { rs1 : Reg(0) }.encode(), None);
+    }
+
+    #[test]
+    fn typed_compressed_registers() {
+        let sub = RvcInstr::Sub { rdp : Reg(10), rs2p : Reg(11) };
+        assert_eq!(sub.encode(), Some( 0x8d0d ));
+        assert_eq!(RvcInstr::decode(0x8d0d), Some( sub ));
+        assert_eq!(RvcInstr::Sub { rdp : Reg(5), rs2p : Reg(11) }.encode(), None);
+    }
 }
@@ -0,0 +1,140 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+
+pub type ISARVF = ISA<RV32Type>;
+
+impl ISARVF {
+    ///single precision floating point
+    pub fn f(xlen : Xlen) -> ISARVF {
+        let mut list = vec! [
+            asdis::instruction32!("flw {frd:freg}, {imm:simm12} ({rs1:xreg})", imm[11:0], rs1[4:0], 010, frd[4:0], 0000111),
+            asdis::instruction32!("fsw {frs2:freg}, {imm:simm12} ({rs1:xreg})", imm[11:5], frs2[4:0], rs1[4:0], 010, imm[4:0], 0100111),
+
+            asdis::instruction32!("fmadd.s {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 00, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1000011),
+            asdis::instruction32!("fmsub.s {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 00, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1000111),
+            asdis::instruction32!("fnmsub.s {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 00, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1001011),
+            asdis::instruction32!("fnmadd.s {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 00, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1001111),
+
+            asdis::instruction32!("fadd.s {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0000000, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fsub.s {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0000100, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fmul.s {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0001000, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fdiv.s {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0001100, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fsqrt.s {frd:freg}, {frs1:freg}, {rm:rm}", 0101100, 00000, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+
+            asdis::instruction32!("fsgnj.s {frd:freg}, {frs1:freg}, {frs2:freg}", 0010000, frs2[4:0], frs1[4:0], 000, frd[4:0], 1010011),
+            asdis::instruction32!("fsgnjn.s {frd:freg}, {frs1:freg}, {frs2:freg}", 0010000, frs2[4:0], frs1[4:0], 001, frd[4:0], 1010011),
+            asdis::instruction32!("fsgnjx.s {frd:freg}, {frs1:freg}, {frs2:freg}", 0010000, frs2[4:0], frs1[4:0], 010, frd[4:0], 1010011),
+            asdis::instruction32!("fmin.s {frd:freg}, {frs1:freg}, {frs2:freg}", 0010100, frs2[4:0], frs1[4:0], 000, frd[4:0], 1010011),
+            asdis::instruction32!("fmax.s {frd:freg}, {frs1:freg}, {frs2:freg}", 0010100, frs2[4:0], frs1[4:0], 001, frd[4:0], 1010011),
+
+            asdis::instruction32!("feq.s {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010000, frs2[4:0], frs1[4:0], 010, rd[4:0], 1010011),
+            asdis::instruction32!("flt.s {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010000, frs2[4:0], frs1[4:0], 001, rd[4:0], 1010011),
+            asdis::instruction32!("fle.s {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010000, frs2[4:0], frs1[4:0], 000, rd[4:0], 1010011),
+            asdis::instruction32!("fclass.s {rd:xreg}, {frs1:freg}", 1110000, 00000, frs1[4:0], 001, rd[4:0], 1010011),
+
+            asdis::instruction32!("fcvt.w.s {rd:xreg}, {frs1:freg}, {rm:rm}", 1100000, 00000, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+            asdis::instruction32!("fcvt.wu.s {rd:xreg}, {frs1:freg}, {rm:rm}", 1100000, 00001, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+            asdis::instruction32!("fcvt.s.w {frd:freg}, {rs1:xreg}, {rm:rm}", 1101000, 00000, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fcvt.s.wu {frd:freg}, {rs1:xreg}, {rm:rm}", 1101000, 00001, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fmv.x.w {rd:xreg}, {frs1:freg}", 1110000, 00000, frs1[4:0], 000, rd[4:0], 1010011),
+            asdis::instruction32!("fmv.w.x {frd:freg}, {rs1:xreg}", 1111000, 00000, rs1[4:0], 000, frd[4:0], 1010011),
+        ];
+
+        if xlen == Xlen::X64 {
+            list.extend( vec! [
+                asdis::instruction32!("fcvt.l.s {rd:xreg}, {frs1:freg}, {rm:rm}", 1100000, 00010, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+                asdis::instruction32!("fcvt.lu.s {rd:xreg}, {frs1:freg}, {rm:rm}", 1100000, 00011, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+                asdis::instruction32!("fcvt.s.l {frd:freg}, {rs1:xreg}, {rm:rm}", 1101000, 00010, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+                asdis::instruction32!("fcvt.s.lu {frd:freg}, {rs1:xreg}, {rm:rm}", 1101000, 00011, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+            ]);
+        }
+
+        ISARVF { list, ..Default::default() }
+    }
+
+    ///double precision floating point
+    pub fn d(xlen : Xlen) -> ISARVF {
+        let mut list = vec! [
+            asdis::instruction32!("fld {frd:freg}, {imm:simm12} ({rs1:xreg})", imm[11:0], rs1[4:0], 011, frd[4:0], 0000111),
+            asdis::instruction32!("fsd {frs2:freg}, {imm:simm12} ({rs1:xreg})", imm[11:5], frs2[4:0], rs1[4:0], 011, imm[4:0], 0100111),
+
+            asdis::instruction32!("fmadd.d {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 01, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1000011),
+            asdis::instruction32!("fmsub.d {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 01, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1000111),
+            asdis::instruction32!("fnmsub.d {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 01, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1001011),
+            asdis::instruction32!("fnmadd.d {frd:freg}, {frs1:freg}, {frs2:freg}, {frs3:freg}, {rm:rm}", frs3[4:0], 01, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1001111),
+
+            asdis::instruction32!("fadd.d {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0000001, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fsub.d {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0000101, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fmul.d {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0001001, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fdiv.d {frd:freg}, {frs1:freg}, {frs2:freg}, {rm:rm}", 0001101, frs2[4:0], frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fsqrt.d {frd:freg}, {frs1:freg}, {rm:rm}", 0101101, 00000, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+
+            asdis::instruction32!("fsgnj.d {frd:freg}, {frs1:freg}, {frs2:freg}", 0010001, frs2[4:0], frs1[4:0], 000, frd[4:0], 1010011),
+            asdis::instruction32!("fsgnjn.d {frd:freg}, {frs1:freg}, {frs2:freg}", 0010001, frs2[4:0], frs1[4:0], 001, frd[4:0], 1010011),
+            asdis::instruction32!("fsgnjx.d {frd:freg}, {frs1:freg}, {frs2:freg}", 0010001, frs2[4:0], frs1[4:0], 010, frd[4:0], 1010011),
+            asdis::instruction32!("fmin.d {frd:freg}, {frs1:freg}, {frs2:freg}", 0010101, frs2[4:0], frs1[4:0], 000, frd[4:0], 1010011),
+            asdis::instruction32!("fmax.d {frd:freg}, {frs1:freg}, {frs2:freg}", 0010101, frs2[4:0], frs1[4:0], 001, frd[4:0], 1010011),
+
+            asdis::instruction32!("fcvt.s.d {frd:freg}, {frs1:freg}, {rm:rm}", 0100000, 00001, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fcvt.d.s {frd:freg}, {frs1:freg}, {rm:rm}", 0100001, 00000, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+
+            asdis::instruction32!("feq.d {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010001, frs2[4:0], frs1[4:0], 010, rd[4:0], 1010011),
+            asdis::instruction32!("flt.d {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010001, frs2[4:0], frs1[4:0], 001, rd[4:0], 1010011),
+            asdis::instruction32!("fle.d {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010001, frs2[4:0], frs1[4:0], 000, rd[4:0], 1010011),
+            asdis::instruction32!("fclass.d {rd:xreg}, {frs1:freg}", 1110001, 00000, frs1[4:0], 001, rd[4:0], 1010011),
+
+            asdis::instruction32!("fcvt.w.d {rd:xreg}, {frs1:freg}, {rm:rm}", 1100001, 00000, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+            asdis::instruction32!("fcvt.wu.d {rd:xreg}, {frs1:freg}, {rm:rm}", 1100001, 00001, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+            asdis::instruction32!("fcvt.d.w {frd:freg}, {rs1:xreg}, {rm:rm}", 1101001, 00000, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fcvt.d.wu {frd:freg}, {rs1:xreg}, {rm:rm}", 1101001, 00001, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+        ];
+
+        if xlen == Xlen::X64 {
+            list.extend( vec! [
+                asdis::instruction32!("fcvt.l.d {rd:xreg}, {frs1:freg}, {rm:rm}", 1100001, 00010, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+                asdis::instruction32!("fcvt.lu.d {rd:xreg}, {frs1:freg}, {rm:rm}", 1100001, 00011, frs1[4:0], rm[2:0], rd[4:0], 1010011),
+                asdis::instruction32!("fcvt.d.l {frd:freg}, {rs1:xreg}, {rm:rm}", 1101001, 00010, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+                asdis::instruction32!("fcvt.d.lu {frd:freg}, {rs1:xreg}, {rm:rm}", 1101001, 00011, rs1[4:0], rm[2:0], frd[4:0], 1010011),
+                asdis::instruction32!("fmv.x.d {rd:xreg}, {frs1:freg}", 1110001, 00000, frs1[4:0], 000, rd[4:0], 1010011),
+                asdis::instruction32!("fmv.d.x {frd:freg}, {rs1:xreg}", 1111001, 00000, rs1[4:0], 000, frd[4:0], 1010011),
+            ]);
+        }
+
+        ISARVF { list, ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn sign_injection() {
+        let isa = ISARVF::f(Xlen::X32);
+        assert_eq!(decode(0x20c58553, &isa), "fsgnj.s f10, f11, f12");
+        assert_eq!(encode("fmv.x.w r10, f11", &isa), Some( 0xe0058553 ));
+    }
+
+    #[test]
+    fn f_and_d() {
+        let mut isa = ISARVF::f(Xlen::X64);
+        isa.append( ISARVF::d(Xlen::X64) );
+        assert_eq!(decode(0x00052507, &isa), "flw f10, 0x0 (r10)");
+        assert_eq!(decode(0x00b57553, &isa), "fadd.s f10, f10, f11, dyn");
+        assert_eq!(decode(0x62b5f543, &isa), "fmadd.d f10, f11, f11, f12, dyn");
+        assert_eq!(decode(0xe2050553, &isa), "fmv.x.d r10, f10");
+        assert_eq!(encode("fsd f8, 0x10 (r2)", &isa), Some( 0x00813827 ));
+        //typed operands: signed immediate and rounding mode
+        assert_eq!(decode(0xffc52507, &isa), "flw f10, -0x4 (r10)");
+        assert_eq!(encode("flw f10, -4 (r10)", &isa), Some( 0xffc52507 ));
+        assert_eq!(encode("flw f10, -0x800 (r10)", &isa), Some( 0x80052507 ));
+        assert_eq!(encode("flw f10, 0x7ff (r10)", &isa), Some( 0x7ff52507 ));
+        assert_eq!(encode("flw f10, 0x800 (r10)", &isa), None);
+        assert_eq!(encode("flw f10, 0x1000 (r10)", &isa), None);
+        assert_eq!(encode("fadd.s f10, f10, f11, rtz", &isa), Some( 0x00b51553 ));
+        assert_eq!(encode("fadd.s f10, f10, r11, rtz", &isa), None);
+    }
+}
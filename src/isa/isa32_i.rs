@@ -1,182 +1,287 @@
 use crate::primitives::*;
 use crate::isa::isa::*;
-use std::collections::HashMap;
 
 pub type ISARV32IMA = ISA<RV32Type>;
 
-///helper to show register
-fn show_register(v : u32) -> String
-{
-    format!("r{}", v)
+asdis::instruction_enum32! {
+    ///typed RV32IM instructions, rows of groups are also rows of instruction tables
+    pub enum Rv32Instr {
+        ///base integer instructions, common to RV32I and RV64I
+        i {
+            Lui = ("lui {rd:xreg}, imm", imm[31:12], rd[4:0], 0110111, hint if rd == 0).sem(Sem::add(Operand::Reg("rd"), Operand::X(0), Operand::Imm("imm"))),
+            Auipc = ("auipc {rd:xreg}, imm", imm[31:12], rd[4:0], 0010111, hint if rd == 0).sem(Sem::add(Operand::Reg("rd"), Operand::Pc, Operand::Imm("imm"))),
+            Addi = ("addi {rd:xreg}, {rs1:xreg}, imm", imm[11:0], rs1[4:0], 000 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::imm(Op::Add)),
+            Slti = ("slti {rd:xreg}, {rs1:xreg}, imm", imm[11:0], rs1[4:0], 010 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::imm(Op::Slt)),
+            Sltiu = ("sltiu {rd:xreg}, {rs1:xreg}, imm", imm[11:0], rs1[4:0], 011 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::imm(Op::Sltu)),
+            Xori = ("xori {rd:xreg}, {rs1:xreg}, imm", imm[11:0], rs1[4:0], 100 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::imm(Op::Xor)),
+            Ori = ("ori {rd:xreg}, {rs1:xreg}, imm", imm[11:0], rs1[4:0], 110 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::imm(Op::Or)),
+            Andi = ("andi {rd:xreg}, {rs1:xreg}, imm", imm[11:0], rs1[4:0], 111 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::imm(Op::And)),
+
+            Add = ("add {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 000 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Add)),
+            Sub = ("sub {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 000 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Sub)),
+            Sll = ("sll {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 001 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Sll)),
+            Slt = ("slt {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 010 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Slt)),
+            Sltu = ("sltu {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 011 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Sltu)),
+            Xor = ("xor {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 100 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Xor)),
+            Srl = ("srl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 101 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Srl)),
+            Sra = ("sra {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 101 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Sra)),
+            Or = ("or {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 110 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::Or)),
+            And = ("and {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 111 ,rd[4:0], 0110011, hint if rd == 0).sem(Sem::reg(Op::And)),
+
+            Jal = ("jal {rd:xreg}, imm", imm[20|10:1|11|19:12] ,rd[4:0], 1101111).sem(Sem::Jal { link : Operand::Reg("rd") }),
+            Jalr = ("jalr {rd:xreg}, imm ({rs1:xreg})", imm[11:0],rs1[4:0], 000, rd[4:0], 1100111).sem(Sem::Jalr { link : Operand::Reg("rd"), base : Operand::Reg("rs1"), offset : Operand::Imm("imm") }),
+
+            Beq = ("beq {rs1:xreg}, {rs2:xreg}, imm",imm[12|10:5],rs2[4:0],rs1[4:0],000,imm[4:1|11], 1100011).sem(Sem::branch(Cond::Eq)),
+            Bne = ("bne {rs1:xreg}, {rs2:xreg}, imm",imm[12|10:5],rs2[4:0],rs1[4:0],001,imm[4:1|11], 1100011).sem(Sem::branch(Cond::Ne)),
+            Blt = ("blt {rs1:xreg}, {rs2:xreg}, imm",imm[12|10:5],rs2[4:0],rs1[4:0],100,imm[4:1|11], 1100011).sem(Sem::branch(Cond::Lt)),
+            Bge = ("bge {rs1:xreg}, {rs2:xreg}, imm",imm[12|10:5],rs2[4:0],rs1[4:0],101,imm[4:1|11], 1100011).sem(Sem::branch(Cond::Ge)),
+            Bltu = ("bltu {rs1:xreg}, {rs2:xreg}, imm",imm[12|10:5],rs2[4:0],rs1[4:0],110,imm[4:1|11], 1100011).sem(Sem::branch(Cond::Ltu)),
+            Bgeu = ("bgeu {rs1:xreg}, {rs2:xreg}, imm",imm[12|10:5],rs2[4:0],rs1[4:0],111,imm[4:1|11], 1100011).sem(Sem::branch(Cond::Geu)),
+
+            Lb = ("lb {rd:xreg}, imm ({rs1:xreg})", imm[11:0],rs1[4:0], 000 ,rd[4:0], 0000011).sem(Sem::load(1, true)),
+            Lh = ("lh {rd:xreg}, imm ({rs1:xreg})", imm[11:0],rs1[4:0], 001 ,rd[4:0], 0000011).sem(Sem::load(2, true)),
+            Lw = ("lw {rd:xreg}, imm ({rs1:xreg})", imm[11:0],rs1[4:0], 010 ,rd[4:0], 0000011).sem(Sem::load(4, true)),
+            Lbu = ("lbu {rd:xreg}, imm ({rs1:xreg})", imm[11:0],rs1[4:0], 100 ,rd[4:0], 0000011).sem(Sem::load(1, false)),
+            Lhu = ("lhu {rd:xreg}, imm ({rs1:xreg})", imm[11:0],rs1[4:0], 101 ,rd[4:0], 0000011).sem(Sem::load(2, false)),
+            Sb = ("sb {rs2:xreg}, imm ({rs1:xreg})", imm[11:5],rs2[4:0],rs1[4:0],000,imm[4:0], 0100011).sem(Sem::store(1)),
+            Sh = ("sh {rs2:xreg}, imm ({rs1:xreg})", imm[11:5],rs2[4:0],rs1[4:0],001,imm[4:0], 0100011).sem(Sem::store(2)),
+            Sw = ("sw {rs2:xreg}, imm ({rs1:xreg})", imm[11:5],rs2[4:0],rs1[4:0],010,imm[4:0], 0100011).sem(Sem::store(4)),
+
+            Fence = ("fence imm", imm[11:0],00000000000000001111),
+            FenceTso = ("fence.tso", 10000011001100000000000000001111),
+            Ecall = ("ecall", 00000000000000000000000001110011),
+            Ebreak = ("ebreak", 00000000000100000000000001110011),
+        },
+
+        ///shifts of RV32I, shift amount is 5 bits
+        shifts {
+            Slli = ("slli {rd:xreg}, {rs1:xreg}, {imm:uimm5}", 0000000, imm[4:0], rs1[4:0], 001 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::shift(Op::Sll)),
+            Srli = ("srli {rd:xreg}, {rs1:xreg}, {imm:uimm5}", 0000000, imm[4:0], rs1[4:0], 101 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::shift(Op::Srl)),
+            Srai = ("srai {rd:xreg}, {rs1:xreg}, {imm:uimm5}", 0100000, imm[4:0], rs1[4:0], 101 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::shift(Op::Sra)),
+        },
+
+        ///integer multiplication and division, common to RV32M and RV64M
+        m {
+            Mul = ("mul {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],000,rd[4:0], 0110011).sem(Sem::reg(Op::Mul)),
+            Mulh = ("mulh {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],001,rd[4:0], 0110011).sem(Sem::reg(Op::Mulh)),
+            Mulhsu = ("mulhsu {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],010,rd[4:0], 0110011).sem(Sem::reg(Op::Mulhsu)),
+            Mulhu = ("mulhu {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],011,rd[4:0], 0110011).sem(Sem::reg(Op::Mulhu)),
+            Div = ("div {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],100,rd[4:0], 0110011).sem(Sem::reg(Op::Div)),
+            Divu = ("divu {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],101,rd[4:0], 0110011).sem(Sem::reg(Op::Divu)),
+            Rem = ("rem {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],110,rd[4:0], 0110011).sem(Sem::reg(Op::Rem)),
+            Remu = ("remu {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001,rs2[4:0],rs1[4:0],111,rd[4:0], 0110011).sem(Sem::reg(Op::Remu)),
+        },
+    }
 }
 
 impl ISARV32IMA {
+    ///RV32IMA with Zicsr and Zifencei
     pub fn new() -> ISARV32IMA {
+        let mut isa = ISARV32IMA::i(Xlen::X32);
+        isa.append( ISARV32IMA::m(Xlen::X32) );
+        isa.append( ISARV32IMA::a(Xlen::X32) );
+        isa.append( ISARV32IMA::zifencei() );
+        isa.append( ISARV32IMA::zicsr() );
+        isa
+    }
 
-        let list = vec! [
-            // RV32I
-            asdis::instruction32!("addi rd, rs1, imm", imm[11:0], rs1[4:0], 000 ,rd[4:0], 0010011),
-            asdis::instruction32!("andi rd, rs1, imm", imm[11:0], rs1[4:0], 111 ,rd[4:0], 0010011),
-            asdis::instruction32!("slti rd, rs1, imm", imm[11:0], rs1[4:0], 010 ,rd[4:0], 0010011),
-            asdis::instruction32!("sltiu rd, rs1, imm", imm[11:0], rs1[4:0], 011 ,rd[4:0], 0010011),
-            asdis::instruction32!("ori rd, rs1, imm", imm[11:0], rs1[4:0], 110 ,rd[4:0], 0010011),
-            asdis::instruction32!("xori rd, rs1, imm", imm[11:0], rs1[4:0], 100 ,rd[4:0], 0010011),
-
-            asdis::instruction32!("slli rd, rs1, imm", 0000000, imm[4:0], rs1[4:0], 001 ,rd[4:0], 0010011),
-            asdis::instruction32!("srli rd, rs1, imm", 0000000, imm[4:0], rs1[4:0], 101 ,rd[4:0], 0010011),
-            asdis::instruction32!("srai rd, rs1, imm", 0100000, imm[4:0], rs1[4:0], 101 ,rd[4:0], 0010011),
-
-            asdis::instruction32!("lui rd, imm", imm[31:12], rd[4:0], 0110111),
-            asdis::instruction32!("auipc rd, imm", imm[31:12], rd[4:0], 0010111),
-
-            asdis::instruction32!("add rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 000 ,rd[4:0], 0110011),
-            asdis::instruction32!("slt rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 010 ,rd[4:0], 0110011),
-            asdis::instruction32!("sltu rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 011 ,rd[4:0], 0110011),
-            asdis::instruction32!("and rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 111 ,rd[4:0], 0110011),
-            asdis::instruction32!("or rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 110 ,rd[4:0], 0110011),
-            asdis::instruction32!("xor rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 100 ,rd[4:0], 0110011),
-            asdis::instruction32!("sll rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 001 ,rd[4:0], 0110011),
-            asdis::instruction32!("srl rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 101 ,rd[4:0], 0110011),
-            asdis::instruction32!("sub rd, rs1, rs2", 0100000, rs2[4:0], rs1[4:0], 000 ,rd[4:0], 0110011),
-            asdis::instruction32!("sra rd, rs1, rs2", 0100000, rs2[4:0], rs1[4:0], 101 ,rd[4:0], 0110011),
-
-            asdis::instruction32!("nop", 00000000000000000000000000010011),
-
-            asdis::instruction32!("jal", imm[10|10:1|11|19:12] ,rd[4:0], 1101111),
-            asdis::instruction32!("jalr", imm[11:0],rs1[4:0], 000, rd[4:0], 1100111),
-
-            asdis::instruction32!("beq rs1, rs2, imm",imm[12|10:5],rs2[4:0],rs1[4:0],000,imm[11|4:1], 1100011),
-            asdis::instruction32!("bne rs1, rs2, imm",imm[12|10:5],rs2[4:0],rs1[4:0],001,imm[11|4:1], 1100011),
-            asdis::instruction32!("blt rs1, rs2, imm",imm[12|10:5],rs2[4:0],rs1[4:0],100,imm[11|4:1], 1100011),
-            asdis::instruction32!("bltu rs1, rs2, imm",imm[12|10:5],rs2[4:0],rs1[4:0],110,imm[11|4:1], 1100011),
-            asdis::instruction32!("bge rs1, rs2, imm",imm[12|10:5],rs2[4:0],rs1[4:0],101,imm[11|4:1], 1100011),
-            asdis::instruction32!("bgeu rs1, rs2, imm",imm[12|10:5],rs2[4:0],rs1[4:0],111,imm[11|4:1], 1100011),
-
-            asdis::instruction32!("lb rd, imm (rs1)", imm[11:0],rs1[4:0], 000 ,rd[4:0], 0000011),
-            asdis::instruction32!("lh rd, imm (rs1)", imm[11:0],rs1[4:0], 001 ,rd[4:0], 0000011),
-            asdis::instruction32!("lw rd, imm (rs1)", imm[11:0],rs1[4:0], 010 ,rd[4:0], 0000011),
-            asdis::instruction32!("ld rd, imm (rs1)", imm[11:0],rs1[4:0], 011 ,rd[4:0], 0000011),
-            asdis::instruction32!("lbu rd, imm (rs1)", imm[11:0],rs1[4:0], 100 ,rd[4:0], 0000011),
-            asdis::instruction32!("lhu rd, imm (rs1)", imm[11:0],rs1[4:0], 101 ,rd[4:0], 0000011),
-            asdis::instruction32!("lwu rd, imm (rs1)", imm[11:0],rs1[4:0], 110 ,rd[4:0], 0000011),
-
-            asdis::instruction32!("sb rs2, imm (rs1)", imm[11:5],rs2[4:0],rs1[4:0],000,imm[4:0], 0100011),
-            asdis::instruction32!("sh rs2, imm (rs1)", imm[11:5],rs2[4:0],rs1[4:0],001,imm[4:0], 0100011),
-            asdis::instruction32!("sw rs2, imm (rs1)", imm[11:5],rs2[4:0],rs1[4:0],010,imm[4:0], 0100011),
-            asdis::instruction32!("sd rs2, imm (rs1)", imm[11:5],rs2[4:0],rs1[4:0],011,imm[4:0], 0100011),
-
-            asdis::instruction32!("fence.tso", 10000011001100000000000000001111),
-            asdis::instruction32!("fence imm", imm[11:0],00000000000000001111),
-
-            asdis::instruction32!("ecall", 00000000000000000000000001110011),
-            asdis::instruction32!("ebreak", 00000000000100000000000001110011),
-
-            //RV32M
-            asdis::instruction32!("mul rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],000,rd[4:0], 0110011),
-            asdis::instruction32!("mulh rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],001,rd[4:0], 0110011),
-            asdis::instruction32!("mulhsu rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],010,rd[4:0], 0110011),
-            asdis::instruction32!("mulhu rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],011,rd[4:0], 0110011),
-            //RV64 asdis::instruction32!("mulw rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],000,rd[4:0], 0111011),
-
-            asdis::instruction32!("div rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],100,rd[4:0], 0110011),
-            asdis::instruction32!("divu rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],101,rd[4:0], 0110011),
-            asdis::instruction32!("rem rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],110,rd[4:0], 0110011),
-            asdis::instruction32!("remu rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],111,rd[4:0], 0110011),
-            //RV64 asdis::instruction32!("divw rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],100,rd[4:0], 0111011),
-            //RV64 asdis::instruction32!("divuw rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],101,rd[4:0], 0111011),
-            //RV64 asdis::instruction32!("remw rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],110,rd[4:0], 0111011),
-            //RV64 asdis::instruction32!("remuw rd, rs1, rs2", 0000001,rs2[4:0],rs1[4:0],111,rd[4:0], 0111011),
-
-            //RV32A
-            asdis::instruction32!("lr.w rd, rs1", 00010,00,00000,rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("lr.w.aq rd, rs1", 00010,10,00000,rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("lr.w.rl rd, rs1", 00010,01,00000,rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("lr.w.aq.rl rd, rs1", 00010,11,00000,rs1[4:0],010,rd[4:0], 0101111),
-
-            //RV64 asdis::instruction32!("lr.d rd, rs1", 00010,00,00000,rs1[4:0],011,rd[4:0], 0101111),
-            //RV64 asdis::instruction32!("lr.d.aq rd, rs1", 00010,10,00000,rs1[4:0],011,rd[4:0], 0101111),
-            //RV64 asdis::instruction32!("lr.d.rl rd, rs1", 00010,01,00000,rs1[4:0],011,rd[4:0], 0101111),
-            //RV64 asdis::instruction32!("lr.d.aq.rl rd, rs1", 00010,11,00000,rs1[4:0],011,rd[4:0], 0101111),
-
-            asdis::instruction32!("sc.w rd, rs1 (rs2)", 00011,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("sc.w.aq rd, rs1 (rs2)", 00011,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("sc.w.rl rd, rs1 (rs2)", 00011,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("sc.w.aq.rl rd, rs1 (rs2)", 00011,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            //RV64 asdis::instruction32!("sc.d rd, rs1 (rs2)", 00011,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111),
-            //RV64 asdis::instruction32!("sc.d.aq rd, rs1 (rs2)", 00011,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111),
-            //RV64 asdis::instruction32!("sc.d.rl rd, rs1 (rs2)", 00011,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111),
-            //RV64 asdis::instruction32!("sc.d.aq.rl rd, rs1 (rs2)", 00011,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111),
-
-            asdis::instruction32!("amoswap.w rd, rs1, rs2", 00001,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoswap.w.aq rd, rs1, rs2", 00001,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoswap.w.rl rd, rs1, rs2", 00001,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoswap.w.aq.rl rd, rs1, rs2", 00001,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amoadd.w rd, rs1, rs2", 00000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoadd.w.aq rd, rs1, rs2", 00000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoadd.w.rl rd, rs1, rs2", 00000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoadd.w.aq.rl rd, rs1, rs2", 00000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amoand.w rd, rs1, rs2", 01100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoand.w.aq rd, rs1, rs2", 01100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoand.w.rl rd, rs1, rs2", 01100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoand.w.aq.rl rd, rs1, rs2", 01100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amoor.w rd, rs1, rs2", 01000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoor.w.aq rd, rs1, rs2", 01000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoor.w.rl rd, rs1, rs2", 01000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoor.w.aq.rl rd, rs1, rs2", 01000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amoxor.w rd, rs1, rs2", 00100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoxor.w.aq rd, rs1, rs2", 00100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoxor.w.rl rd, rs1, rs2", 00100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amoxor.w.aq.rl rd, rs1, rs2", 00100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amomax.w rd, rs1, rs2", 10100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomax.w.aq rd, rs1, rs2", 10100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomax.w.rl rd, rs1, rs2", 10100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomax.w.aq.rl rd, rs1, rs2", 10100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amomaxu.w rd, rs1, rs2", 11100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomaxu.w.aq rd, rs1, rs2", 11100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomaxu.w.rl rd, rs1, rs2", 11100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomaxu.w.aq.rl rd, rs1, rs2", 11100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amomin.w rd, rs1, rs2", 10000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomin.w.aq rd, rs1, rs2", 10000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomin.w.rl rd, rs1, rs2", 10000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amomin.w.aq.rl rd, rs1, rs2", 10000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            asdis::instruction32!("amominu.w rd, rs1, rs2", 11000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amominu.w.aq rd, rs1, rs2", 11000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amominu.w.rl rd, rs1, rs2", 11000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-            asdis::instruction32!("amominu.w.aq.rl rd, rs1, rs2", 11000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111),
-
-            //for RV64 repeat last 9 parck of 4 commands with suffix .d, with changed with param from 010 to 011
-
-            //Zifencei
-            asdis::instruction32!("fence.i",00000000000000000000000000001111),
-
-            //Zicsr
-            asdis::instruction32!("csrrw rd, csr, rs1",csr[11:0],rs1[4:0],001,rd[4:0],1110011),
-            asdis::instruction32!("csrrs rd, csr, rs1",csr[11:0],rs1[4:0],010,rd[4:0],1110011),
-            asdis::instruction32!("csrrc rd, csr, rs1",csr[11:0],rs1[4:0],011,rd[4:0],1110011),
-            asdis::instruction32!("csrrwi rd, csr, imm",csr[11:0],imm[4:0],101,rd[4:0],1110011),
-            asdis::instruction32!("csrrsi rd, csr, imm",csr[11:0],imm[4:0],110,rd[4:0],1110011),
-            asdis::instruction32!("csrrci rd, csr, imm",csr[11:0],imm[4:0],111,rd[4:0],1110011),
+    ///base integer instructions
+    pub fn i(xlen : Xlen) -> ISARV32IMA {
+        let mut list = Rv32Instr::i();
+        list.extend( vec! [
+            asdis::instruction32!("nop", 00000000000000000000000000010011).sem(Sem::Nop),
 
             //MISC
             asdis::instruction32!("illegal.0", 00000000000000000000000000000000),
             asdis::instruction32!("illegal.1", 11111111111111111111111111111111),
-       ];
+        ]);
+
+        match xlen {
+            Xlen::X32 => list.extend( Rv32Instr::shifts() ),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction32!("slli {rd:xreg}, {rs1:xreg}, {imm:uimm6}", 000000, imm[5:0], rs1[4:0], 001 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::shift(Op::Sll)),
+                asdis::instruction32!("srli {rd:xreg}, {rs1:xreg}, {imm:uimm6}", 000000, imm[5:0], rs1[4:0], 101 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::shift(Op::Srl)),
+                asdis::instruction32!("srai {rd:xreg}, {rs1:xreg}, {imm:uimm6}", 010000, imm[5:0], rs1[4:0], 101 ,rd[4:0], 0010011, hint if rd == 0).sem(Sem::shift(Op::Sra)),
+
+                asdis::instruction32!("ld {rd:xreg}, imm ({rs1:xreg})", imm[11:0], rs1[4:0], 011, rd[4:0], 0000011).sem(Sem::load(8, true)),
+                asdis::instruction32!("lwu {rd:xreg}, imm ({rs1:xreg})", imm[11:0], rs1[4:0], 110, rd[4:0], 0000011).sem(Sem::load(4, false)),
+                asdis::instruction32!("sd {rs2:xreg}, imm ({rs1:xreg})", imm[11:5], rs2[4:0], rs1[4:0], 011, imm[4:0], 0100011).sem(Sem::store(8)),
+
+                asdis::instruction32!("addiw {rd:xreg}, {rs1:xreg}, imm", imm[11:0], rs1[4:0], 000, rd[4:0], 0011011, hint if rd == 0).sem(Sem::imm(Op::Add).word()),
+                asdis::instruction32!("slliw {rd:xreg}, {rs1:xreg}, {imm:uimm5}", 0000000, imm[4:0], rs1[4:0], 001, rd[4:0], 0011011, hint if rd == 0).sem(Sem::shift(Op::Sll).word()),
+                asdis::instruction32!("srliw {rd:xreg}, {rs1:xreg}, {imm:uimm5}", 0000000, imm[4:0], rs1[4:0], 101, rd[4:0], 0011011, hint if rd == 0).sem(Sem::shift(Op::Srl).word()),
+                asdis::instruction32!("sraiw {rd:xreg}, {rs1:xreg}, {imm:uimm5}", 0100000, imm[4:0], rs1[4:0], 101, rd[4:0], 0011011, hint if rd == 0).sem(Sem::shift(Op::Sra).word()),
+                asdis::instruction32!("addw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 000, rd[4:0], 0111011, hint if rd == 0).sem(Sem::reg(Op::Add).word()),
+                asdis::instruction32!("subw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 000, rd[4:0], 0111011, hint if rd == 0).sem(Sem::reg(Op::Sub).word()),
+                asdis::instruction32!("sllw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 001, rd[4:0], 0111011, hint if rd == 0).sem(Sem::reg(Op::Sll).word()),
+                asdis::instruction32!("srlw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 101, rd[4:0], 0111011, hint if rd == 0).sem(Sem::reg(Op::Srl).word()),
+                asdis::instruction32!("sraw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 101, rd[4:0], 0111011, hint if rd == 0).sem(Sem::reg(Op::Sra).word()),
+
+            ]),
+        };
+
+        ISARV32IMA { list, ..Default::default() }
+    }
+
+    ///integer multiplication and division
+    pub fn m(xlen : Xlen) -> ISARV32IMA {
+        let mut list = Rv32Instr::m();
+
+        if xlen == Xlen::X64 {
+            list.extend( vec! [
+                asdis::instruction32!("mulw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001, rs2[4:0], rs1[4:0], 000, rd[4:0], 0111011).sem(Sem::reg(Op::Mul).word()),
+                asdis::instruction32!("divw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001, rs2[4:0], rs1[4:0], 100, rd[4:0], 0111011).sem(Sem::reg(Op::Div).word()),
+                asdis::instruction32!("divuw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001, rs2[4:0], rs1[4:0], 101, rd[4:0], 0111011).sem(Sem::reg(Op::Divu).word()),
+                asdis::instruction32!("remw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001, rs2[4:0], rs1[4:0], 110, rd[4:0], 0111011).sem(Sem::reg(Op::Rem).word()),
+                asdis::instruction32!("remuw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000001, rs2[4:0], rs1[4:0], 111, rd[4:0], 0111011).sem(Sem::reg(Op::Remu).word()),
+            ]);
+        }
+
+        ISARV32IMA { list, ..Default::default() }
+    }
 
-       let mut show_dict = HashMap::new();
-       show_dict.insert(String::from("rd"), show_register as ShowFun::<RV32Type>);
-       show_dict.insert(String::from("rs1"), show_register as ShowFun::<RV32Type>);
-       show_dict.insert(String::from("rs2"), show_register as ShowFun::<RV32Type>);
+    ///atomic instructions
+    pub fn a(xlen : Xlen) -> ISARV32IMA {
+        let mut list = vec! [
+            asdis::instruction32!("lr.w {rd:xreg}, {rs1:xreg}", 00010,00,00000,rs1[4:0],010,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 4 }),
+            asdis::instruction32!("lr.w.aq {rd:xreg}, {rs1:xreg}", 00010,10,00000,rs1[4:0],010,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 4 }),
+            asdis::instruction32!("lr.w.rl {rd:xreg}, {rs1:xreg}", 00010,01,00000,rs1[4:0],010,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 4 }),
+            asdis::instruction32!("lr.w.aq.rl {rd:xreg}, {rs1:xreg}", 00010,11,00000,rs1[4:0],010,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 4 }),
+
+            asdis::instruction32!("sc.w {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 4 }),
+            asdis::instruction32!("sc.w.aq {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 4 }),
+            asdis::instruction32!("sc.w.rl {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 4 }),
+            asdis::instruction32!("sc.w.aq.rl {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 4 }),
+
+            asdis::instruction32!("amoswap.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 4 }),
+            asdis::instruction32!("amoswap.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 4 }),
+            asdis::instruction32!("amoswap.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 4 }),
+            asdis::instruction32!("amoswap.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 4 }),
+
+            asdis::instruction32!("amoadd.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 4 }),
+            asdis::instruction32!("amoadd.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 4 }),
+            asdis::instruction32!("amoadd.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 4 }),
+            asdis::instruction32!("amoadd.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 4 }),
+
+            asdis::instruction32!("amoand.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 4 }),
+            asdis::instruction32!("amoand.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 4 }),
+            asdis::instruction32!("amoand.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 4 }),
+            asdis::instruction32!("amoand.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 4 }),
+
+            asdis::instruction32!("amoor.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 4 }),
+            asdis::instruction32!("amoor.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 4 }),
+            asdis::instruction32!("amoor.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 4 }),
+            asdis::instruction32!("amoor.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 4 }),
+
+            asdis::instruction32!("amoxor.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 4 }),
+            asdis::instruction32!("amoxor.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 4 }),
+            asdis::instruction32!("amoxor.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 4 }),
+            asdis::instruction32!("amoxor.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 4 }),
+
+            asdis::instruction32!("amomax.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 4 }),
+            asdis::instruction32!("amomax.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 4 }),
+            asdis::instruction32!("amomax.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 4 }),
+            asdis::instruction32!("amomax.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 4 }),
+
+            asdis::instruction32!("amomaxu.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 4 }),
+            asdis::instruction32!("amomaxu.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 4 }),
+            asdis::instruction32!("amomaxu.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 4 }),
+            asdis::instruction32!("amomaxu.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 4 }),
+
+            asdis::instruction32!("amomin.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 4 }),
+            asdis::instruction32!("amomin.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 4 }),
+            asdis::instruction32!("amomin.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 4 }),
+            asdis::instruction32!("amomin.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 4 }),
+
+            asdis::instruction32!("amominu.w {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,00,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 4 }),
+            asdis::instruction32!("amominu.w.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,10,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 4 }),
+            asdis::instruction32!("amominu.w.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,01,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 4 }),
+            asdis::instruction32!("amominu.w.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,11,rs2[4:0],rs1[4:0],010,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 4 }),
+        ];
+
+        if xlen == Xlen::X64 {
+            list.extend( vec! [
+                asdis::instruction32!("lr.d {rd:xreg}, {rs1:xreg}", 00010,00,00000,rs1[4:0],011,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 8 }),
+                asdis::instruction32!("lr.d.aq {rd:xreg}, {rs1:xreg}", 00010,10,00000,rs1[4:0],011,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 8 }),
+                asdis::instruction32!("lr.d.rl {rd:xreg}, {rs1:xreg}", 00010,01,00000,rs1[4:0],011,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 8 }),
+                asdis::instruction32!("lr.d.aq.rl {rd:xreg}, {rs1:xreg}", 00010,11,00000,rs1[4:0],011,rd[4:0], 0101111).sem(Sem::LoadReserved { bytes : 8 }),
+
+                asdis::instruction32!("sc.d {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 8 }),
+                asdis::instruction32!("sc.d.aq {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 8 }),
+                asdis::instruction32!("sc.d.rl {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 8 }),
+                asdis::instruction32!("sc.d.aq.rl {rd:xreg}, {rs1:xreg} ({rs2:xreg})", 00011,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::StoreConditional { bytes : 8 }),
+
+                asdis::instruction32!("amoswap.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 8 }),
+                asdis::instruction32!("amoswap.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 8 }),
+                asdis::instruction32!("amoswap.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 8 }),
+                asdis::instruction32!("amoswap.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00001,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Second, bytes : 8 }),
+
+                asdis::instruction32!("amoadd.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 8 }),
+                asdis::instruction32!("amoadd.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 8 }),
+                asdis::instruction32!("amoadd.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 8 }),
+                asdis::instruction32!("amoadd.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00000,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Add, bytes : 8 }),
+
+                asdis::instruction32!("amoand.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 8 }),
+                asdis::instruction32!("amoand.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 8 }),
+                asdis::instruction32!("amoand.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 8 }),
+                asdis::instruction32!("amoand.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01100,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::And, bytes : 8 }),
+
+                asdis::instruction32!("amoor.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 8 }),
+                asdis::instruction32!("amoor.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 8 }),
+                asdis::instruction32!("amoor.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 8 }),
+                asdis::instruction32!("amoor.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 01000,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Or, bytes : 8 }),
+
+                asdis::instruction32!("amoxor.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 8 }),
+                asdis::instruction32!("amoxor.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 8 }),
+                asdis::instruction32!("amoxor.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 8 }),
+                asdis::instruction32!("amoxor.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 00100,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Xor, bytes : 8 }),
+
+                asdis::instruction32!("amomax.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 8 }),
+                asdis::instruction32!("amomax.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 8 }),
+                asdis::instruction32!("amomax.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 8 }),
+                asdis::instruction32!("amomax.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10100,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Max, bytes : 8 }),
+
+                asdis::instruction32!("amomaxu.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 8 }),
+                asdis::instruction32!("amomaxu.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 8 }),
+                asdis::instruction32!("amomaxu.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 8 }),
+                asdis::instruction32!("amomaxu.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11100,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Maxu, bytes : 8 }),
+
+                asdis::instruction32!("amomin.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 8 }),
+                asdis::instruction32!("amomin.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 8 }),
+                asdis::instruction32!("amomin.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 8 }),
+                asdis::instruction32!("amomin.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 10000,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Min, bytes : 8 }),
+
+                asdis::instruction32!("amominu.d {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,00,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 8 }),
+                asdis::instruction32!("amominu.d.aq {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,10,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 8 }),
+                asdis::instruction32!("amominu.d.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,01,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 8 }),
+                asdis::instruction32!("amominu.d.aq.rl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 11000,11,rs2[4:0],rs1[4:0],011,rd[4:0], 0101111).sem(Sem::Amo { op : Op::Minu, bytes : 8 }),
+            ]);
+        }
+
+        ISARV32IMA { list, ..Default::default() }
+    }
+
+    ///instruction-fetch fence
+    pub fn zifencei() -> ISARV32IMA {
+        let list = vec! [
+            asdis::instruction32!("fence.i",00000000000000000001000000001111),
+        ];
+
+        ISARV32IMA { list, ..Default::default() }
+    }
 
-        ISARV32IMA { list, show_dict }
+    ///control and status register instructions
+    pub fn zicsr() -> ISARV32IMA {
+        let list = vec! [
+            asdis::instruction32!("csrrw {rd:xreg}, {csr:csr}, {rs1:xreg}",csr[11:0],rs1[4:0],001,rd[4:0],1110011).sem(Sem::Csr { op : CsrOp::Write, src : Operand::Reg("rs1") }),
+            asdis::instruction32!("csrrs {rd:xreg}, {csr:csr}, {rs1:xreg}",csr[11:0],rs1[4:0],010,rd[4:0],1110011).sem(Sem::Csr { op : CsrOp::Set, src : Operand::Reg("rs1") }),
+            asdis::instruction32!("csrrc {rd:xreg}, {csr:csr}, {rs1:xreg}",csr[11:0],rs1[4:0],011,rd[4:0],1110011).sem(Sem::Csr { op : CsrOp::Clear, src : Operand::Reg("rs1") }),
+            asdis::instruction32!("csrrwi {rd:xreg}, {csr:csr}, imm",csr[11:0],imm[4:0],101,rd[4:0],1110011).sem(Sem::Csr { op : CsrOp::Write, src : Operand::Uimm("imm") }),
+            asdis::instruction32!("csrrsi {rd:xreg}, {csr:csr}, imm",csr[11:0],imm[4:0],110,rd[4:0],1110011).sem(Sem::Csr { op : CsrOp::Set, src : Operand::Uimm("imm") }),
+            asdis::instruction32!("csrrci {rd:xreg}, {csr:csr}, imm",csr[11:0],imm[4:0],111,rd[4:0],1110011).sem(Sem::Csr { op : CsrOp::Clear, src : Operand::Uimm("imm") }),
+        ];
+
+        ISARV32IMA { list, ..Default::default() }
     }
 }
 
@@ -186,6 +291,7 @@ mod test {
     use super::*;
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn ok() {
         let isa = ISARV32IMA::new();
         println!("{:?}", isa);
@@ -203,4 +309,100 @@ mod test {
         assert_eq!(i1.bin, bin);
     }
 
+    #[test]
+    fn csr_names() {
+        let isa = ISARV32IMA::new();
+        assert_eq!(crate::decoder::decode(0x30529073, &isa), "csrrw r0, mtvec, r5");
+        assert_eq!(crate::encoder::encode("csrrw r0, mtvec, r5", &isa), Some( 0x30529073 ));
+        assert_eq!(crate::encoder::encode("csrrs r10, pmpaddr3, r0", &isa), Some( 0x3b302573 ));
+    }
+
+    #[test]
+    fn hints() {
+        let isa = ISARV32IMA::new();
+        assert_eq!(crate::decoder::decode(0x00000013, &isa), "nop");
+        assert_eq!(crate::decoder::decode(0x00508013, &isa), "addi r0, r1, 0x5 # hint");
+        assert_eq!(crate::decoder::decode(0x00209013, &isa), "slli r0, r1, 0x2 # hint");
+        assert_eq!(crate::decoder::decode(0x00209093, &isa), "slli r1, r1, 0x2");
+        assert_eq!(crate::encoder::encode("addi r0, r1, 5", &isa), Some( 0x00508013 ));
+    }
+
+    #[test]
+    fn shift_amount_rv64() {
+        let isa = ISARV32IMA::i(Xlen::X64);
+        assert_eq!(crate::decoder::decode(0x03f59513, &isa), "slli r10, r11, 0x3F");
+        assert_eq!(crate::encoder::encode("slli r10, r11, 63", &isa), Some( 0x03f59513 ));
+        assert_eq!(crate::encoder::encode("slli r10, r11, 64", &isa), None);
+        assert_eq!(crate::encoder::encode("slliw r10, r11, 32", &isa), None);
+    }
+
+    #[test]
+    fn fence_i() {
+        let isa = ISARV32IMA::new();
+        assert_eq!(crate::decoder::decode(0x0000100f, &isa), "fence.i");
+        assert_eq!(crate::encoder::encode("fence.i", &isa), Some( 0x0000100f ));
+        assert_eq!(crate::decoder::decode(0x0000000f, &isa), "fence 0x0");
+    }
+
+    #[test]
+    fn branch_offset() {
+        //imm[11] is in bit 7 of word, imm[4:1] are in bits 11:8
+        let isa = ISARV32IMA::new();
+        assert_eq!(crate::decoder::decode(0x002080e3, &isa), "beq r1, r2, 0x800");
+        assert_eq!(crate::decoder::decode(0x00209f63, &isa), "bne r1, r2, 0x1E");
+        assert_eq!(crate::decoder::decode(0x0020ffe3, &isa), "bgeu r1, r2, 0x81E");
+        assert_eq!(crate::encoder::encode("beq r1, r2, 0x800", &isa), Some( 0x002080e3 ));
+        assert_eq!(crate::encoder::encode("bgeu r1, r2, 0x81E", &isa), Some( 0x0020ffe3 ));
+    }
+
+    #[test]
+    fn jal_offset() {
+        //imm[20] is the highest bit of word, it was given as imm[10] before
+        let isa = ISARV32IMA::new();
+        assert_eq!(crate::decoder::decode(0x8000006f, &isa), "jal r0, 0x100000");
+        assert_eq!(crate::decoder::decode(0x7fe0006f, &isa), "jal r0, 0x7FE");
+        assert_eq!(crate::decoder::decode(0x001000ef, &isa), "jal r1, 0x800");
+        assert_eq!(crate::encoder::encode("jal r0, 0x100000", &isa), Some( 0x8000006f ));
+        assert_eq!(crate::encoder::encode("jal r0, 0x7FE", &isa), Some( 0x7fe0006f ));
+    }
+
+    #[test]
+    fn typed() {
+        let addi = Rv32Instr::Addi { rd : Reg(10), rs1 : Reg(10), imm : -1 };
+        assert_eq!(addi.encode(), Some( 0xfff50513 ));
+        assert_eq!(Rv32Instr::decode(0xfff50513), Some( addi ));
+
+        let beq = Rv32Instr::Beq { rs1 : Reg(10), rs2 : Reg(11), imm : -8 };
+        assert_eq!(beq.encode().and_then(Rv32Instr::decode), Some( beq ));
+        let jal = Rv32Instr::Jal { rd : Reg(1), imm : 0x800 };
+        assert_eq!(jal.encode(), Some( 0x001000ef ));
+        assert_eq!(Rv32Instr::decode(0x0000_0073), Some( Rv32Instr::Ecall ));
+        assert_eq!(Rv32Instr::decode(0x4015_5513), Some( Rv32Instr::Srai { rd : Reg(10), rs1 : Reg(10), imm : 1 } ));
+        assert_eq!(Rv32Instr::decode(0x02b50533), Some( Rv32Instr::Mul { rd : Reg(10), rs1 : Reg(10), rs2 : Reg(11) } ));
+        assert_eq!(Rv32Instr::decode(0x8330000f), Some( Rv32Instr::FenceTso ));
+        assert_eq!(Rv32Instr::decode(0x0000_0000), None);
+    }
+
+    #[test]
+    fn typed_out_of_range() {
+        assert_eq!(Rv32Instr::Addi { rd : Reg(10), rs1 : Reg(10), imm : 2048 }.encode(), None);
+        assert_eq!(Rv32Instr::Addi { rd : Reg(10), rs1 : Reg(10), imm : -2048 }.encode(), Some( 0x80050513 ));
+        assert_eq!(Rv32Instr::Addi { rd : Reg(32), rs1 : Reg(10), imm : 0 }.encode(), None);
+        assert_eq!(Rv32Instr::Slli { rd : Reg(10), rs1 : Reg(10), imm : 31 }.encode(), Some( 0x01f51513 ));
+        assert_eq!(Rv32Instr::Slli { rd : Reg(10), rs1 : Reg(10), imm : 32 }.encode(), None);
+        //offset of branch is even, lui keeps upper 20 bits only
+        assert_eq!(Rv32Instr::Beq { rs1 : Reg(10), rs2 : Reg(11), imm : 3 }.encode(), None);
+        assert_eq!(Rv32Instr::Lui { rd : Reg(5), imm : 0x1234 }.encode(), None);
+    }
+
+    #[test]
+    fn typed_same_as_table() {
+        let isa = ISARV32IMA::new();
+        assert_eq!(Rv32Instr::Lui { rd : Reg(5), imm : -0x1000 }.encode(), crate::encoder::encode("lui r5, 0xFFFFF000", &isa));
+        assert_eq!(Rv32Instr::Sw { rs2 : Reg(8), imm : 12, rs1 : Reg(2) }.encode(), crate::encoder::encode("sw r8, 12 (r2)", &isa));
+        assert_eq!(Rv32Instr::Bne { rs1 : Reg(5), rs2 : Reg(0), imm : 0x10 }.encode(), crate::encoder::encode("bne r5, r0, 0x10", &isa));
+        assert_eq!(Rv32Instr::Srai { rd : Reg(10), rs1 : Reg(10), imm : 1 }.encode(), crate::encoder::encode("srai r10, r10, 0x1", &isa));
+        //rows of typed groups are rows of the table
+        assert_eq!(Rv32Instr::i().len() + Rv32Instr::shifts().len() + 3, ISARV32IMA::i(Xlen::X32).list.len());
+    }
 }
@@ -0,0 +1,202 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+use std::collections::HashMap;
+
+pub type ISARVK = ISA<RV32Type>;
+
+///byte select of aes32 and sm4 instructions, and round number of aes64ks1i are shown as decimal
+fn show_decimal(v : u32) -> String
+{
+    format!("{}", v)
+}
+
+///helper to build show dictionary common for all cryptography extensions
+fn show_dict() -> ShowDict<RV32Type> {
+    let mut show_dict = HashMap::new();
+    show_dict.insert(String::from("bs"), show_decimal as ShowFun::<RV32Type>);
+    show_dict.insert(String::from("rnum"), show_decimal as ShowFun::<RV32Type>);
+    show_dict
+}
+
+impl ISARVK {
+    ///Zbkb, bit-manipulation for cryptography
+    pub fn zbkb(xlen : Xlen) -> ISARVK {
+        let mut list = vec! [
+            asdis::instruction32!("andn {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 111, rd[4:0], 0110011).sem(Sem::reg(Op::Andn)),
+            asdis::instruction32!("orn {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 110, rd[4:0], 0110011).sem(Sem::reg(Op::Orn)),
+            asdis::instruction32!("xnor {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 100, rd[4:0], 0110011).sem(Sem::reg(Op::Xnor)),
+            asdis::instruction32!("rol {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 001, rd[4:0], 0110011).sem(Sem::reg(Op::Rol)),
+            asdis::instruction32!("ror {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 101, rd[4:0], 0110011).sem(Sem::reg(Op::Ror)),
+            asdis::instruction32!("pack {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000100, rs2[4:0], rs1[4:0], 100, rd[4:0], 0110011),
+            asdis::instruction32!("packh {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000100, rs2[4:0], rs1[4:0], 111, rd[4:0], 0110011),
+            asdis::instruction32!("brev8 {rd:xreg}, {rs1:xreg}", 011010000111, rs1[4:0], 101, rd[4:0], 0010011),
+        ];
+
+        match xlen {
+            Xlen::X32 => list.extend( vec! [
+                asdis::instruction32!("rev8 {rd:xreg}, {rs1:xreg}", 011010011000, rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::unary(Unary::Rev8)),
+                asdis::instruction32!("rori {rd:xreg}, {rs1:xreg}, shamt", 0110000, shamt[4:0], rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::shamt(Op::Ror)),
+                asdis::instruction32!("zip {rd:xreg}, {rs1:xreg}", 000010001111, rs1[4:0], 001, rd[4:0], 0010011),
+                asdis::instruction32!("unzip {rd:xreg}, {rs1:xreg}", 000010001111, rs1[4:0], 101, rd[4:0], 0010011),
+            ]),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction32!("rev8 {rd:xreg}, {rs1:xreg}", 011010111000, rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::unary(Unary::Rev8)),
+                asdis::instruction32!("rori {rd:xreg}, {rs1:xreg}, shamt", 011000, shamt[5:0], rs1[4:0], 101, rd[4:0], 0010011).sem(Sem::shamt(Op::Ror)),
+                asdis::instruction32!("rolw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 001, rd[4:0], 0111011).sem(Sem::reg(Op::Rol).word()),
+                asdis::instruction32!("rorw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0110000, rs2[4:0], rs1[4:0], 101, rd[4:0], 0111011).sem(Sem::reg(Op::Ror).word()),
+                asdis::instruction32!("roriw {rd:xreg}, {rs1:xreg}, shamt", 0110000, shamt[4:0], rs1[4:0], 101, rd[4:0], 0011011).sem(Sem::shamt(Op::Ror).word()),
+                asdis::instruction32!("packw {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000100, rs2[4:0], rs1[4:0], 100, rd[4:0], 0111011),
+            ]),
+        };
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+
+    ///Zbkc, carry-less multiplication for cryptography
+    pub fn zbkc() -> ISARVK {
+        let list = vec! [
+            asdis::instruction32!("clmul {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 001, rd[4:0], 0110011),
+            asdis::instruction32!("clmulh {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000101, rs2[4:0], rs1[4:0], 011, rd[4:0], 0110011),
+        ];
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+
+    ///Zbkx, crossbar permutations
+    pub fn zbkx() -> ISARVK {
+        let list = vec! [
+            asdis::instruction32!("xperm4 {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010100, rs2[4:0], rs1[4:0], 010, rd[4:0], 0110011),
+            asdis::instruction32!("xperm8 {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010100, rs2[4:0], rs1[4:0], 100, rd[4:0], 0110011),
+        ];
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+
+    ///Zknd, NIST AES decryption
+    pub fn zknd(xlen : Xlen) -> ISARVK {
+        let list = match xlen {
+            Xlen::X32 => vec! [
+                asdis::instruction32!("aes32dsi {rd:xreg}, {rs1:xreg}, {rs2:xreg}, bs", bs[1:0], 10101, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("aes32dsmi {rd:xreg}, {rs1:xreg}, {rs2:xreg}, bs", bs[1:0], 10111, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+            ],
+            Xlen::X64 => vec! [
+                asdis::instruction32!("aes64ds {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0011101, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("aes64dsm {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0011111, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("aes64im {rd:xreg}, {rs1:xreg}", 0011000, 00000, rs1[4:0], 001, rd[4:0], 0010011),
+                asdis::instruction32!("aes64ks1i {rd:xreg}, {rs1:xreg}, rnum", 00110001, rnum[3:0], rs1[4:0], 001, rd[4:0], 0010011, rnum == {0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10}),
+                asdis::instruction32!("aes64ks2 {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0111111, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+            ],
+        };
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+
+    ///Zkne, NIST AES encryption
+    pub fn zkne(xlen : Xlen) -> ISARVK {
+        let list = match xlen {
+            Xlen::X32 => vec! [
+                asdis::instruction32!("aes32esi {rd:xreg}, {rs1:xreg}, {rs2:xreg}, bs", bs[1:0], 10001, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("aes32esmi {rd:xreg}, {rs1:xreg}, {rs2:xreg}, bs", bs[1:0], 10011, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+            ],
+            Xlen::X64 => vec! [
+                asdis::instruction32!("aes64es {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0011001, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("aes64esm {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0011011, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("aes64ks1i {rd:xreg}, {rs1:xreg}, rnum", 00110001, rnum[3:0], rs1[4:0], 001, rd[4:0], 0010011, rnum == {0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10}),
+                asdis::instruction32!("aes64ks2 {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0111111, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+            ],
+        };
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+
+    ///Zknh, NIST hash functions
+    pub fn zknh(xlen : Xlen) -> ISARVK {
+        let mut list = vec! [
+            asdis::instruction32!("sha256sig0 {rd:xreg}, {rs1:xreg}", 0001000, 00010, rs1[4:0], 001, rd[4:0], 0010011),
+            asdis::instruction32!("sha256sig1 {rd:xreg}, {rs1:xreg}", 0001000, 00011, rs1[4:0], 001, rd[4:0], 0010011),
+            asdis::instruction32!("sha256sum0 {rd:xreg}, {rs1:xreg}", 0001000, 00000, rs1[4:0], 001, rd[4:0], 0010011),
+            asdis::instruction32!("sha256sum1 {rd:xreg}, {rs1:xreg}", 0001000, 00001, rs1[4:0], 001, rd[4:0], 0010011),
+        ];
+
+        //SHA-512 works on register pairs on RV32
+        match xlen {
+            Xlen::X32 => list.extend( vec! [
+                asdis::instruction32!("sha512sig0h {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0101110, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("sha512sig0l {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0101010, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("sha512sig1h {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0101111, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("sha512sig1l {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0101011, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("sha512sum0r {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0101000, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+                asdis::instruction32!("sha512sum1r {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0101001, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+            ]),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction32!("sha512sig0 {rd:xreg}, {rs1:xreg}", 0001000, 00110, rs1[4:0], 001, rd[4:0], 0010011),
+                asdis::instruction32!("sha512sig1 {rd:xreg}, {rs1:xreg}", 0001000, 00111, rs1[4:0], 001, rd[4:0], 0010011),
+                asdis::instruction32!("sha512sum0 {rd:xreg}, {rs1:xreg}", 0001000, 00100, rs1[4:0], 001, rd[4:0], 0010011),
+                asdis::instruction32!("sha512sum1 {rd:xreg}, {rs1:xreg}", 0001000, 00101, rs1[4:0], 001, rd[4:0], 0010011),
+            ]),
+        };
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+
+    ///Zksed, ShangMi SM4 block cipher
+    pub fn zksed() -> ISARVK {
+        let list = vec! [
+            asdis::instruction32!("sm4ed {rd:xreg}, {rs1:xreg}, {rs2:xreg}, bs", bs[1:0], 11000, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+            asdis::instruction32!("sm4ks {rd:xreg}, {rs1:xreg}, {rs2:xreg}, bs", bs[1:0], 11010, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+        ];
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+
+    ///Zksh, ShangMi SM3 hash function
+    pub fn zksh() -> ISARVK {
+        let list = vec! [
+            asdis::instruction32!("sm3p0 {rd:xreg}, {rs1:xreg}", 0001000, 01000, rs1[4:0], 001, rd[4:0], 0010011),
+            asdis::instruction32!("sm3p1 {rd:xreg}, {rs1:xreg}", 0001000, 01001, rs1[4:0], 001, rd[4:0], 0010011),
+        ];
+
+        ISARVK { list, show_dict : show_dict(), ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::{decode, try_decode};
+    use crate::encoder::encode;
+
+    #[test]
+    fn pack() {
+        let isa = ISARVK::zbkb(Xlen::X32);
+        assert_eq!(decode(0x08c5c533, &isa), "pack r10, r11, r12");
+        assert_eq!(encode("brev8 r10, r11", &isa), Some( 0x6875d513 ));
+    }
+
+    #[test]
+    fn byte_select() {
+        let isa = ISARVK::zkne(Xlen::X32);
+        assert_eq!(decode(0xe2c58533, &isa), "aes32esi r10, r11, r12, 3");
+        assert_eq!(encode("aes32esmi r10, r11, r12, 2", &isa), Some( 0xa6c58533 ));
+        assert_eq!(encode("aes32esi r10, r11, r12, 4", &isa), None);
+        let isa = ISARVK::zksed();
+        assert_eq!(decode(0x70c58533, &isa), "sm4ed r10, r11, r12, 1");
+    }
+
+    #[test]
+    fn xlen() {
+        let isa = ISARVK::zknh(Xlen::X64);
+        assert_eq!(decode(0x10659513, &isa), "sha512sig0 r10, r11");
+        let isa = ISARVK::zknd(Xlen::X64);
+        assert_eq!(decode(0x31a59513, &isa), "aes64ks1i r10, r11, 10");
+    }
+
+    #[test]
+    fn round_number() {
+        let isa = ISARVK::zknd(Xlen::X64);
+        assert_eq!(try_decode(0x31b59513, &isa), None);
+        assert_eq!(encode("aes64ks1i r10, r11, 10", &isa), Some( 0x31a59513 ));
+        assert_eq!(encode("aes64ks1i r10, r11, 11", &isa), None);
+    }
+}
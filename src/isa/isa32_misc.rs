@@ -0,0 +1,108 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+
+pub type ISARVMisc = ISA<RV32Type>;
+
+impl ISARVMisc {
+    ///Zicbom, cache-block management
+    pub fn zicbom() -> ISARVMisc {
+        let list = vec! [
+            asdis::instruction32!("cbo.inval ({rs1:xreg})", 000000000000, rs1[4:0], 010, 00000, 0001111),
+            asdis::instruction32!("cbo.clean ({rs1:xreg})", 000000000001, rs1[4:0], 010, 00000, 0001111),
+            asdis::instruction32!("cbo.flush ({rs1:xreg})", 000000000010, rs1[4:0], 010, 00000, 0001111),
+        ];
+
+        ISARVMisc { list, ..Default::default() }
+    }
+
+    ///Zicboz, cache-block zero
+    pub fn zicboz() -> ISARVMisc {
+        let list = vec! [
+            asdis::instruction32!("cbo.zero ({rs1:xreg})", 000000000100, rs1[4:0], 010, 00000, 0001111),
+        ];
+
+        ISARVMisc { list, ..Default::default() }
+    }
+
+    ///Zicbop, cache-block prefetch, carved out of ori with rd=x0
+    pub fn zicbop() -> ISARVMisc {
+        let list = vec! [
+            asdis::instruction32!("prefetch.i imm ({rs1:xreg})", imm[11:5], 00000, rs1[4:0], 110, 00000, 0010011),
+            asdis::instruction32!("prefetch.r imm ({rs1:xreg})", imm[11:5], 00001, rs1[4:0], 110, 00000, 0010011),
+            asdis::instruction32!("prefetch.w imm ({rs1:xreg})", imm[11:5], 00011, rs1[4:0], 110, 00000, 0010011),
+        ];
+
+        ISARVMisc { list, ..Default::default() }
+    }
+
+    ///Zicond, integer conditional operations
+    pub fn zicond() -> ISARVMisc {
+        let list = vec! [
+            asdis::instruction32!("czero.eqz {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000111, rs2[4:0], rs1[4:0], 101, rd[4:0], 0110011),
+            asdis::instruction32!("czero.nez {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000111, rs2[4:0], rs1[4:0], 111, rd[4:0], 0110011),
+        ];
+
+        ISARVMisc { list, ..Default::default() }
+    }
+
+    ///Zawrs, wait on reservation set
+    pub fn zawrs() -> ISARVMisc {
+        let list = vec! [
+            asdis::instruction32!("wrs.nto", 00000000110100000000000001110011),
+            asdis::instruction32!("wrs.sto", 00000001110100000000000001110011),
+        ];
+
+        ISARVMisc { list, ..Default::default() }
+    }
+
+    ///Zihintpause, carved out of fence with pred=W and succ=0
+    pub fn zihintpause() -> ISARVMisc {
+        let list = vec! [
+            asdis::instruction32!("pause", 00000001000000000000000000001111),
+        ];
+
+        ISARVMisc { list, ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::ISARV32IMA;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn zicbom() {
+        let isa = ISARVMisc::zicbom();
+        assert_eq!(decode(0x0005200f, &isa), "cbo.inval (r10)");
+        assert_eq!(encode("cbo.inval (r10)", &isa), Some( 0x0005200f ));
+    }
+
+    #[test]
+    fn priority() {
+        let mut isa = ISARV32IMA::new();
+        isa.append( ISARVMisc::zicbop() );
+        isa.append( ISARVMisc::zihintpause() );
+        assert_eq!(decode(0x0100000f, &isa), "pause");
+        assert_eq!(decode(0x0ff0000f, &isa), "fence 0xFF");
+        assert_eq!(decode(0x0000100f, &isa), "fence.i");
+        assert_eq!(decode(0x04156013, &isa), "prefetch.r 0x40 (r10)");
+        assert_eq!(decode(0x04156093, &isa), "ori r1, r10, 0x41");
+        assert_eq!(encode("prefetch.w 0x20 (r11)", &isa), Some( 0x0235e013 ));
+        assert_eq!(encode("prefetch.w 0x21 (r11)", &isa), None);
+    }
+
+    #[test]
+    fn cache_and_cond() {
+        let mut isa = ISARVMisc::zicbom();
+        isa.append( ISARVMisc::zicboz() );
+        isa.append( ISARVMisc::zicond() );
+        isa.append( ISARVMisc::zawrs() );
+        assert_eq!(decode(0x0015200f, &isa), "cbo.clean (r10)");
+        assert_eq!(decode(0x0045200f, &isa), "cbo.zero (r10)");
+        assert_eq!(decode(0x0eb55533, &isa), "czero.eqz r10, r10, r11");
+        assert_eq!(decode(0x00d00073, &isa), "wrs.nto");
+    }
+}
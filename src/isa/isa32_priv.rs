@@ -0,0 +1,96 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+
+pub type ISARVPriv = ISA<RV32Type>;
+
+impl ISARVPriv {
+    ///machine and supervisor level instructions, including debug mode
+    pub fn privileged() -> ISARVPriv {
+        let list = vec! [
+            asdis::instruction32!("mret", 00110000001000000000000001110011),
+            asdis::instruction32!("sret", 00010000001000000000000001110011),
+            asdis::instruction32!("mnret", 01110000001000000000000001110011),
+            asdis::instruction32!("dret", 01111011001000000000000001110011),
+            asdis::instruction32!("wfi", 00010000010100000000000001110011),
+
+            asdis::instruction32!("sfence.vma {rs1:xreg}, {rs2:xreg}", 0001001, rs2[4:0], rs1[4:0], 000, 00000, 1110011),
+        ];
+
+        ISARVPriv { list, ..Default::default() }
+    }
+
+    ///Svinval, fine-grained address-translation cache invalidation
+    pub fn svinval() -> ISARVPriv {
+        let list = vec! [
+            asdis::instruction32!("sinval.vma {rs1:xreg}, {rs2:xreg}", 0001011, rs2[4:0], rs1[4:0], 000, 00000, 1110011),
+            asdis::instruction32!("sfence.w.inval", 00011000000000000000000001110011),
+            asdis::instruction32!("sfence.inval.ir", 00011000000100000000000001110011),
+        ];
+
+        ISARVPriv { list, ..Default::default() }
+    }
+
+    ///hypervisor extension
+    pub fn hypervisor(xlen : Xlen) -> ISARVPriv {
+        let mut list = vec! [
+            asdis::instruction32!("hfence.vvma {rs1:xreg}, {rs2:xreg}", 0010001, rs2[4:0], rs1[4:0], 000, 00000, 1110011),
+            asdis::instruction32!("hfence.gvma {rs1:xreg}, {rs2:xreg}", 0110001, rs2[4:0], rs1[4:0], 000, 00000, 1110011),
+            asdis::instruction32!("hinval.vvma {rs1:xreg}, {rs2:xreg}", 0010011, rs2[4:0], rs1[4:0], 000, 00000, 1110011),
+            asdis::instruction32!("hinval.gvma {rs1:xreg}, {rs2:xreg}", 0110011, rs2[4:0], rs1[4:0], 000, 00000, 1110011),
+
+            asdis::instruction32!("hlv.b {rd:xreg}, ({rs1:xreg})", 0110000, 00000, rs1[4:0], 100, rd[4:0], 1110011),
+            asdis::instruction32!("hlv.bu {rd:xreg}, ({rs1:xreg})", 0110000, 00001, rs1[4:0], 100, rd[4:0], 1110011),
+            asdis::instruction32!("hlv.h {rd:xreg}, ({rs1:xreg})", 0110010, 00000, rs1[4:0], 100, rd[4:0], 1110011),
+            asdis::instruction32!("hlv.hu {rd:xreg}, ({rs1:xreg})", 0110010, 00001, rs1[4:0], 100, rd[4:0], 1110011),
+            asdis::instruction32!("hlvx.hu {rd:xreg}, ({rs1:xreg})", 0110010, 00011, rs1[4:0], 100, rd[4:0], 1110011),
+            asdis::instruction32!("hlv.w {rd:xreg}, ({rs1:xreg})", 0110100, 00000, rs1[4:0], 100, rd[4:0], 1110011),
+            asdis::instruction32!("hlvx.wu {rd:xreg}, ({rs1:xreg})", 0110100, 00011, rs1[4:0], 100, rd[4:0], 1110011),
+
+            asdis::instruction32!("hsv.b {rs2:xreg}, ({rs1:xreg})", 0110001, rs2[4:0], rs1[4:0], 100, 00000, 1110011),
+            asdis::instruction32!("hsv.h {rs2:xreg}, ({rs1:xreg})", 0110011, rs2[4:0], rs1[4:0], 100, 00000, 1110011),
+            asdis::instruction32!("hsv.w {rs2:xreg}, ({rs1:xreg})", 0110101, rs2[4:0], rs1[4:0], 100, 00000, 1110011),
+        ];
+
+        if xlen == Xlen::X64 {
+            list.extend( vec! [
+                asdis::instruction32!("hlv.wu {rd:xreg}, ({rs1:xreg})", 0110100, 00001, rs1[4:0], 100, rd[4:0], 1110011),
+                asdis::instruction32!("hlv.d {rd:xreg}, ({rs1:xreg})", 0110110, 00000, rs1[4:0], 100, rd[4:0], 1110011),
+                asdis::instruction32!("hsv.d {rs2:xreg}, ({rs1:xreg})", 0110111, rs2[4:0], rs1[4:0], 100, 00000, 1110011),
+            ]);
+        }
+
+        ISARVPriv { list, ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn supervisor_return() {
+        let isa = ISARVPriv::privileged();
+        assert_eq!(decode(0x10200073, &isa), "sret");
+        assert_eq!(encode("sret", &isa), Some( 0x10200073 ));
+    }
+
+    #[test]
+    fn trap_return() {
+        let isa = ISARVPriv::privileged();
+        assert_eq!(decode(0x30200073, &isa), "mret");
+        assert_eq!(decode(0x10500073, &isa), "wfi");
+        assert_eq!(decode(0x12b50073, &isa), "sfence.vma r10, r11");
+        assert_eq!(encode("sfence.vma r10, r11", &isa), Some( 0x12b50073 ));
+    }
+
+    #[test]
+    fn hypervisor() {
+        let isa = ISARVPriv::hypervisor(Xlen::X32);
+        assert_eq!(decode(0x6c054573, &isa), "RV32Type. Not found!");
+        let isa = ISARVPriv::hypervisor(Xlen::X64);
+        assert_eq!(decode(0x6c054573, &isa), "hlv.d r10, (r10)");
+    }
+}
@@ -0,0 +1,383 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+use std::collections::HashMap;
+
+pub type ISARVV = ISA<RV32Type>;
+
+const LMUL : [&str; 8] = ["m1", "m2", "m4", "m8", "", "mf8", "mf4", "mf2"];
+
+///show vtype as e32,m2,ta,ma, reserved values are shown as number
+fn show_vtype(v : u32) -> String
+{
+    let vlmul = (v & 7) as usize;
+    let vsew = (v >> 3) & 7;
+    if v >> 8 != 0 || vsew > 3 || LMUL[vlmul].is_empty() {
+        return format!("{:#X}", v);
+    }
+    format!("e{},{},{},{}", 8 << vsew, LMUL[vlmul],
+            if v & 0x40 != 0 { "ta" } else { "tu" },
+            if v & 0x80 != 0 { "ma" } else { "mu" })
+}
+
+fn parse_vtype(s : &str) -> Option<u32>
+{
+    if let Some( h ) = s.strip_prefix("0x") {
+        return u32::from_str_radix(h, 16).ok();
+    }
+    let mut v = 0;
+    for (n, p) in s.split(',').map(str::trim).enumerate() {
+        match (n, p) {
+            (0, e) => {
+                let sew = e.strip_prefix('e')?.parse::<u32>().ok()?;
+                if !sew.is_power_of_two() || !(8..=64).contains(&sew) {
+                    return None;
+                }
+                v |= (sew.trailing_zeros() - 3) << 3;
+            },
+            (1, m) => v |= LMUL.iter().position(|l| !l.is_empty() && *l == m)? as u32,
+            (2, "ta") => v |= 0x40,
+            (3, "ma") => v |= 0x80,
+            (2, "tu") | (3, "mu") => (),
+            _ => return None,
+        }
+    }
+    Some( v )
+}
+
+///helper to build fixed bits item
+fn bits(len : usize, val : u32) -> Item<RV32Type> {
+    Item::Bits { len, val }
+}
+
+///helper to build ident item from continuous bit range
+fn field(name : &str, hi : u32, lo : u32) -> Item<RV32Type> {
+    Item::Ident { name : String::from(name), bitspec : (lo..=hi).rev().collect() }
+}
+
+///helper to build instruction from text and item list
+fn instruction(text : &str, list : Vec<Item<RV32Type>>) -> Instruction<RV32Type> {
+    debug_assert_eq!(list.iter().map(|i| match i {
+        Item::Bits { len, .. } => *len,
+        Item::Ident { bitspec, .. } => bitspec.len(),
+    }).sum::<usize>(), 32, "{}", text);
+    Instruction::new( BinaryInstruction { list }, TextInstruction::from(text) )
+}
+
+///element widths of vector memory operations and their width field encoding
+const EEW : [(u32, u32); 4] = [(8, 0b000), (16, 0b101), (32, 0b110), (64, 0b111)];
+
+///unit-stride, strided and indexed loads and stores, with segment forms
+fn memory() -> Vec<Instruction<RV32Type>> {
+    let mut list = Vec::new();
+
+    for nf in 1..=8 {
+        let seg = if nf == 1 { String::new() } else { format!("seg{}", nf) };
+        for (eew, width) in EEW.iter() {
+            let mem = |mop, umop : Option<u32>, opcode, text : String| {
+                let mid = match umop {
+                    Some( v ) => bits(5, v),
+                    None if mop & 1 == 1 => field("vs2", 4, 0),
+                    None => field("rs2", 4, 0),
+                };
+                instruction(&text, vec![bits(3, nf - 1), bits(1, 0), bits(2, mop), field("vm", 0, 0), mid,
+                                        field("rs1", 4, 0), bits(3, *width), field(if opcode == 0b0000111 { "vd" } else { "vs3" }, 4, 0),
+                                        bits(7, opcode)])
+            };
+            list.push( mem(0b00, Some( 0b00000 ), 0b0000111, format!("vl{}e{}.v {{vd:vreg}}, ({{rs1:xreg}}), {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b00, Some( 0b10000 ), 0b0000111, format!("vl{}e{}ff.v {{vd:vreg}}, ({{rs1:xreg}}), {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b00, Some( 0b00000 ), 0b0100111, format!("vs{}e{}.v {{vs3:vreg}}, ({{rs1:xreg}}), {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b10, None, 0b0000111, format!("vls{}e{}.v {{vd:vreg}}, ({{rs1:xreg}}), {{rs2:xreg}}, {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b10, None, 0b0100111, format!("vss{}e{}.v {{vs3:vreg}}, ({{rs1:xreg}}), {{rs2:xreg}}, {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b01, None, 0b0000111, format!("vlux{}ei{}.v {{vd:vreg}}, ({{rs1:xreg}}), {{vs2:vreg}}, {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b11, None, 0b0000111, format!("vlox{}ei{}.v {{vd:vreg}}, ({{rs1:xreg}}), {{vs2:vreg}}, {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b01, None, 0b0100111, format!("vsux{}ei{}.v {{vs3:vreg}}, ({{rs1:xreg}}), {{vs2:vreg}}, {{vm:vm}}", seg, eew)) );
+            list.push( mem(0b11, None, 0b0100111, format!("vsox{}ei{}.v {{vs3:vreg}}, ({{rs1:xreg}}), {{vs2:vreg}}, {{vm:vm}}", seg, eew)) );
+        }
+    }
+
+    //mask and whole register loads and stores
+    list.push( instruction("vlm.v {vd:vreg}, ({rs1:xreg})", vec![bits(12, 0b000000101011), field("rs1", 4, 0), bits(3, 0), field("vd", 4, 0), bits(7, 0b0000111)]) );
+    list.push( instruction("vsm.v {vs3:vreg}, ({rs1:xreg})", vec![bits(12, 0b000000101011), field("rs1", 4, 0), bits(3, 0), field("vs3", 4, 0), bits(7, 0b0100111)]) );
+    for nr in [1, 2, 4, 8] {
+        for (eew, width) in EEW.iter() {
+            list.push( instruction(&format!("vl{}re{}.v {{vd:vreg}}, ({{rs1:xreg}})", nr, eew),
+                                   vec![bits(3, nr - 1), bits(9, 0b000101000), field("rs1", 4, 0), bits(3, *width), field("vd", 4, 0), bits(7, 0b0000111)]) );
+        }
+        list.push( instruction(&format!("vs{}r.v {{vs3:vreg}}, ({{rs1:xreg}})", nr),
+                               vec![bits(3, nr - 1), bits(9, 0b000101000), field("rs1", 4, 0), bits(3, 0), field("vs3", 4, 0), bits(7, 0b0100111)]) );
+    }
+
+    list
+}
+
+///arithmetic instruction categories, they define funct3 of every operand form
+#[derive(Clone, Copy, PartialEq)]
+enum Category {
+    OPI,
+    OPM,
+    OPF,
+}
+
+///order of operands in text, multiply-add instructions have the source which is multiplied before vs2
+#[derive(Clone, Copy, PartialEq)]
+enum Order {
+    ///vd, vs2, vs1/rs1/imm
+    Vs2First,
+    ///vd, vs1/rs1, vs2
+    Vs1First,
+}
+
+///build arithmetic instruction of given category for given suffix (vv, vx, vi, vf, wv, vs ...)
+fn arith(cat : Category, order : Order, name : &str, funct6 : u32, suffix : &str, uimm : bool) -> Instruction<RV32Type> {
+    let (funct3, src, src_name) = match (cat, suffix.chars().last()) {
+        (Category::OPI, Some( 'x' )) => (0b100, "rs1", "{rs1:xreg}"),
+        (Category::OPI, Some( 'i' )) if uimm => (0b011, "uimm", "{uimm:uimm5}"),
+        (Category::OPI, Some( 'i' )) => (0b011, "simm5", "{simm5:simm5}"),
+        (Category::OPI, _) => (0b000, "vs1", "{vs1:vreg}"),
+        (Category::OPM, Some( 'x' )) => (0b110, "rs1", "{rs1:xreg}"),
+        (Category::OPM, _) => (0b010, "vs1", "{vs1:vreg}"),
+        (Category::OPF, Some( 'f' )) => (0b101, "frs1", "{frs1:freg}"),
+        (Category::OPF, _) => (0b001, "vs1", "{vs1:vreg}"),
+    };
+    let text = match order {
+        Order::Vs1First => format!("{}.{} {{vd:vreg}}, {}, {{vs2:vreg}}, {{vm:vm}}", name, suffix, src_name),
+        Order::Vs2First => format!("{}.{} {{vd:vreg}}, {{vs2:vreg}}, {}, {{vm:vm}}", name, suffix, src_name),
+    };
+    instruction(&text, vec![bits(6, funct6), field("vm", 0, 0), field("vs2", 4, 0), field(src, 4, 0), bits(3, funct3), field("vd", 4, 0), bits(7, 0b1010111)])
+}
+
+///integer arithmetic, funct6 and operand forms
+const OPI : [(&str, u32, &str); 38] = [
+    ("vadd", 0b000000, "vv vx vi"), ("vsub", 0b000010, "vv vx"), ("vrsub", 0b000011, "vx vi"),
+    ("vminu", 0b000100, "vv vx"), ("vmin", 0b000101, "vv vx"), ("vmaxu", 0b000110, "vv vx"), ("vmax", 0b000111, "vv vx"),
+    ("vand", 0b001001, "vv vx vi"), ("vor", 0b001010, "vv vx vi"), ("vxor", 0b001011, "vv vx vi"),
+    ("vrgather", 0b001100, "vv vx vi"), ("vslideup", 0b001110, "vx vi"), ("vrgatherei16", 0b001110, "vv"), ("vslidedown", 0b001111, "vx vi"),
+    ("vmseq", 0b011000, "vv vx vi"), ("vmsne", 0b011001, "vv vx vi"), ("vmsltu", 0b011010, "vv vx"), ("vmslt", 0b011011, "vv vx"),
+    ("vmsleu", 0b011100, "vv vx vi"), ("vmsle", 0b011101, "vv vx vi"), ("vmsgtu", 0b011110, "vx vi"), ("vmsgt", 0b011111, "vx vi"),
+    ("vsaddu", 0b100000, "vv vx vi"), ("vsadd", 0b100001, "vv vx vi"), ("vssubu", 0b100010, "vv vx"), ("vssub", 0b100011, "vv vx"),
+    ("vsll", 0b100101, "vv vx vi"), ("vsmul", 0b100111, "vv vx"), ("vsrl", 0b101000, "vv vx vi"), ("vsra", 0b101001, "vv vx vi"),
+    ("vssrl", 0b101010, "vv vx vi"), ("vssra", 0b101011, "vv vx vi"),
+    ("vnsrl", 0b101100, "wv wx wi"), ("vnsra", 0b101101, "wv wx wi"), ("vnclipu", 0b101110, "wv wx wi"), ("vnclip", 0b101111, "wv wx wi"),
+    ("vwredsumu", 0b110000, "vs"), ("vwredsum", 0b110001, "vs"),
+];
+
+///OPI instructions with unsigned immediate
+const OPI_UIMM : [&str; 12] = ["vrgather", "vslideup", "vslidedown", "vsll", "vsrl", "vsra", "vssrl", "vssra", "vnsrl", "vnsra", "vnclipu", "vnclip"];
+
+///integer multiply, divide, reductions and widening arithmetic
+const OPM : [(&str, u32, &str); 33] = [
+    ("vredsum", 0b000000, "vs"), ("vredand", 0b000001, "vs"), ("vredor", 0b000010, "vs"), ("vredxor", 0b000011, "vs"),
+    ("vredminu", 0b000100, "vs"), ("vredmin", 0b000101, "vs"), ("vredmaxu", 0b000110, "vs"), ("vredmax", 0b000111, "vs"),
+    ("vaaddu", 0b001000, "vv vx"), ("vaadd", 0b001001, "vv vx"), ("vasubu", 0b001010, "vv vx"), ("vasub", 0b001011, "vv vx"),
+    ("vslide1up", 0b001110, "vx"), ("vslide1down", 0b001111, "vx"),
+    ("vdivu", 0b100000, "vv vx"), ("vdiv", 0b100001, "vv vx"), ("vremu", 0b100010, "vv vx"), ("vrem", 0b100011, "vv vx"),
+    ("vmulhu", 0b100100, "vv vx"), ("vmul", 0b100101, "vv vx"), ("vmulhsu", 0b100110, "vv vx"), ("vmulh", 0b100111, "vv vx"),
+    ("vwaddu", 0b110000, "vv vx"), ("vwadd", 0b110001, "vv vx"), ("vwsubu", 0b110010, "vv vx"), ("vwsub", 0b110011, "vv vx"),
+    ("vwaddu", 0b110100, "wv wx"), ("vwadd", 0b110101, "wv wx"), ("vwsubu", 0b110110, "wv wx"), ("vwsub", 0b110111, "wv wx"),
+    ("vwmulu", 0b111000, "vv vx"), ("vwmulsu", 0b111010, "vv vx"), ("vwmul", 0b111011, "vv vx"),
+];
+
+///floating point arithmetic
+const OPF : [(&str, u32, &str); 30] = [
+    ("vfadd", 0b000000, "vv vf"), ("vfredusum", 0b000001, "vs"), ("vfsub", 0b000010, "vv vf"), ("vfredosum", 0b000011, "vs"),
+    ("vfmin", 0b000100, "vv vf"), ("vfredmin", 0b000101, "vs"), ("vfmax", 0b000110, "vv vf"), ("vfredmax", 0b000111, "vs"),
+    ("vfsgnj", 0b001000, "vv vf"), ("vfsgnjn", 0b001001, "vv vf"), ("vfsgnjx", 0b001010, "vv vf"),
+    ("vfslide1up", 0b001110, "vf"), ("vfslide1down", 0b001111, "vf"),
+    ("vmfeq", 0b011000, "vv vf"), ("vmfle", 0b011001, "vv vf"), ("vmflt", 0b011011, "vv vf"), ("vmfne", 0b011100, "vv vf"),
+    ("vmfgt", 0b011101, "vf"), ("vmfge", 0b011111, "vf"),
+    ("vfdiv", 0b100000, "vv vf"), ("vfrdiv", 0b100001, "vf"), ("vfmul", 0b100100, "vv vf"), ("vfrsub", 0b100111, "vf"),
+    ("vfwadd", 0b110000, "vv vf"), ("vfwredusum", 0b110001, "vs"), ("vfwsub", 0b110010, "vv vf"), ("vfwredosum", 0b110011, "vs"),
+    ("vfwadd", 0b110100, "wv wf"), ("vfwsub", 0b110110, "wv wf"),
+    ("vfwmul", 0b111000, "vv vf"),
+];
+
+///integer multiply-add, operands are vd, vs1/rs1, vs2
+const OPM_MULADD : [(&str, u32, &str); 8] = [
+    ("vmadd", 0b101001, "vv vx"), ("vnmsub", 0b101011, "vv vx"), ("vmacc", 0b101101, "vv vx"), ("vnmsac", 0b101111, "vv vx"),
+    ("vwmaccu", 0b111100, "vv vx"), ("vwmacc", 0b111101, "vv vx"), ("vwmaccus", 0b111110, "vx"), ("vwmaccsu", 0b111111, "vv vx"),
+];
+
+///floating point multiply-add, operands are vd, vs1/rs1, vs2
+const OPF_MULADD : [(&str, u32, &str); 12] = [
+    ("vfmadd", 0b101000, "vv vf"), ("vfnmadd", 0b101001, "vv vf"), ("vfmsub", 0b101010, "vv vf"), ("vfnmsub", 0b101011, "vv vf"),
+    ("vfmacc", 0b101100, "vv vf"), ("vfnmacc", 0b101101, "vv vf"), ("vfmsac", 0b101110, "vv vf"), ("vfnmsac", 0b101111, "vv vf"),
+    ("vfwmacc", 0b111100, "vv vf"), ("vfwnmacc", 0b111101, "vv vf"), ("vfwmsac", 0b111110, "vv vf"), ("vfwnmsac", 0b111111, "vv vf"),
+];
+
+///floating point unary operations of VFUNARY0 and VFUNARY1 groups, vs1 field selects operation
+const OPF_UNARY : [(&str, u32, u32); 25] = [
+    ("vfcvt.xu.f.v", 0b010010, 0b00000), ("vfcvt.x.f.v", 0b010010, 0b00001), ("vfcvt.f.xu.v", 0b010010, 0b00010), ("vfcvt.f.x.v", 0b010010, 0b00011),
+    ("vfcvt.rtz.xu.f.v", 0b010010, 0b00110), ("vfcvt.rtz.x.f.v", 0b010010, 0b00111),
+    ("vfwcvt.xu.f.v", 0b010010, 0b01000), ("vfwcvt.x.f.v", 0b010010, 0b01001), ("vfwcvt.f.xu.v", 0b010010, 0b01010), ("vfwcvt.f.x.v", 0b010010, 0b01011),
+    ("vfwcvt.f.f.v", 0b010010, 0b01100), ("vfwcvt.rtz.xu.f.v", 0b010010, 0b01110), ("vfwcvt.rtz.x.f.v", 0b010010, 0b01111),
+    ("vfncvt.xu.f.w", 0b010010, 0b10000), ("vfncvt.x.f.w", 0b010010, 0b10001), ("vfncvt.f.xu.w", 0b010010, 0b10010), ("vfncvt.f.x.w", 0b010010, 0b10011),
+    ("vfncvt.f.f.w", 0b010010, 0b10100), ("vfncvt.rod.f.f.w", 0b010010, 0b10101), ("vfncvt.rtz.xu.f.w", 0b010010, 0b10110), ("vfncvt.rtz.x.f.w", 0b010010, 0b10111),
+    ("vfsqrt.v", 0b010011, 0b00000), ("vfrsqrt7.v", 0b010011, 0b00100), ("vfrec7.v", 0b010011, 0b00101), ("vfclass.v", 0b010011, 0b10000),
+];
+
+impl ISARVV {
+    ///RVV 1.0 vector extension
+    pub fn v() -> ISARVV {
+
+        let mut list = vec! [
+            // configuration
+            asdis::instruction32!("vsetvli {rd:xreg}, {rs1:xreg}, zimm", 0, zimm[10:0], rs1[4:0], 111, rd[4:0], 1010111),
+            asdis::instruction32!("vsetivli {rd:xreg}, {uimm:uimm5}, zimm", 11, zimm[9:0], uimm[4:0], 111, rd[4:0], 1010111),
+            asdis::instruction32!("vsetvl {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 1000000, rs2[4:0], rs1[4:0], 111, rd[4:0], 1010111),
+
+            // forms with carry, merge and moves
+            asdis::instruction32!("vadc.vvm {vd:vreg}, {vs2:vreg}, {vs1:vreg}, v0", 010000, 0, vs2[4:0], vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vadc.vxm {vd:vreg}, {vs2:vreg}, {rs1:xreg}, v0", 010000, 0, vs2[4:0], rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vadc.vim {vd:vreg}, {vs2:vreg}, {simm5:simm5}, v0", 010000, 0, vs2[4:0], simm5[4:0], 011, vd[4:0], 1010111),
+            asdis::instruction32!("vmadc.vvm {vd:vreg}, {vs2:vreg}, {vs1:vreg}, v0", 010001, 0, vs2[4:0], vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vmadc.vxm {vd:vreg}, {vs2:vreg}, {rs1:xreg}, v0", 010001, 0, vs2[4:0], rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vmadc.vim {vd:vreg}, {vs2:vreg}, {simm5:simm5}, v0", 010001, 0, vs2[4:0], simm5[4:0], 011, vd[4:0], 1010111),
+            asdis::instruction32!("vmadc.vv {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 010001, 1, vs2[4:0], vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vmadc.vx {vd:vreg}, {vs2:vreg}, {rs1:xreg}", 010001, 1, vs2[4:0], rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vmadc.vi {vd:vreg}, {vs2:vreg}, {simm5:simm5}", 010001, 1, vs2[4:0], simm5[4:0], 011, vd[4:0], 1010111),
+            asdis::instruction32!("vsbc.vvm {vd:vreg}, {vs2:vreg}, {vs1:vreg}, v0", 010010, 0, vs2[4:0], vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vsbc.vxm {vd:vreg}, {vs2:vreg}, {rs1:xreg}, v0", 010010, 0, vs2[4:0], rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vmsbc.vvm {vd:vreg}, {vs2:vreg}, {vs1:vreg}, v0", 010011, 0, vs2[4:0], vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vmsbc.vxm {vd:vreg}, {vs2:vreg}, {rs1:xreg}, v0", 010011, 0, vs2[4:0], rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vmsbc.vv {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 010011, 1, vs2[4:0], vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vmsbc.vx {vd:vreg}, {vs2:vreg}, {rs1:xreg}", 010011, 1, vs2[4:0], rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vmerge.vvm {vd:vreg}, {vs2:vreg}, {vs1:vreg}, v0", 010111, 0, vs2[4:0], vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vmerge.vxm {vd:vreg}, {vs2:vreg}, {rs1:xreg}, v0", 010111, 0, vs2[4:0], rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vmerge.vim {vd:vreg}, {vs2:vreg}, {simm5:simm5}, v0", 010111, 0, vs2[4:0], simm5[4:0], 011, vd[4:0], 1010111),
+            asdis::instruction32!("vmv.v.v {vd:vreg}, {vs1:vreg}", 010111, 1, 00000, vs1[4:0], 000, vd[4:0], 1010111),
+            asdis::instruction32!("vmv.v.x {vd:vreg}, {rs1:xreg}", 010111, 1, 00000, rs1[4:0], 100, vd[4:0], 1010111),
+            asdis::instruction32!("vmv.v.i {vd:vreg}, {simm5:simm5}", 010111, 1, 00000, simm5[4:0], 011, vd[4:0], 1010111),
+            asdis::instruction32!("vfmerge.vfm {vd:vreg}, {vs2:vreg}, {frs1:freg}, v0", 010111, 0, vs2[4:0], frs1[4:0], 101, vd[4:0], 1010111),
+            asdis::instruction32!("vfmv.v.f {vd:vreg}, {frs1:freg}", 010111, 1, 00000, frs1[4:0], 101, vd[4:0], 1010111),
+            asdis::instruction32!("vmv1r.v {vd:vreg}, {vs2:vreg}", 100111, 1, vs2[4:0], 00000, 011, vd[4:0], 1010111),
+            asdis::instruction32!("vmv2r.v {vd:vreg}, {vs2:vreg}", 100111, 1, vs2[4:0], 00001, 011, vd[4:0], 1010111),
+            asdis::instruction32!("vmv4r.v {vd:vreg}, {vs2:vreg}", 100111, 1, vs2[4:0], 00011, 011, vd[4:0], 1010111),
+            asdis::instruction32!("vmv8r.v {vd:vreg}, {vs2:vreg}", 100111, 1, vs2[4:0], 00111, 011, vd[4:0], 1010111),
+
+            // scalar moves and unary operations
+            asdis::instruction32!("vmv.x.s {rd:xreg}, {vs2:vreg}", 010000, 1, vs2[4:0], 00000, 010, rd[4:0], 1010111),
+            asdis::instruction32!("vmv.s.x {vd:vreg}, {rs1:xreg}", 010000, 1, 00000, rs1[4:0], 110, vd[4:0], 1010111),
+            asdis::instruction32!("vfmv.f.s {frd:freg}, {vs2:vreg}", 010000, 1, vs2[4:0], 00000, 001, frd[4:0], 1010111),
+            asdis::instruction32!("vfmv.s.f {vd:vreg}, {frs1:freg}", 010000, 1, 00000, frs1[4:0], 101, vd[4:0], 1010111),
+            asdis::instruction32!("vcpop.m {rd:xreg}, {vs2:vreg}, {vm:vm}", 010000, vm[0], vs2[4:0], 10000, 010, rd[4:0], 1010111),
+            asdis::instruction32!("vfirst.m {rd:xreg}, {vs2:vreg}, {vm:vm}", 010000, vm[0], vs2[4:0], 10001, 010, rd[4:0], 1010111),
+            asdis::instruction32!("vzext.vf8 {vd:vreg}, {vs2:vreg}, {vm:vm}", 010010, vm[0], vs2[4:0], 00010, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vsext.vf8 {vd:vreg}, {vs2:vreg}, {vm:vm}", 010010, vm[0], vs2[4:0], 00011, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vzext.vf4 {vd:vreg}, {vs2:vreg}, {vm:vm}", 010010, vm[0], vs2[4:0], 00100, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vsext.vf4 {vd:vreg}, {vs2:vreg}, {vm:vm}", 010010, vm[0], vs2[4:0], 00101, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vzext.vf2 {vd:vreg}, {vs2:vreg}, {vm:vm}", 010010, vm[0], vs2[4:0], 00110, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vsext.vf2 {vd:vreg}, {vs2:vreg}, {vm:vm}", 010010, vm[0], vs2[4:0], 00111, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmsbf.m {vd:vreg}, {vs2:vreg}, {vm:vm}", 010100, vm[0], vs2[4:0], 00001, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmsof.m {vd:vreg}, {vs2:vreg}, {vm:vm}", 010100, vm[0], vs2[4:0], 00010, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmsif.m {vd:vreg}, {vs2:vreg}, {vm:vm}", 010100, vm[0], vs2[4:0], 00011, 010, vd[4:0], 1010111),
+            asdis::instruction32!("viota.m {vd:vreg}, {vs2:vreg}, {vm:vm}", 010100, vm[0], vs2[4:0], 10000, 010, vd[4:0], 1010111),
+            asdis::instruction32!("vid.v {vd:vreg}, {vm:vm}", 010100, vm[0], 00000, 10001, 010, vd[4:0], 1010111),
+
+            // mask logical
+            asdis::instruction32!("vcompress.vm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 010111, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmandn.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011000, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmand.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011001, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmor.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011010, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmxor.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011011, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmorn.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011100, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmnand.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011101, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmnor.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011110, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+            asdis::instruction32!("vmxnor.mm {vd:vreg}, {vs2:vreg}, {vs1:vreg}", 011111, 1, vs2[4:0], vs1[4:0], 010, vd[4:0], 1010111),
+        ];
+
+        for (cat, order, table) in [(Category::OPI, Order::Vs2First, &OPI[..]), (Category::OPM, Order::Vs2First, &OPM[..]),
+                                    (Category::OPF, Order::Vs2First, &OPF[..]), (Category::OPM, Order::Vs1First, &OPM_MULADD[..]),
+                                    (Category::OPF, Order::Vs1First, &OPF_MULADD[..])] {
+            for (name, funct6, forms) in table {
+                let uimm = cat == Category::OPI && OPI_UIMM.contains(name);
+                for suffix in forms.split_whitespace() {
+                    list.push( arith(cat, order, name, *funct6, suffix, uimm) );
+                }
+            }
+        }
+
+        for (name, funct6, vs1) in OPF_UNARY {
+            list.push( instruction(&format!("{} {{vd:vreg}}, {{vs2:vreg}}, {{vm:vm}}", name),
+                                   vec![bits(6, funct6), field("vm", 0, 0), field("vs2", 4, 0), bits(5, vs1), bits(3, 0b001), field("vd", 4, 0), bits(7, 0b1010111)]) );
+        }
+
+        list.extend( memory() );
+
+        let mut show_dict = HashMap::new();
+        let mut parse_dict = HashMap::new();
+        show_dict.insert(String::from("zimm"), show_vtype as ShowFun::<RV32Type>);
+        parse_dict.insert(String::from("zimm"), parse_vtype as ParseFun::<RV32Type>);
+
+        ISARVV { list, show_dict, parse_dict, ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn unit_stride_store() {
+        let isa = ISARVV::v();
+        assert_eq!(decode(0x020560a7, &isa), "vse32.v v1, (r10)");
+        assert_eq!(encode("vse32.v v1, (r10), v0.t", &isa), Some( 0x000560a7 ));
+    }
+
+    #[test]
+    fn vtype() {
+        assert_eq!(show_vtype(0xD1), "e32,m2,ta,ma");
+        assert_eq!(show_vtype(0x07), "e8,mf2,tu,mu");
+        assert_eq!(show_vtype(0x04), "0x4");
+        assert_eq!(parse_vtype("e32, m2, ta, ma"), Some( 0xD1 ));
+        assert_eq!(parse_vtype("e8,mf2,tu,mu"), Some( 0x07 ));
+    }
+
+    #[test]
+    fn decode_v() {
+        let isa = ISARVV::v();
+        assert_eq!(decode(0x0d107557, &isa), "vsetvli r10, r0, e32,m2,ta,ma");
+        assert_eq!(decode(0x022180d7, &isa), "vadd.vv v1, v2, v3");
+        assert_eq!(decode(0x002180d7, &isa), "vadd.vv v1, v2, v3, v0.t");
+        assert_eq!(decode(0x022fb0d7, &isa), "vadd.vi v1, v2, -0x1");
+        assert_eq!(decode(0x02056087, &isa), "vle32.v v1, (r10)");
+        assert_eq!(decode(0xe0056087, &isa), "vlseg8e32.v v1, (r10), v0.t");
+        assert_eq!(decode(0x0cb56087, &isa), "vloxei32.v v1, (r10), v11, v0.t");
+        assert_eq!(decode(0xb420a157, &isa), "vmacc.vv v2, v1, v2, v0.t");
+    }
+
+    #[test]
+    fn encode_v() {
+        let isa = ISARVV::v();
+        assert_eq!(encode("vsetvli r10, r0, e32, m2, ta, ma", &isa), Some( 0x0d107557 ));
+        assert_eq!(encode("vadd.vv v1, v2, v3, v0.t", &isa), Some( 0x002180d7 ));
+        assert_eq!(encode("vadd.vv v1, v2, v3", &isa), Some( 0x022180d7 ));
+        assert_eq!(encode("vadd.vi v1, v2, -1", &isa), Some( 0x022fb0d7 ));
+        assert_eq!(encode("vlseg8e32.v v1, (r10), v0.t", &isa), Some( 0xe0056087 ));
+        assert_eq!(encode("vadd.vi v1, v2, 16", &isa), None);
+        assert_eq!(encode("vsll.vi v1, v2, 31", &isa), Some( 0x962fb0d7 ));
+        assert_eq!(encode("vsll.vi v1, v2, 32", &isa), None);
+        assert_eq!(encode("vadd.vv v1, v2, v3, v1.t", &isa), None);
+        assert_eq!(decode(0xa62560d7, &isa), "vmadd.vx v1, r10, v2");
+    }
+
+    #[test]
+    fn float_unary_and_widening() {
+        let isa = ISARVV::v();
+        assert_eq!(decode(0x4a2010d7, &isa), "vfcvt.xu.f.v v1, v2");
+        assert_eq!(decode(0x482390d7, &isa), "vfcvt.rtz.x.f.v v1, v2, v0.t");
+        assert_eq!(decode(0x4a261257, &isa), "vfwcvt.f.f.v v4, v2");
+        assert_eq!(decode(0x4a2a90d7, &isa), "vfncvt.rod.f.f.w v1, v2");
+        assert_eq!(decode(0x4e2010d7, &isa), "vfsqrt.v v1, v2");
+        assert_eq!(decode(0x4e2290d7, &isa), "vfrec7.v v1, v2");
+        assert_eq!(decode(0x4e2810d7, &isa), "vfclass.v v1, v2");
+        assert_eq!(decode(0xfa209257, &isa), "vfwmsac.vv v4, v1, v2");
+        assert_eq!(decode(0xc6208257, &isa), "vwredsum.vs v4, v2, v1");
+        assert_eq!(encode("vfrsqrt7.v v1, v2", &isa), Some( 0x4e2210d7 ));
+        assert_eq!(encode("vfncvt.rtz.x.f.w v1, v2", &isa), Some( 0x4a2b90d7 ));
+        assert_eq!(encode("vfwnmsac.vf v4, f10, v2", &isa), Some( 0xfe255257 ));
+        assert_eq!(encode("vwredsumu.vs v4, v2, v1", &isa), Some( 0xc2208257 ));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+use std::collections::HashMap;
+
+pub type ISARVXThead = ISA<RV32Type>;
+
+///shift amount of th.addsl and bit positions of th.ext are shown as decimal
+fn show_decimal(v : u32) -> String
+{
+    format!("{}", v)
+}
+
+impl ISARVXThead {
+    ///T-Head vendor extensions XTheadBa, XTheadBb, XTheadCondMov and XTheadMac in custom-0 opcode space
+    pub fn xthead(xlen : Xlen) -> ISARVXThead {
+        let mut list = vec! [
+            //XTheadBa
+            asdis::instruction32!("th.addsl {rd:xreg}, {rs1:xreg}, {rs2:xreg}, sh", 00000, sh[1:0], rs2[4:0], rs1[4:0], 001, rd[4:0], 0001011),
+
+            //XTheadBb
+            asdis::instruction32!("th.ext {rd:xreg}, {rs1:xreg}, msb, lsb", msb[5:0], lsb[5:0], rs1[4:0], 010, rd[4:0], 0001011),
+            asdis::instruction32!("th.extu {rd:xreg}, {rs1:xreg}, msb, lsb", msb[5:0], lsb[5:0], rs1[4:0], 011, rd[4:0], 0001011),
+            asdis::instruction32!("th.ff0 {rd:xreg}, {rs1:xreg}", 100001000000, rs1[4:0], 001, rd[4:0], 0001011),
+            asdis::instruction32!("th.ff1 {rd:xreg}, {rs1:xreg}", 100001100000, rs1[4:0], 001, rd[4:0], 0001011),
+            asdis::instruction32!("th.rev {rd:xreg}, {rs1:xreg}", 100000100000, rs1[4:0], 001, rd[4:0], 0001011),
+            asdis::instruction32!("th.tstnbz {rd:xreg}, {rs1:xreg}", 100000000000, rs1[4:0], 001, rd[4:0], 0001011),
+
+            //XTheadCondMov
+            asdis::instruction32!("th.mveqz {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100000, rs2[4:0], rs1[4:0], 001, rd[4:0], 0001011),
+            asdis::instruction32!("th.mvnez {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0100001, rs2[4:0], rs1[4:0], 001, rd[4:0], 0001011),
+
+            //XTheadMac
+            asdis::instruction32!("th.mula {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010000, rs2[4:0], rs1[4:0], 001, rd[4:0], 0001011),
+            asdis::instruction32!("th.muls {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010001, rs2[4:0], rs1[4:0], 001, rd[4:0], 0001011),
+            asdis::instruction32!("th.mulah {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010100, rs2[4:0], rs1[4:0], 001, rd[4:0], 0001011),
+            asdis::instruction32!("th.mulsh {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0010101, rs2[4:0], rs1[4:0], 001, rd[4:0], 0001011),
+        ];
+
+        //shift amount and bit number are 6 bits, values above 31 are reserved for RV32
+        match xlen {
+            Xlen::X32 => list.extend( vec! [
+                asdis::instruction32!("th.srri {rd:xreg}, {rs1:xreg}, sh", 000100, sh[5:0], rs1[4:0], 001, rd[4:0], 0001011, reserved if sh >= 32),
+                asdis::instruction32!("th.tst {rd:xreg}, {rs1:xreg}, sh", 100010, sh[5:0], rs1[4:0], 001, rd[4:0], 0001011, reserved if sh >= 32),
+            ]),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction32!("th.srri {rd:xreg}, {rs1:xreg}, sh", 000100, sh[5:0], rs1[4:0], 001, rd[4:0], 0001011),
+                asdis::instruction32!("th.tst {rd:xreg}, {rs1:xreg}, sh", 100010, sh[5:0], rs1[4:0], 001, rd[4:0], 0001011),
+            ]),
+        };
+
+        let mut show_dict = HashMap::new();
+        for f in ["sh", "msb", "lsb"] {
+            show_dict.insert(String::from(f), show_decimal as ShowFun::<RV32Type>);
+        }
+
+        ISARVXThead { list, show_dict, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::{IsaSet, ISARV32IMA};
+    use crate::decoder::decode_set;
+    use crate::encoder::encode_set;
+
+    #[test]
+    fn bit_field() {
+        let isa = ISARVXThead::xthead(Xlen::X64);
+        assert_eq!(crate::decoder::decode(0x3c85a50b, &isa), "th.ext r10, r11, 15, 8");
+        assert_eq!(crate::encoder::encode("th.ff1 r10, r11", &isa), Some( 0x8605950b ));
+    }
+
+    #[test]
+    fn fragment() {
+        let mut set = IsaSet::<RV32Type>::default();
+        set.add("standard", ISARV32IMA::new()).unwrap();
+        set.add("xthead", ISARVXThead::xthead(Xlen::X32)).unwrap();
+        assert_eq!(decode_set(0x06b5150b, &set), "th.addsl r10, r10, r11, 3");
+        assert_eq!(decode_set(0x00b50533, &set), "add r10, r10, r11");
+        assert_eq!(encode_set("th.mveqz r10, r11, r12", &set), Some( 0x40c5950b ));
+        assert_eq!(encode_set("add r10, r10, r11", &set), Some( 0x00b50533 ));
+    }
+
+    #[test]
+    fn shift_amount() {
+        let isa = ISARVXThead::xthead(Xlen::X64);
+        assert_eq!(crate::decoder::decode(0x1215950b, &isa), "th.srri r10, r11, 33");
+        assert_eq!(crate::decoder::decode(0x8a15950b, &isa), "th.tst r10, r11, 33");
+        assert_eq!(crate::encoder::encode("th.srri r10, r11, 63", &isa), Some( 0x13f5950b ));
+        let isa = ISARVXThead::xthead(Xlen::X32);
+        assert_eq!(crate::decoder::decode(0x1015950b, &isa), "th.srri r10, r11, 1");
+        assert_eq!(crate::decoder::decode(0x1215950b, &isa), "th.srri r10, r11, 33 # reserved");
+    }
+}
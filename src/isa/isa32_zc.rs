@@ -0,0 +1,247 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+use std::collections::HashMap;
+
+pub type ISARVZc = ISA<CompactType>;
+
+///saved register of cm.mvsa01 and cm.mva01s, 3 bits encode s0-s7 (x8, x9, x18-x23)
+fn show_sregister(v : u32) -> String
+{
+    match v {
+        0 | 1 => show_register(v + 8),
+        _ => show_register(v + 16),
+    }
+}
+
+///parse saved register of cm.mvsa01 and cm.mva01s
+fn parse_sregister(s : &str) -> Option<u32>
+{
+    match parse_register(s)? {
+        v @ 8..=9 => Some( v - 8 ),
+        v @ 18..=23 => Some( v - 16 ),
+        _ => None,
+    }
+}
+
+///register list of push/pop instructions, like {ra, s0-s2}. Values 0-3 are reserved
+fn show_rlist(v : u32) -> String
+{
+    match v {
+        4 => String::from("{ra}"),
+        5 => String::from("{ra, s0}"),
+        6..=14 => format!("{{ra, s0-s{}}}", v - 5),
+        15 => String::from("{ra, s0-s11}"),
+        _ => format!("{:#X}", v),
+    }
+}
+
+///parse register list, whitespaces inside of braces are not significant
+fn parse_rlist(s : &str) -> Option<u32>
+{
+    let s : String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let list = s.strip_prefix('{')?.strip_suffix('}')?;
+    match list {
+        "ra" => Some( 4 ),
+        "ra,s0" => Some( 5 ),
+        "ra,s0-s11" => Some( 15 ),
+        _ => match list.strip_prefix("ra,s0-s")?.parse::<u32>() {
+            Ok( n ) if (1..=9).contains(&n) => Some( n + 5 ),
+            _ => None,
+        },
+    }
+}
+
+///minimal stack adjustment which is enough to store registers of rlist
+fn stack_adj_base(rlist : u32, xlen : Xlen) -> u32
+{
+    match (xlen, rlist) {
+        (Xlen::X32, 4..=7) => 16,
+        (Xlen::X32, 8..=11) => 32,
+        (Xlen::X32, 12..=14) => 48,
+        (Xlen::X32, _) => 64,
+        (Xlen::X64, _) => {
+            //each register takes 8 bytes, stack is aligned to 16 bytes
+            let regs = match rlist { 15 => 13, _ => rlist.saturating_sub(3) };
+            (regs * 8).div_ceil(16) * 16
+        },
+    }
+}
+
+///stack adjustment of cm.push and cm.pop in bytes, spimm is the value of its field
+pub fn stack_adj(rlist : u32, spimm : u32, xlen : Xlen) -> u32
+{
+    stack_adj_base(rlist, xlen) + spimm
+}
+
+///registers of rlist in order they are stored below old sp: the highest s register first, ra last,
+///none for reserved values
+pub fn rlist_registers(rlist : u32) -> Vec<u8>
+{
+    let saved = match rlist {
+        0..=3 => return Vec::new(),
+        15 => 12,
+        v => v - 4,
+    };
+    let mut list = vec![1];
+    list.extend( [8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27].iter().take(saved as usize) );
+    list.reverse();
+    list
+}
+
+///stack adjustment is shown in bytes, that is base adjustment for rlist plus spimm
+fn show_spimm(v : u32, fields : &Fields<CompactType>, xlen : Xlen) -> String
+{
+    let rlist = fields.iter().find(|(n, _, _)| n == "rlist").map_or(0, |(_, v, _)| *v);
+    format!("{}", stack_adj_base(rlist, xlen) + v)
+}
+
+///parse stack adjustment in bytes, rlist operand should be already parsed
+fn parse_spimm(s : &str, values : &[(&str, u32)], xlen : Xlen) -> Option<u32>
+{
+    let rlist = values.iter().find(|(n, _)| *n == "rlist")?.1;
+    let adj = match s.strip_prefix("0x") {
+        Some( h ) => u32::from_str_radix(h, 16).ok()?,
+        None => s.parse::<u32>().ok()?,
+    };
+    let v = adj.checked_sub(stack_adj_base(rlist, xlen))?;
+    if v <= 48 && v % 16 == 0 { Some( v ) } else { None }
+}
+
+fn show_spimm32(v : u32, fields : &Fields<CompactType>) -> String { show_spimm(v, fields, Xlen::X32) }
+fn show_spimm64(v : u32, fields : &Fields<CompactType>) -> String { show_spimm(v, fields, Xlen::X64) }
+fn parse_spimm32(s : &str, values : &[(&str, u32)]) -> Option<u32> { parse_spimm(s, values, Xlen::X32) }
+fn parse_spimm64(s : &str, values : &[(&str, u32)]) -> Option<u32> { parse_spimm(s, values, Xlen::X64) }
+
+impl ISARVZc {
+    ///Zcb, simple code-size saving instructions
+    pub fn zcb(xlen : Xlen) -> ISARVZc {
+        let mut list = vec! [
+            asdis::instruction16!("c.lbu {rdp:creg}, imm ({rs1p:creg})", 100000, rs1p[2:0], imm[0|1], rdp[2:0], 00).sem(Sem::load(1, false).compressed(Operand::Reg("rs1"))),
+            asdis::instruction16!("c.lhu {rdp:creg}, imm ({rs1p:creg})", 100001, rs1p[2:0], 0, imm[1], rdp[2:0], 00).sem(Sem::load(2, false).compressed(Operand::Reg("rs1"))),
+            asdis::instruction16!("c.lh {rdp:creg}, imm ({rs1p:creg})", 100001, rs1p[2:0], 1, imm[1], rdp[2:0], 00).sem(Sem::load(2, true).compressed(Operand::Reg("rs1"))),
+            asdis::instruction16!("c.sb {rs2p:creg}, imm ({rs1p:creg})", 100010, rs1p[2:0], imm[0|1], rs2p[2:0], 00).sem(Sem::store(1).compressed(Operand::Reg("rs1"))),
+            asdis::instruction16!("c.sh {rs2p:creg}, imm ({rs1p:creg})", 100011, rs1p[2:0], 0, imm[1], rs2p[2:0], 00).sem(Sem::store(2).compressed(Operand::Reg("rs1"))),
+
+            asdis::instruction16!("c.zext.b {rdp:creg}", 100111, rdp[2:0], 1100001).sem(Sem::extend(8, false)),
+            asdis::instruction16!("c.sext.b {rdp:creg}", 100111, rdp[2:0], 1100101).sem(Sem::extend(8, true)),
+            asdis::instruction16!("c.zext.h {rdp:creg}", 100111, rdp[2:0], 1101001).sem(Sem::extend(16, false)),
+            asdis::instruction16!("c.sext.h {rdp:creg}", 100111, rdp[2:0], 1101101).sem(Sem::extend(16, true)),
+            asdis::instruction16!("c.not {rdp:creg}", 100111, rdp[2:0], 1110101).sem(Sem::rd(Op::Xor, Operand::Const(-1))),
+            asdis::instruction16!("c.mul {rdp:creg}, {rs2p:creg}", 100111, rdp[2:0], 10, rs2p[2:0], 01).sem(Sem::rd(Op::Mul, Operand::Reg("rs2"))),
+        ];
+
+        if xlen == Xlen::X64 {
+            list.push( asdis::instruction16!("c.zext.w {rdp:creg}", 100111, rdp[2:0], 1110001).sem(Sem::extend(32, false)) );
+        }
+
+        ISARVZc { list, ..Default::default() }
+    }
+
+    ///Zcmp, push/pop and double move of saved registers
+    pub fn zcmp(xlen : Xlen) -> ISARVZc {
+        let list = vec! [
+            asdis::instruction16!("cm.push rlist, -spimm", 10111000, rlist[3:0], spimm[5:4], 10, reserved if rlist == {0, 1, 2, 3}).sem(Sem::Push),
+            asdis::instruction16!("cm.pop rlist, spimm", 10111010, rlist[3:0], spimm[5:4], 10, reserved if rlist == {0, 1, 2, 3}).sem(Sem::Pop { ret : false, zero : false }),
+            asdis::instruction16!("cm.popretz rlist, spimm", 10111100, rlist[3:0], spimm[5:4], 10, reserved if rlist == {0, 1, 2, 3}).sem(Sem::Pop { ret : true, zero : true }),
+            asdis::instruction16!("cm.popret rlist, spimm", 10111110, rlist[3:0], spimm[5:4], 10, reserved if rlist == {0, 1, 2, 3}).sem(Sem::Pop { ret : true, zero : false }),
+            asdis::instruction16!("cm.mvsa01 r1s, r2s", 101011, r1s[2:0], 01, r2s[2:0], 10, reserved if r1s == r2s),
+            asdis::instruction16!("cm.mva01s r1s, r2s", 101011, r1s[2:0], 11, r2s[2:0], 10),
+        ];
+
+        let mut show_dict = HashMap::new();
+        let mut parse_dict = HashMap::new();
+        for r in ["r1s", "r2s"] {
+            show_dict.insert(String::from(r), show_sregister as ShowFun::<CompactType>);
+            parse_dict.insert(String::from(r), parse_sregister as ParseFun::<CompactType>);
+        }
+        show_dict.insert(String::from("rlist"), show_rlist as ShowFun::<CompactType>);
+        parse_dict.insert(String::from("rlist"), parse_rlist as ParseFun::<CompactType>);
+
+        let (show_spimm, parse_spimm) = match xlen {
+            Xlen::X32 => (show_spimm32 as ShowFieldsFun::<CompactType>, parse_spimm32 as ParseFieldsFun::<CompactType>),
+            Xlen::X64 => (show_spimm64 as ShowFieldsFun::<CompactType>, parse_spimm64 as ParseFieldsFun::<CompactType>),
+        };
+        let mut show_fields_dict = HashMap::new();
+        show_fields_dict.insert(String::from("spimm"), show_spimm);
+        let mut parse_fields_dict = HashMap::new();
+        parse_fields_dict.insert(String::from("spimm"), parse_spimm);
+
+        ISARVZc { list, show_dict, parse_dict, show_fields_dict, parse_fields_dict }
+    }
+
+    ///Zcmt, table jumps, index below 32 is cm.jt, 32 and above is cm.jalt
+    pub fn zcmt() -> ISARVZc {
+        let list = vec! [
+            asdis::instruction16!("cm.jt index", 101000000, index[4:0], 10),
+            asdis::instruction16!("cm.jalt index", 101000, index[7:0], 10, index >= 32),
+        ];
+
+        ISARVZc { list, ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn zcb_extend() {
+        let isa = ISARVZc::zcb(Xlen::X32);
+        assert_eq!(decode(0x9c61, &isa), "c.zext.b r8");
+        assert_eq!(encode("c.sext.h r8", &isa), Some( 0x9c6d ));
+    }
+
+    #[test]
+    fn rlist() {
+        for v in 4..16 {
+            assert_eq!(parse_rlist(&show_rlist(v)), Some( v ));
+        }
+        assert_eq!(parse_rlist("{ ra,s0-s2 }"), Some( 7 ));
+        assert_eq!(parse_rlist("{ra, s0-s10}"), None);
+        assert_eq!(stack_adj_base(15, Xlen::X64), 112);
+        assert_eq!(stack_adj_base(7, Xlen::X64), 32);
+    }
+
+    #[test]
+    fn push_pop() {
+        let isa = ISARVZc::zcmp(Xlen::X32);
+        assert_eq!(decode(0xb876, &isa), "cm.push {ra, s0-s2}, -32");
+        assert_eq!(encode("cm.push {ra, s0-s2}, -32", &isa), Some( 0xb876 ));
+        assert_eq!(encode("cm.popret {ra}, 32", &isa), Some( 0xbe46 ));
+        assert_eq!(encode("cm.pop {ra}, 24", &isa), None);
+        assert_eq!(decode(0xac7e, &isa), "cm.mva01s r8, r23");
+        let isa = ISARVZc::zcmp(Xlen::X64);
+        assert_eq!(decode(0xb8f6, &isa), "cm.push {ra, s0-s11}, -128");
+    }
+
+    #[test]
+    fn zcmp_reserved() {
+        let isa = ISARVZc::zcmp(Xlen::X32);
+        assert_eq!(decode(0xb802, &isa), "cm.push 0x0, -64 # reserved");
+        assert_eq!(decode(0xbe32, &isa), "cm.popret 0x3, 64 # reserved");
+        assert_eq!(decode(0xac22, &isa), "cm.mvsa01 r8, r8 # reserved");
+        assert_eq!(decode(0xac26, &isa), "cm.mvsa01 r8, r9");
+    }
+
+    #[test]
+    fn zcmt() {
+        let isa = ISARVZc::zcmt();
+        assert_eq!(decode(0xa016, &isa), "cm.jt 0x5");
+        assert_eq!(decode(0xa082, &isa), "cm.jalt 0x20");
+        assert_eq!(encode("cm.jalt 5", &isa), None);
+        assert_eq!(encode("cm.jt 5", &isa), Some( 0xa016 ));
+        assert_eq!(encode("cm.jalt 0x20", &isa), Some( 0xa082 ));
+    }
+
+    #[test]
+    fn zcb() {
+        let isa = ISARVZc::zcb(Xlen::X32);
+        assert_eq!(decode(0x8040, &isa), "c.lbu r8, 0x1 (r8)");
+        assert_eq!(encode("c.sb r9, 1 (r10)", &isa), Some( 0x8944 ));
+        assert_eq!(decode(0x9c75, &isa), "c.not r8");
+        assert_eq!(decode(0x9c71, &isa), "CompactType. Not found!");
+    }
+}
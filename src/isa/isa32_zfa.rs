@@ -0,0 +1,111 @@
+use crate::primitives::*;
+use crate::isa::isa::*;
+use std::collections::HashMap;
+
+pub type ISARVZfa = ISA<RV32Type>;
+
+///constants loaded by fli, indexed by rs1 field
+const FLI_CONSTANTS : [&str; 32] = [
+    "-1.0", "min", "0x1p-16", "0x1p-15", "0x1p-8", "0x1p-7", "0.0625", "0.125",
+    "0.25", "0.3125", "0.375", "0.4375", "0.5", "0.625", "0.75", "0.875",
+    "1.0", "1.25", "1.5", "1.75", "2.0", "2.5", "3.0", "4.0",
+    "8.0", "16.0", "128.0", "256.0", "32768.0", "65536.0", "inf", "nan",
+];
+
+///helper to show constant of fli
+fn show_fli(v : u32) -> String
+{
+    String::from(FLI_CONSTANTS[v as usize & 0x1F])
+}
+
+///helper to parse constant of fli, given as in FLI_CONSTANTS table
+fn parse_fli(s : &str) -> Option<u32>
+{
+    FLI_CONSTANTS.iter().position(|c| *c == s).map(|v| v as u32)
+}
+
+impl ISARVZfa {
+    ///Zfa, additional floating point instructions for all of F, D, Zfh and Q formats.
+    ///fcvtmod.w.d always rounds towards zero, so rtz operand is not shown
+    pub fn zfa(xlen : Xlen) -> ISARVZfa {
+        let mut list = vec! [
+            asdis::instruction32!("fli.s {frd:freg}, fli", 1111000, 00001, fli[4:0], 000, frd[4:0], 1010011),
+            asdis::instruction32!("fli.d {frd:freg}, fli", 1111001, 00001, fli[4:0], 000, frd[4:0], 1010011),
+            asdis::instruction32!("fli.h {frd:freg}, fli", 1111010, 00001, fli[4:0], 000, frd[4:0], 1010011),
+            asdis::instruction32!("fli.q {frd:freg}, fli", 1111011, 00001, fli[4:0], 000, frd[4:0], 1010011),
+
+            asdis::instruction32!("fminm.s {frd:freg}, {frs1:freg}, {frs2:freg}", 0010100, frs2[4:0], frs1[4:0], 010, frd[4:0], 1010011),
+            asdis::instruction32!("fmaxm.s {frd:freg}, {frs1:freg}, {frs2:freg}", 0010100, frs2[4:0], frs1[4:0], 011, frd[4:0], 1010011),
+            asdis::instruction32!("fminm.d {frd:freg}, {frs1:freg}, {frs2:freg}", 0010101, frs2[4:0], frs1[4:0], 010, frd[4:0], 1010011),
+            asdis::instruction32!("fmaxm.d {frd:freg}, {frs1:freg}, {frs2:freg}", 0010101, frs2[4:0], frs1[4:0], 011, frd[4:0], 1010011),
+            asdis::instruction32!("fminm.h {frd:freg}, {frs1:freg}, {frs2:freg}", 0010110, frs2[4:0], frs1[4:0], 010, frd[4:0], 1010011),
+            asdis::instruction32!("fmaxm.h {frd:freg}, {frs1:freg}, {frs2:freg}", 0010110, frs2[4:0], frs1[4:0], 011, frd[4:0], 1010011),
+            asdis::instruction32!("fminm.q {frd:freg}, {frs1:freg}, {frs2:freg}", 0010111, frs2[4:0], frs1[4:0], 010, frd[4:0], 1010011),
+            asdis::instruction32!("fmaxm.q {frd:freg}, {frs1:freg}, {frs2:freg}", 0010111, frs2[4:0], frs1[4:0], 011, frd[4:0], 1010011),
+
+            asdis::instruction32!("fround.s {frd:freg}, {frs1:freg}, {rm:rm}", 0100000, 00100, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("froundnx.s {frd:freg}, {frs1:freg}, {rm:rm}", 0100000, 00101, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fround.d {frd:freg}, {frs1:freg}, {rm:rm}", 0100001, 00100, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("froundnx.d {frd:freg}, {frs1:freg}, {rm:rm}", 0100001, 00101, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fround.h {frd:freg}, {frs1:freg}, {rm:rm}", 0100010, 00100, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("froundnx.h {frd:freg}, {frs1:freg}, {rm:rm}", 0100010, 00101, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("fround.q {frd:freg}, {frs1:freg}, {rm:rm}", 0100011, 00100, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+            asdis::instruction32!("froundnx.q {frd:freg}, {frs1:freg}, {rm:rm}", 0100011, 00101, frs1[4:0], rm[2:0], frd[4:0], 1010011),
+
+            asdis::instruction32!("fleq.s {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010000, frs2[4:0], frs1[4:0], 100, rd[4:0], 1010011),
+            asdis::instruction32!("fltq.s {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010000, frs2[4:0], frs1[4:0], 101, rd[4:0], 1010011),
+            asdis::instruction32!("fleq.d {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010001, frs2[4:0], frs1[4:0], 100, rd[4:0], 1010011),
+            asdis::instruction32!("fltq.d {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010001, frs2[4:0], frs1[4:0], 101, rd[4:0], 1010011),
+            asdis::instruction32!("fleq.h {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010010, frs2[4:0], frs1[4:0], 100, rd[4:0], 1010011),
+            asdis::instruction32!("fltq.h {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010010, frs2[4:0], frs1[4:0], 101, rd[4:0], 1010011),
+            asdis::instruction32!("fleq.q {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010011, frs2[4:0], frs1[4:0], 100, rd[4:0], 1010011),
+            asdis::instruction32!("fltq.q {rd:xreg}, {frs1:freg}, {frs2:freg}", 1010011, frs2[4:0], frs1[4:0], 101, rd[4:0], 1010011),
+
+            asdis::instruction32!("fcvtmod.w.d {rd:xreg}, {frs1:freg}", 1100001, 01000, frs1[4:0], 001, rd[4:0], 1010011),
+        ];
+
+        match xlen {
+            Xlen::X32 => list.extend( vec! [
+                asdis::instruction32!("fmvh.x.d {rd:xreg}, {frs1:freg}", 1110001, 00001, frs1[4:0], 000, rd[4:0], 1010011),
+                asdis::instruction32!("fmvp.d.x {frd:freg}, {rs1:xreg}, {rs2:xreg}", 1011001, rs2[4:0], rs1[4:0], 000, frd[4:0], 1010011),
+            ]),
+            Xlen::X64 => list.extend( vec! [
+                asdis::instruction32!("fmvh.x.q {rd:xreg}, {frs1:freg}", 1110011, 00001, frs1[4:0], 000, rd[4:0], 1010011),
+                asdis::instruction32!("fmvp.q.x {frd:freg}, {rs1:xreg}, {rs2:xreg}", 1011011, rs2[4:0], rs1[4:0], 000, frd[4:0], 1010011),
+            ]),
+        };
+
+        let mut show_dict = HashMap::new();
+        let mut parse_dict = HashMap::new();
+        show_dict.insert(String::from("fli"), show_fli as ShowFun::<RV32Type>);
+        parse_dict.insert(String::from("fli"), parse_fli as ParseFun::<RV32Type>);
+
+        ISARVZfa { list, show_dict, parse_dict, ..Default::default() }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn min_max() {
+        let isa = ISARVZfa::zfa(Xlen::X32);
+        assert_eq!(decode(0x28c5a553, &isa), "fminm.s f10, f11, f12");
+        assert_eq!(encode("fmaxm.s f10, f11, f12", &isa), Some( 0x28c5b553 ));
+    }
+
+    #[test]
+    fn zfa() {
+        let isa = ISARVZfa::zfa(Xlen::X32);
+        assert_eq!(decode(0xf2160553, &isa), "fli.d f10, 0.5");
+        assert_eq!(encode("fli.s f1, min", &isa), Some( 0xf01080d3 ));
+        assert_eq!(decode(0x40459553, &isa), "fround.s f10, f11, rtz");
+        assert_eq!(decode(0x2a3120d3, &isa), "fminm.d f1, f2, f3");
+        assert_eq!(decode(0xc2859553, &isa), "fcvtmod.w.d r10, f11");
+        assert_eq!(encode("fround.s f10, f11, dyn", &isa), Some( 0x4045f553 ));
+    }
+}
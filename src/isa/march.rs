@@ -0,0 +1,351 @@
+//! ISA configuration from RISC-V ISA string, like `rv32imafc_zicsr_zba_zbb`
+
+use crate::primitives::*;
+use crate::isa::isa::*;
+use crate::isa::{ISARV32IMA, ISARV32C, ISARVF, ISARVB, ISARVV, ISARVPriv, ISARVK, ISARVZc, ISARVMisc, ISARVZfa, IsaSet, VendorRegistry};
+
+///canonical order of single-letter extensions which follow base ISA
+const CANONICAL_ORDER : &str = "mafdqlcbkjtpvnh";
+
+///extensions of G, besides base I
+const G_EXTENSIONS : [&str; 6] = ["m", "a", "f", "d", "zicsr", "zifencei"];
+
+///multi-letter standard extensions, some of them have no instructions
+const KNOWN_EXTENSIONS : [&str; 39] = [
+    "zicsr", "zifencei", "zicntr", "zihpm", "zihintpause", "zicbom", "zicboz", "zicbop", "zicond", "zawrs",
+    "zba", "zbb", "zbc", "zbs", "zbkb", "zbkc", "zbkx",
+    "zk", "zkn", "zks", "zknd", "zkne", "zknh", "zksed", "zksh", "zkr", "zkt",
+    "zca", "zcb", "zcf", "zcd", "zcmp", "zcmt", "zfa", "ztso",
+    "svinval", "svnapot", "svpbmt", "sstc",
+];
+
+///base ISA width and extensions, implied extensions are included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct March {
+    pub xlen : Xlen,
+    pub extensions : Vec<String>,
+}
+
+///major and minor version of extension
+type Version = (u32, u32);
+
+///split version like 2p1 or 2 from the end of extension name, returns name and version
+fn split_version(s : &str) -> Result<(&str, Option<Version>), String> {
+    let name = s.trim_end_matches(|c : char| c.is_ascii_digit() || c == 'p');
+    //p is extension name itself if there are no digits before it
+    let name = match s[name.len()..].chars().next() {
+        Some( 'p' ) => &s[..name.len() + 1],
+        _ => name,
+    };
+    let version = &s[name.len()..];
+    if version.is_empty() {
+        return Ok( (name, None) );
+    }
+    let (major, minor) = version.split_once('p').unwrap_or((version, "0"));
+    match (major.parse::<u32>(), minor.parse::<u32>()) {
+        (Ok( major ), Ok( minor )) => Ok( (name, Some( (major, minor) )) ),
+        _ => Err( format!("Wrong version of extension {} : {}", name, version) ),
+    }
+}
+
+///length of single-letter extension with optional version at start of string
+fn single_len(s : &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut n = 1;
+    while n < bytes.len() && bytes[n].is_ascii_digit() {
+        n += 1;
+    }
+    if n > 1 && n + 1 < bytes.len() && bytes[n] == b'p' && bytes[n + 1].is_ascii_digit() {
+        n += 1;
+        while n < bytes.len() && bytes[n].is_ascii_digit() {
+            n += 1;
+        }
+    }
+    n
+}
+
+impl March {
+    ///parse ISA string, G is expanded and implied extensions are added
+    pub fn parse(s : &str) -> Result<March, String> {
+        March::parse_with(s, &VendorRegistry::default())
+    }
+
+    ///parse ISA string, vendor extensions are looked up in given registry
+    pub fn parse_with(s : &str, vendors : &VendorRegistry) -> Result<March, String> {
+        let s = s.trim().to_ascii_lowercase();
+        let (xlen, rest) = if let Some( r ) = s.strip_prefix("rv32") {
+            (Xlen::X32, r)
+        } else if let Some( r ) = s.strip_prefix("rv64") {
+            (Xlen::X64, r)
+        } else {
+            return Err( format!("ISA string must start with rv32 or rv64 : {}", s) );
+        };
+
+        let mut march = March { xlen, extensions : Vec::new() };
+        let mut parts = rest.split('_');
+        let mut singles = parts.next().unwrap_or("");
+
+        let base = singles.chars().next().ok_or("Base ISA is missing")?;
+        match base {
+            'i' | 'e' => march.add(&base.to_string())?,
+            'g' => {
+                march.add("i")?;
+                for e in G_EXTENSIONS {
+                    march.add(e)?;
+                }
+            },
+            _ => return Err( format!("Base ISA must be i, e or g : {}", base) ),
+        }
+        let n = single_len(singles);
+        split_version(&singles[..n])?;
+        singles = &singles[n..];
+
+        let mut last = 0;
+        while !singles.is_empty() {
+            let n = single_len(singles);
+            let (name, _) = split_version(&singles[..n])?;
+            let order = CANONICAL_ORDER.find(name).ok_or_else(|| format!("Unknown extension {}", name))?;
+            if order < last {
+                return Err( format!("Extension {} is out of canonical order {}", name, CANONICAL_ORDER) );
+            }
+            last = order;
+            march.add(name)?;
+            singles = &singles[n..];
+        }
+
+        for part in parts {
+            let (name, _) = split_version(part)?;
+            let known = match name.chars().next() {
+                Some( 'x' ) => vendors.is_vendor(name),
+                _ => (name.len() == 1 && CANONICAL_ORDER.contains(name)) || KNOWN_EXTENSIONS.contains(&name),
+            };
+            if !known {
+                return Err( format!("Unknown extension {}", part) );
+            }
+            march.add(name)?;
+        }
+
+        march.imply();
+        if march.has("zcd") && (march.has("zcmp") || march.has("zcmt")) {
+            return Err( String::from("Zcmp and Zcmt are incompatible with Zcd") );
+        }
+        Ok( march )
+    }
+
+    ///check if extension is present
+    pub fn has(&self, name : &str) -> bool {
+        self.extensions.iter().any(|e| e == name)
+    }
+
+    ///add extension, every extension could be given only once
+    fn add(&mut self, name : &str) -> Result<(), String> {
+        if name.is_empty() || self.has(name) {
+            return Err( format!("Extension '{}' is empty or duplicated", name) );
+        }
+        self.extensions.push( String::from(name) );
+        Ok( () )
+    }
+
+    ///extensions implied by given one, some of them depend on other extensions
+    fn implied(&self, name : &str) -> Vec<&'static str> {
+        match name {
+            "d" | "zfa" | "zcf" => vec!["f"],
+            "f" | "h" => vec!["zicsr"],
+            "q" | "v" | "zcd" => vec!["d"],
+            "b" => vec!["zba", "zbb", "zbs"],
+            "zk" => vec!["zkn", "zkr", "zkt"],
+            "zkn" => vec!["zbkb", "zbkc", "zbkx", "zkne", "zknd", "zknh"],
+            "zks" => vec!["zbkb", "zbkc", "zbkx", "zksed", "zksh"],
+            "zcb" | "zcmp" => vec!["zca"],
+            "zcmt" => vec!["zca", "zicsr"],
+            "c" => {
+                let mut v = vec!["zca"];
+                if self.xlen == Xlen::X32 && self.has("f") {
+                    v.push("zcf");
+                }
+                if self.has("d") {
+                    v.push("zcd");
+                }
+                v
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    ///add implied extensions until nothing changes
+    fn imply(&mut self) {
+        loop {
+            let added : Vec<&str> = self.extensions.iter()
+                .flat_map(|e| self.implied(e))
+                .filter(|e| !self.has(e))
+                .collect();
+            if added.is_empty() {
+                break;
+            }
+            for e in added {
+                if !self.has(e) {
+                    self.extensions.push( String::from(e) );
+                }
+            }
+        }
+    }
+
+    ///table of standard 32-bit instructions, with one more fragment for every vendor extension.
+    ///Extensions without instructions, like Zkr or Zicntr, are accepted and ignored.
+    ///Register operands are x0..x15 with E base
+    pub fn isa32(&self) -> Result<IsaSet<RV32Type>, String> {
+        self.isa32_with(&VendorRegistry::default())
+    }
+
+    ///table of 32-bit instructions, vendor extensions are taken from given registry
+    pub fn isa32_with(&self, vendors : &VendorRegistry) -> Result<IsaSet<RV32Type>, String> {
+        let xlen = self.xlen;
+        let mut isa = ISARV32IMA::i(xlen);
+        isa.append( ISARVPriv::privileged() );
+        for e in &self.extensions {
+            match e.as_str() {
+                "m" => isa.append( ISARV32IMA::m(xlen) ),
+                "a" => isa.append( ISARV32IMA::a(xlen) ),
+                "f" => isa.append( ISARVF::f(xlen) ),
+                "d" => isa.append( ISARVF::d(xlen) ),
+                "v" => isa.append( ISARVV::v() ),
+                "h" => isa.append( ISARVPriv::hypervisor(xlen) ),
+                "zicsr" => isa.append( ISARV32IMA::zicsr() ),
+                "zifencei" => isa.append( ISARV32IMA::zifencei() ),
+                "zba" => isa.append( ISARVB::zba(xlen) ),
+                "zbb" => isa.append( ISARVB::zbb(xlen) ),
+                "zbc" => isa.append( ISARVB::zbc() ),
+                "zbs" => isa.append( ISARVB::zbs(xlen) ),
+                "zbkb" => isa.append( ISARVK::zbkb(xlen) ),
+                "zbkc" => isa.append( ISARVK::zbkc() ),
+                "zbkx" => isa.append( ISARVK::zbkx() ),
+                "zknd" => isa.append( ISARVK::zknd(xlen) ),
+                "zkne" => isa.append( ISARVK::zkne(xlen) ),
+                "zknh" => isa.append( ISARVK::zknh(xlen) ),
+                "zksed" => isa.append( ISARVK::zksed() ),
+                "zksh" => isa.append( ISARVK::zksh() ),
+                "zicbom" => isa.append( ISARVMisc::zicbom() ),
+                "zicboz" => isa.append( ISARVMisc::zicboz() ),
+                "zicbop" => isa.append( ISARVMisc::zicbop() ),
+                "zicond" => isa.append( ISARVMisc::zicond() ),
+                "zawrs" => isa.append( ISARVMisc::zawrs() ),
+                "zihintpause" => isa.append( ISARVMisc::zihintpause() ),
+                "zfa" => isa.append( ISARVZfa::zfa(xlen) ),
+                "svinval" => isa.append( ISARVPriv::svinval() ),
+                _ => (),
+            }
+        }
+
+        if self.has("e") {
+            isa.embedded();
+        }
+
+        let mut set = IsaSet::default();
+        set.add_unchecked("standard", isa);
+        for e in self.extensions.iter().filter(|e| e.starts_with('x')) {
+            let mut fragment = vendors.fragment(e, xlen).ok_or_else(|| format!("Unknown vendor extension {}", e))?;
+            if self.has("e") {
+                fragment.embedded();
+            }
+            if let Err( conflicts ) = set.add(e, fragment) {
+                let list : Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+                return Err( list.join("\n") );
+            }
+        }
+        Ok( set )
+    }
+
+    ///table of 16-bit instructions, empty if there is no Zca. Register operands are x0..x15 with E base
+    pub fn isa16(&self) -> ISA<CompactType> {
+        let mut isa = ISA::<CompactType>::default();
+        for e in &self.extensions {
+            match e.as_str() {
+                "zca" => isa.append( ISARV32C::zca(self.xlen) ),
+                "zcf" if self.xlen == Xlen::X32 => isa.append( ISARV32C::zcf() ),
+                "zcd" => isa.append( ISARV32C::zcd() ),
+                "zcb" => isa.append( ISARVZc::zcb(self.xlen) ),
+                "zcmp" => isa.append( ISARVZc::zcmp(self.xlen) ),
+                "zcmt" => isa.append( ISARVZc::zcmt() ),
+                _ => (),
+            }
+        }
+        if self.has("e") {
+            isa.embedded();
+        }
+        isa
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::{decode, decode_set};
+
+    #[test]
+    fn parse() {
+        let m = March::parse("rv32imafc_zicsr_zba_zbb").unwrap();
+        assert_eq!(m.xlen, Xlen::X32);
+        for e in ["i", "m", "a", "f", "c", "zicsr", "zba", "zbb", "zca", "zcf"] {
+            assert!(m.has(e), "{}", e);
+        }
+        assert!(!m.has("d"));
+        assert!(!m.has("zcd"));
+
+        let m = March::parse("RV64GC").unwrap();
+        for e in ["i", "m", "a", "f", "d", "zicsr", "zifencei", "c", "zca", "zcd"] {
+            assert!(m.has(e), "{}", e);
+        }
+        assert!(!m.has("zcf"));
+
+        let m = March::parse("rv32i2p1m2_zk_xthead").unwrap();
+        for e in ["m", "zkn", "zbkb", "zknh", "zkr", "xthead"] {
+            assert!(m.has(e), "{}", e);
+        }
+        assert!(March::parse("rv32id").unwrap().has("zicsr"));
+        assert!(March::parse("rv32ip").unwrap().has("p"));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(March::parse("rv128i").is_err());
+        assert!(March::parse("rv32").is_err());
+        assert!(March::parse("rv32ma").is_err());
+        assert!(March::parse("rv32iam").is_err());
+        assert!(March::parse("rv32imm").is_err());
+        assert!(March::parse("rv32i_foo").is_err());
+        assert!(March::parse("rv32i2px").is_err());
+        assert!(March::parse("rv64gc_zcmp").is_err());
+        assert!(March::parse("rv32i_xfoo").is_err());
+        assert!(March::parse("rv32i_zfoo").is_err());
+        assert!(March::parse("rv32i_sfoo").is_err());
+    }
+
+    #[test]
+    fn embedded() {
+        let m = March::parse("rv32ec").unwrap();
+        let set = m.isa32().unwrap();
+        assert_eq!(decode_set(0x00b50533, &set), "add r10, r10, r11");
+        assert_eq!(decode_set(0x01050533, &set), "RV32Type. Not found!");
+        assert_eq!(crate::encoder::encode_set("add r16, r10, r11", &set), None);
+        assert_eq!(decode(0x8742, &m.isa16()), "CompactType. Not found!");
+        assert_eq!(decode(0x8742, &March::parse("rv32ic").unwrap().isa16()), "c.mv r14, r16");
+    }
+
+    #[test]
+    fn tables() {
+        let ld = 0x00053503;
+        let m = March::parse("rv32imc").unwrap();
+        assert_eq!(decode_set(ld, &m.isa32().unwrap()), "RV32Type. Not found!");
+        assert_eq!(decode_set(0x00b5252f, &m.isa32().unwrap()), "RV32Type. Not found!");
+        assert_eq!(decode(0x2505, &m.isa16()), "c.jal 0x620");
+
+        let m = March::parse("rv64imac").unwrap();
+        assert_eq!(decode_set(ld, &m.isa32().unwrap()), "ld r10, 0x0 (r10)");
+        assert_eq!(decode_set(0x00b5252f, &m.isa32().unwrap()), "amoadd.w r10, r10, r11");
+        assert_eq!(decode(0x2505, &m.isa16()), "c.addiw r10, 0x1");
+
+        let m = March::parse("rv32i").unwrap();
+        assert_eq!(decode(0x2505, &m.isa16()), "CompactType. Not found!");
+    }
+}
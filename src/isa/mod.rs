@@ -1,7 +1,34 @@
 mod isa;
 mod isa32_i;
 mod isa32_c;
+mod isa32_f;
+mod isa32_b;
+mod isa32_v;
+mod isa32_priv;
+mod isa32_k;
+mod isa32_zc;
+mod isa32_misc;
+mod isa32_zfa;
+mod isa32_xthead;
+mod vendor;
+mod set;
+mod opcodes;
+mod march;
+mod csr;
 
-pub use isa::{ShowFun, ShowDict, ISA};
-pub use isa32_c::ISARV32C;
-pub use isa32_i::ISARV32IMA;
+pub use isa::{ISA, Xlen, show_operand, parse_operand, sign_extend};
+pub use isa32_c::{ISARV32C, RvcInstr};
+pub use isa32_i::{ISARV32IMA, Rv32Instr};
+pub use isa32_f::ISARVF;
+pub use isa32_b::ISARVB;
+pub use isa32_v::ISARVV;
+pub use isa32_priv::ISARVPriv;
+pub use isa32_k::ISARVK;
+pub use isa32_zc::{ISARVZc, stack_adj, rlist_registers};
+pub use isa32_misc::ISARVMisc;
+pub use isa32_zfa::ISARVZfa;
+pub use isa32_xthead::ISARVXThead;
+pub use vendor::{VendorFragment, VendorRegistry};
+pub use set::IsaSet;
+pub use opcodes::parse_opcodes;
+pub use march::March;
@@ -0,0 +1,201 @@
+//! Loader of instruction descriptions in riscv-opcodes text format, like
+//! `add rd rs1 rs2 31..25=0 14..12=0 6..2=0x0C 1..0=3`
+
+use crate::primitives::*;
+use crate::isa::isa::*;
+
+///operand fields of riscv-opcodes: (field, ident, highest bit of field in word, bitspec of ident).
+///Fields like bimm12hi and bimm12lo are parts of the same ident
+const ARG_LUT : [(&str, &str, u32, &str); 37] = [
+    ("rd", "rd", 11, "4:0"), ("rs1", "rs1", 19, "4:0"), ("rs2", "rs2", 24, "4:0"), ("rs3", "rs3", 31, "4:0"),
+    ("rm", "rm", 14, "2:0"),
+    ("imm12", "imm", 31, "11:0"), ("imm12hi", "imm", 31, "11:5"), ("imm12lo", "imm", 11, "4:0"),
+    ("bimm12hi", "imm", 31, "12|10:5"), ("bimm12lo", "imm", 11, "4:1|11"),
+    ("imm20", "imm", 31, "31:12"), ("jimm20", "imm", 31, "20|10:1|11|19:12"),
+    ("shamtw", "shamt", 24, "4:0"), ("shamtd", "shamt", 25, "5:0"), ("shamtw4", "shamt", 23, "3:0"),
+    ("csr", "csr", 31, "11:0"), ("zimm", "zimm", 19, "4:0"),
+    ("fm", "fm", 31, "3:0"), ("pred", "pred", 27, "3:0"), ("succ", "succ", 23, "3:0"),
+    ("aq", "aq", 26, "0"), ("rl", "rl", 25, "0"),
+    ("vd", "vd", 11, "4:0"), ("vs3", "vs3", 11, "4:0"), ("vs1", "vs1", 19, "4:0"), ("vs2", "vs2", 24, "4:0"),
+    ("vm", "vm", 25, "0"), ("nf", "nf", 31, "2:0"), ("wd", "wd", 26, "0"),
+    ("simm5", "simm5", 19, "4:0"), ("zimm5", "zimm", 19, "4:0"),
+    ("zimm10", "zimm", 29, "9:0"), ("zimm11", "zimm", 30, "10:0"),
+    ("zimm6hi", "zimm", 26, "5"), ("zimm6lo", "zimm", 19, "4:0"),
+    ("bs", "bs", 31, "1:0"), ("rnum", "rnum", 23, "3:0"),
+];
+
+///bit of instruction word
+#[derive(Clone, PartialEq, Eq)]
+enum Slot {
+    Fixed(bool),
+    Field(String, u32),
+}
+
+///put slot to given bit of word, every bit could be specified only once
+fn put(slots : &mut [Option<Slot>], bit : u32, slot : Slot) -> Result<(), String> {
+    match slots.get_mut(bit as usize) {
+        None => Err( format!("Bit {} is out of instruction word", bit) ),
+        Some( Some( _ ) ) => Err( format!("Bit {} is specified twice", bit) ),
+        Some( s ) => {
+            *s = Some( slot );
+            Ok( () )
+        },
+    }
+}
+
+///build list of items from slots, from highest bit of word to lowest
+fn items<T:Num>(slots : &[Option<Slot>]) -> Result<Vec<Item<T>>, String> {
+    let mut list = Vec::<Item<T>>::new();
+    let mut bits = String::new();
+    for (n, slot) in slots.iter().enumerate().rev() {
+        let slot = slot.as_ref().ok_or_else(|| format!("Bit {} is not specified", n))?;
+        match slot {
+            Slot::Fixed( b ) => bits.push( if *b { '1' } else { '0' } ),
+            Slot::Field( name, sbit ) => {
+                if !bits.is_empty() {
+                    let val = T::from_str_radix(&bits, 2).map_err(|e| e.to_string())?;
+                    list.push( Item::Bits { len : bits.len(), val } );
+                    bits.clear();
+                }
+                match list.last_mut() {
+                    Some( Item::Ident { name : n, bitspec } ) if n == name => bitspec.push(*sbit),
+                    _ => list.push( Item::Ident { name : name.clone(), bitspec : vec![*sbit] } ),
+                }
+            },
+        }
+    }
+    if !bits.is_empty() {
+        let val = T::from_str_radix(&bits, 2).map_err(|e| e.to_string())?;
+        list.push( Item::Bits { len : bits.len(), val } );
+    }
+    Ok( list )
+}
+
+///operand of text template, registers, CSR, rounding mode, vector mask and simm5 are annotated with their kind
+fn typed(ident : &str) -> String {
+    match ident {
+        "rd" | "rs1" | "rs2" | "rs3" => format!("{{{}:xreg}}", ident),
+        "vd" | "vs1" | "vs2" | "vs3" => format!("{{{}:vreg}}", ident),
+        "csr" | "rm" | "vm" | "simm5" => format!("{{{}:{}}}", ident, ident),
+        _ => String::from(ident),
+    }
+}
+
+///parse one line of description, returns instruction
+pub fn parse_line<T:Num>(line : &str) -> Result<Instruction<T>, String> {
+    let mut tokens = line.split_whitespace();
+    let mnemonic = tokens.next().ok_or("Empty line")?;
+    let mut slots : Vec<Option<Slot>> = vec![None; T::i_max_bit() as usize + 1];
+    let mut operands = Vec::<&str>::new();
+
+    for token in tokens {
+        match token.split_once('=') {
+            Some( (range, value) ) => {
+                let (hi, lo) = match range.split_once("..") {
+                    Some( (hi, lo) ) => (parse_value(hi)? as u32, parse_value(lo)? as u32),
+                    None => (parse_value(range)? as u32, parse_value(range)? as u32),
+                };
+                if hi < lo {
+                    return Err( format!("Wrong bit range {}", range) );
+                }
+                //ignored bits are not checked by decoder, every range is kept as ident of its own,
+                //which is not an operand
+                if value == "ignore" {
+                    let name = format!("ignore_{}_{}", hi, lo);
+                    for bit in lo..=hi {
+                        put(&mut slots, bit, Slot::Field(name.clone(), bit - lo))?;
+                    }
+                    continue;
+                }
+                let value = parse_value(value)?;
+                if hi - lo < 63 && value >> (hi - lo + 1) != 0 {
+                    return Err( format!("Value {} does not fit to bits {}", value, range) );
+                }
+                for bit in lo..=hi {
+                    put(&mut slots, bit, Slot::Fixed( (value >> (bit - lo)) & 1 == 1 ))?;
+                }
+            },
+            None => {
+                let (_, ident, msb, spec) = ARG_LUT.iter().find(|(f, _, _, _)| *f == token)
+                    .ok_or_else(|| format!("Unknown field {}", token))?;
+                for (k, sbit) in parse_bitspec(spec)?.iter().enumerate() {
+                    let bit = msb.checked_sub(k as u32).ok_or_else(|| format!("Wrong field {}", token))?;
+                    put(&mut slots, bit, Slot::Field(String::from(*ident), *sbit))?;
+                }
+                if !operands.contains(ident) {
+                    operands.push(ident);
+                }
+            },
+        }
+    }
+
+    let list = items::<T>(&slots)?;
+    validate(&list)?;
+    let operands : Vec<String> = operands.iter().map(|o| typed(o)).collect();
+    let text = match operands.is_empty() {
+        true => String::from(mnemonic),
+        false => format!("{} {}", mnemonic, operands.join(", ")),
+    };
+    Ok( Instruction::new( BinaryInstruction { list }, TextInstruction::from(&text[..]) ) )
+}
+
+///parse text in riscv-opcodes format, '#' starts comment, pseudo instructions and
+///imports (lines starting with '$') are skipped. Errors are reported with line number
+pub fn parse_opcodes(text : &str) -> Result<ISA<RV32Type>, String> {
+    let mut list = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() || line.starts_with('$') {
+            continue;
+        }
+        list.push( parse_line::<RV32Type>(line).map_err(|e| format!("line {}: {}", n + 1, e))? );
+    }
+
+    Ok( ISA { list, ..Default::default() } )
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decoder::decode;
+    use crate::encoder::encode;
+
+    #[test]
+    fn same_as_macro() {
+        let i = parse_line::<RV32Type>("beq bimm12hi rs1 rs2 bimm12lo 14..12=0 6..2=0x18 1..0=3").unwrap();
+        let m = asdis::instruction32!("beq imm, {rs1:xreg}, {rs2:xreg}", imm[12|10:5], rs2[4:0], rs1[4:0], 000, imm[4:1|11], 1100011);
+        assert_eq!(i.bin.list.len(), 6);
+        assert_eq!(i.mask(), m.mask());
+        assert_eq!(i.pattern(), m.pattern());
+        assert_eq!(i.text, m.text);
+    }
+
+    #[test]
+    fn load() {
+        let text = "# draft extension\n\
+                    add rd rs1 rs2 31..25=0 14..12=0 6..2=0x0C 1..0=3\n\
+                    $pseudo_op rv_i::addi nop 31..0=0x13\n\
+                    jal rd jimm20 6..2=0x1b 1..0=3\n";
+        let isa = parse_opcodes(text).unwrap();
+        assert_eq!(isa.list.len(), 2);
+        assert_eq!(decode(0x00b50533, &isa), "add r10, r10, r11");
+        assert_eq!(encode("jal r1, 0x800", &isa), Some( 0x001000ef ));
+    }
+
+    #[test]
+    fn ignored_ranges() {
+        let i = parse_line::<RV32Type>("fence.i 31..20=ignore 19..15=ignore 14..12=1 11..7=ignore 6..2=0x03 1..0=3").unwrap();
+        assert_eq!(i.text, TextInstruction::from("fence.i"));
+        assert_eq!(i.mask(), 0x0000707f);
+        assert_eq!(i.pattern(), 0x0000100f);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse_opcodes("add rd rs1 rs2 31..25=0 14..12=0 6..2=0x0C").unwrap_err().starts_with("line 1: Bit 1 is not specified"));
+        assert!(parse_line::<RV32Type>("add rd rs1 rs2 31..25=0 14..12=0 6..2=0x0C 1..0=3 11=1").is_err());
+        assert!(parse_line::<RV32Type>("add rd rs1 rs2 31..25=0 14..12=8 6..2=0x0C 1..0=3").is_err());
+        assert!(parse_line::<RV32Type>("add rd rs1 foo 31..25=0 14..12=0 6..2=0x0C 1..0=3").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::primitives::*;
+use crate::isa::isa::ISA;
+use std::fmt;
+
+///named part of instruction set, like standard extensions or vendor extension,
+///show and parse functions of fragment are applied only to its own instructions
+#[derive(Debug)]
+pub struct Fragment<T:Num> {
+    pub name : String,
+    pub isa : ISA<T>,
+}
+
+///two instructions from different fragments which share some encodings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub fragment : String,
+    pub existing : String,
+    pub added : String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' overlaps with '{}' of {}", self.added, self.existing, self.fragment)
+    }
+}
+
+///set of instruction set fragments, decoder and encoder use all of them
+#[derive(Debug)]
+pub struct IsaSet<T:Num> {
+    pub fragments : Vec<Fragment<T>>,
+}
+
+impl<T:Num> Default for IsaSet<T> {
+    fn default() -> Self {
+        IsaSet { fragments : Vec::new() }
+    }
+}
+
+///mnemonic of instruction, for diagnostics
+fn mnemonic<T:Num>(i : &Instruction<T>) -> String {
+    String::from(i.text.mnemonic())
+}
+
+///check if some word is matched by both instructions, constraints are not taken into account
+pub fn overlap<T:Num>(a : &Instruction<T>, b : &Instruction<T>) -> bool {
+    (a.pattern() ^ b.pattern()) & a.mask() & b.mask() == T::i_zero()
+}
+
+impl<T:Num> IsaSet<T> {
+    ///find instructions of given ISA which overlap with instructions of fragments in set
+    pub fn conflicts(&self, isa : &ISA<T>) -> Vec<Conflict> {
+        let mut result = Vec::new();
+        for f in &self.fragments {
+            for existing in &f.isa.list {
+                for added in isa.list.iter().filter(|i| overlap(existing, i)) {
+                    let c = Conflict { fragment : f.name.clone(), existing : mnemonic(existing), added : mnemonic(added) };
+                    if !result.contains(&c) {
+                        result.push( c );
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    ///add fragment to set, fragments which overlap with ones already in set are rejected
+    pub fn add(&mut self, name : &str, isa : ISA<T>) -> Result<(), Vec<Conflict>> {
+        let conflicts = self.conflicts(&isa);
+        if !conflicts.is_empty() {
+            return Err( conflicts );
+        }
+        self.add_unchecked(name, isa);
+        Ok( () )
+    }
+
+    ///add fragment to set without conflict detection, to be used for fragments which
+    ///intentionally carve instructions out of existing encodings
+    pub fn add_unchecked(&mut self, name : &str, isa : ISA<T>) {
+        self.fragments.push( Fragment { name : String::from(name), isa } );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::ISARV32IMA;
+
+    #[test]
+    fn conflicts() {
+        let mut set = IsaSet::<RV32Type>::default();
+        set.add("rv32i", ISARV32IMA::new()).unwrap();
+        let custom = ISA::<RV32Type> { list : vec![
+            asdis::instruction32!("acc.add rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 000, rd[4:0], 0001011),
+        ], ..Default::default() };
+        assert!(set.add("custom", custom).is_ok());
+        let bad = ISA::<RV32Type> { list : vec![
+            asdis::instruction32!("my.add rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+        ], ..Default::default() };
+        let r = set.add("bad", bad).unwrap_err();
+        assert_eq!(r.len(), 1);
+        assert_eq!(format!("{}", r[0]), "'my.add' overlaps with 'add' of rv32i");
+        assert_eq!(set.fragments.len(), 2);
+    }
+}
@@ -0,0 +1,84 @@
+//! Registry of vendor extensions, which are given by name in ISA string or by --vendor option
+
+use crate::primitives::*;
+use crate::isa::isa::*;
+use crate::isa::isa32_xthead::ISARVXThead;
+
+///constructor of vendor extension table for given base ISA width
+pub type VendorFragment = fn(Xlen) -> ISA<RV32Type>;
+
+///vendor extensions which are always known
+const BUILTIN : [(&str, VendorFragment); 1] = [
+    ("xthead", ISARVXThead::xthead),
+];
+
+///vendor extensions known by ISA string parser, built-in ones and registered by users of library
+#[derive(Clone)]
+pub struct VendorRegistry {
+    list : Vec<(String, VendorFragment)>,
+}
+
+impl Default for VendorRegistry {
+    fn default() -> VendorRegistry {
+        VendorRegistry { list : BUILTIN.iter().map(|(n, f)| (String::from(*n), *f)).collect() }
+    }
+}
+
+impl VendorRegistry {
+    ///register vendor extension, then its name is accepted in ISA strings. Name must start with x,
+    ///built-in and already registered names could not be replaced
+    pub fn register(&mut self, name : &str, fragment : VendorFragment) -> Result<(), String> {
+        let name = name.to_ascii_lowercase();
+        if name.len() < 2 || !name.starts_with('x') || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err( format!("Wrong name of vendor extension {}", name) );
+        }
+        if self.is_vendor(&name) {
+            return Err( format!("Vendor extension {} is already registered", name) );
+        }
+        self.list.push( (name, fragment) );
+        Ok( () )
+    }
+
+    ///check if vendor extension is known
+    pub fn is_vendor(&self, name : &str) -> bool {
+        self.constructor(name).is_some()
+    }
+
+    ///vendor extension fragment by name, to be added to set of standard extensions
+    pub fn fragment(&self, name : &str, xlen : Xlen) -> Option<ISA<RV32Type>> {
+        self.constructor(name).map(|f| f(xlen))
+    }
+
+    fn constructor(&self, name : &str) -> Option<VendorFragment> {
+        let name = name.to_ascii_lowercase();
+        self.list.iter().find(|(n, _)| *n == name).map(|(_, f)| *f)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::March;
+    use crate::decoder::decode_set;
+
+    fn xfoo(_ : Xlen) -> ISA<RV32Type> {
+        let list = vec! [ asdis::instruction32!("foo.add {rd:xreg}, {rs1:xreg}, {rs2:xreg}", 0000000, rs2[4:0], rs1[4:0], 000, rd[4:0], 1111011) ];
+        ISA { list, ..Default::default() }
+    }
+
+    #[test]
+    fn register() {
+        let mut vendors = VendorRegistry::default();
+        assert!(March::parse_with("rv32i_xfoo", &vendors).is_err());
+        vendors.register("xfoo", xfoo).unwrap();
+        assert!(vendors.register("xfoo", xfoo).is_err());
+        assert!(vendors.register("xthead", xfoo).is_err());
+        assert!(vendors.register("foo", xfoo).is_err());
+
+        let set = March::parse_with("rv32i_xfoo", &vendors).unwrap().isa32_with(&vendors).unwrap();
+        assert_eq!(decode_set(0x00b5057b, &set), "foo.add r10, r10, r11");
+        assert!(vendors.is_vendor("xthead"));
+        assert!(!VendorRegistry::default().is_vendor("xfoo"));
+    }
+}
@@ -1,171 +1,31 @@
-use proc_macro::{TokenStream, TokenTree, Delimiter};
-use quote::quote;
-
-mod primitives;
-use primitives::{Item, TextInstruction, BinaryInstruction, Instruction , Num, CompactType, RV32Type};
-use std::convert::From;
-
-fn bits_len<T:Num>( v : &Vec<Item<T>> ) -> u32 {
-    let mut r : u32 = 0;
-    for item in v {
-        match item {
-            Item::Bits {len, .. } => r += *len as u32,
-            Item::Ident { name:_ , bitspec } => r += bitspec.len() as u32,
-        };
-    };
-    r
-}
-
-//fn delimiter_string(d : &proc_macro::Delimiter) -> &str {
-//    match d {
-//        Delimiter::Parenthesis => "()",
-//        Delimiter::Brace => "{}",
-//        Delimiter::Bracket => "[]",
-//        Delimiter::None => "NONE",
-//    }
-//}
-
-fn parse_bitspec(ts : TokenStream) -> Vec::<u32> {
-
-    enum State {
-        None,
-        Val( u32 ),
-        First( u32 ),
-        Pair(u32, u32),
-    }
-
-    let mut bitspec  = Vec::<u32>::new();
-    let mut current = State::None;
-
-    for tt in ts {
-        match tt {
-            TokenTree::Group(_) => panic!("Group inside Group is not allowed"),
-            TokenTree::Ident(_) => panic!("Ident inside Group is not allowed"),
-            TokenTree::Punct(g) => {
-                let ch = g.as_char();
-                match ch {
-                    '|' => {
-                        match current {
-                            State::Val( n ) => { bitspec.push(n); current = State::None;},
-                            State::Pair(a, b) => {
-                                assert!(a >= b, "bitspec pair : first integer must be >= then second");
-                                for i in (b..=a).rev() {
-                                    bitspec.push(i);
-                                };
-                                current = State::None;
-                            },
-                            _ =>  panic!("| misplaced"),
-
-                        }
-                    },
-                    ':' => {
-                        match current {
-                            State::Val( a ) => current = State::First( a ),
-                            _ => panic!(": misplaced"),
-                        }
-                    }
-                    _ => panic!("Only | or : are allowed in bit specificators"),
-                }
-            },
-            TokenTree::Literal(g) => {
-                match g.to_string().parse::<u32>() {
-                    Ok( n ) => match current {
-                        State::None => current = State::Val( n ),
-                        State::First( a ) => current = State::Pair(a, n),
-                        _ => (), //panic!("Missing delimeter in bit spec"),
-                    }
-                    Err( err ) => panic!("Only unsigned integer allowed as bit specificators : {}", err),
-                }
-            },
-        }
-    }
-
-    match current {
-        State::Val( n ) => bitspec.push(n),
-        State::Pair(a, b) => {
-                                assert!(a >= b, "bitspec pair : first integer must be >= then second");
-                                for i in (b..=a).rev() {
-                                    bitspec.push(i);
-                                };
-                            },
-        _ => (),
-    }
-
-    return bitspec;
-}
-
-fn parse_token_string<T:Num>(ts : TokenStream) -> Instruction<T> {
-
-    enum State {
-        Empty,
-        Ident(String),
-    }
-
-    let mut current = State::Empty;
-    let mut r = Vec::<Item<T>>::new();
-
-    let mut iter = ts.into_iter();
-
-    let text = match iter.next() {
-        None => panic!("Empty token stream!"),
-        Some( tt ) => match tt {
-            TokenTree::Literal(g) => g.to_string() ,
-            _ => panic!("First argument must be a command description"),
-        }
-    };
-    assert!(text.len() > 2, "Quotes for command description not found");
-    let text = TextInstruction::from(&text[1..text.len() - 1]); //without quotes
-
-    for tt in iter {
-        match tt {
-            TokenTree::Group(g) => {
-                assert!(g.delimiter() == Delimiter::Bracket, "Only [] delimeters allowed for bitspecs");
-                match current {
-                    State::Ident( idnt ) => {
-                        let bs = parse_bitspec( g.stream() );
-                        r.push( Item::Ident { name : idnt, bitspec : bs } );
-                        current = State::Empty;
-                    }
-                    _ => panic!("Missplaced bispecs"),
-                };
-             },
-            TokenTree::Ident(g) => {
-                match current {
-                    State::Empty => current = State::Ident( g.to_string() ),
-                    _ => panic!("misplaced Ident"),
-                }
-            }
-            TokenTree::Punct(g) => {
-                if g.as_char() != ',' {
-                    panic!("Only ',' allowed as separator of items");
-                }
-            },
-            TokenTree::Literal(g) => {
-                let str = &g.to_string();
-                match T::from_str_radix(str, 2) {
-                    Err( err ) => panic!("Not a binary string : {}", err),
-                    Ok( val ) => r.push( Item::Bits::<T> { len : str.len(), val  } ),
-                }
-            },
-        }
-    }
-
-    let bl = bits_len(&r);
-    assert_eq!(bl, T::i_max_bit() + 1, "Number of the bits in provided ISA and specified command must be equal!");
-
-    let bin = BinaryInstruction { list : r };
-
-    Instruction::<T>::new(bin, text)
-}
-
-#[proc_macro]
-pub fn instruction16(items: TokenStream) -> TokenStream {
-    let r  = parse_token_string::<CompactType>(items);
-    TokenStream::from( quote! { #r } )
-}
-
-#[proc_macro]
-pub fn instruction32(items: TokenStream) -> TokenStream {
-    let r  = parse_token_string::<RV32Type>(items);
-    TokenStream::from( quote! { #r } )
-}
+//! RISC-V instruction tables, decoder and encoder built from them, ELF reader and analysis of code.
+//! Tables are written with instruction macros of asdis-macros, which are reexported here, and primitives of
+//! instruction descriptions shared with the macros are reexported from asdis-primitives
+//!
+//! ```
+//! use asdis::isa::Rv32Instr;
+//! use asdis::primitives::Reg;
+//!
+//! let addi = Rv32Instr::Addi { rd : Reg(10), rs1 : Reg(10), imm : -1 };
+//! assert_eq!(addi.encode(), Some( 0xfff50513 ));
+//!
+//! asdis::instruction_enum32! {
+//!     enum Custom {
+//!         Add = ("add rd, rs1, rs2", 0000000, rs2[4:0], rs1[4:0], 000, rd[4:0], 0110011),
+//!     }
+//! }
+//! assert_eq!(Custom::decode(0x00b50533), Some( Custom::Add { rd : Reg(10), rs1 : Reg(10), rs2 : Reg(11) } ));
+//! ```
+
+#![allow(clippy::module_inception, clippy::upper_case_acronyms)]
+
+extern crate self as asdis;
+
+pub use asdis_macros::{instruction16, instruction32, instruction48, instruction64, instruction_enum16, instruction_enum32};
+
+pub use asdis_primitives as primitives;
+pub mod isa;
+pub mod decoder;
+pub mod encoder;
+pub mod elf;
+pub mod analysis;
@@ -1,101 +1,484 @@
+#![allow(clippy::module_inception, clippy::upper_case_acronyms)]
+
 use structopt::StructOpt;
 use std::fs::File;
 use std::io::BufReader;
 use std::io::prelude::*;
+use std::io::{Error, ErrorKind};
 
-mod primitives;
-mod isa;
-use isa::{ISARV32C, ISARV32IMA};
-mod decoder;
-use decoder::decode;
+use asdis::{primitives, isa, decoder, encoder, elf, analysis};
+use isa::{ISA, IsaSet, March, VendorRegistry, parse_opcodes};
+use decoder::{decode, decode_set, try_decode, raw_insn, instruction_length};
+use encoder::{encode, encode_set};
+use elf::{Elf, STT_FUNC, SHN_UNDEF};
+use analysis::{Tables, Insn, Image, Function, Convention, discover, functions, jump_tables, listing, line, annotations, cfgs, dot, cfg_text, ir_text, decompile, abi_text, Xrefs, json, call_graph_text, xref_text};
+use std::collections::BTreeMap;
 use std::num::ParseIntError;
+use primitives::{CompactType, RV32Type, Long48Type, Long64Type};
 
 enum IData {
     Word(u32),
     Half(u16),
+    Long48(u64),
+    Long64(u64),
+    /// instruction longer than 64 bits, or of reserved length
+    Raw(Vec<u8>),
+    /// bytes at the end of input, too few for instruction of their length
+    Trailing(Vec<u8>),
 }
 
-struct IDataStream {
-    buf_reader : BufReader<File>,
+struct IDataStream<R : BufRead> {
+    buf_reader : R,
 }
 
-impl IDataStream {
-    fn new(buf_reader : BufReader<File>) -> IDataStream {
+impl<R : BufRead> IDataStream<R> {
+    fn new(buf_reader : R) -> IDataStream<R> {
         IDataStream { buf_reader }
     }
+
+    /// read as many bytes as available up to length of buffer, returns number of bytes read
+    fn read_up_to(&mut self, buf : &mut [u8]) -> std::io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            match self.buf_reader.read(&mut buf[n..]) {
+                Ok( 0 ) => break,
+                Ok( k ) => n += k,
+                Err( e ) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err( e ) => return Err( e ),
+            }
+        }
+        Ok( n )
+    }
 }
 
-impl Iterator for IDataStream {
+impl<R : BufRead> Iterator for IDataStream<R> {
     type Item = std::io::Result<IData>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.buf_reader.fill_buf() {
-            Err ( e ) => return Some( Err( e ) ),
+            Err ( e ) => Some( Err( e ) ),
             Ok ( buffer ) => {
                 if buffer.is_empty() {
                     return None;
                 }
 
-                if  self.buf_reader.buffer()[0] & 3 == 3 {   //4 bytes word
-                   let mut buffer = [0; 4];
-                   if self.buf_reader.read_exact(&mut buffer).is_ok() {
-                       return Some(Ok( IData::Word( u32::from_le_bytes(buffer) ) ) );
-                   } else {
-                       return None;
-                   }
-                } else  { // 3 bytes Half-word
-                   let mut buffer = [0; 2];
-                   if self.buf_reader.read_exact(&mut buffer).is_ok() {
-                       return Some(Ok( IData::Half( u16::from_le_bytes(buffer) ) ) );
-                   } else {
-                       return None;
-                   }
+                let mut first = [0; 2];
+                match self.read_up_to(&mut first) {
+                    Err( e ) => return Some( Err( e ) ),
+                    Ok( 2 ) => (),
+                    Ok( n ) => return Some(Ok( IData::Trailing( first[..n].to_vec() ) ) ),
+                }
+                let length = instruction_length( u16::from_le_bytes(first) );
+                //reserved formats of 192 bits and longer are skipped by half-word
+                let mut bytes = vec![0; length.unwrap_or(2)];
+                bytes[..2].copy_from_slice(&first);
+                match self.read_up_to(&mut bytes[2..]) {
+                    Err( e ) => return Some( Err( e ) ),
+                    Ok( n ) if n + 2 < bytes.len() => {
+                        bytes.truncate(n + 2);
+                        return Some(Ok( IData::Trailing( bytes ) ) );
+                    },
+                    Ok( _ ) => (),
+                }
+
+                let mut word = [0; 8];
+                let n = std::cmp::min(bytes.len(), 8);
+                word[..n].copy_from_slice(&bytes[..n]);
+                let word = u64::from_le_bytes(word);
+                match length {
+                    Some( 2 ) => Some(Ok( IData::Half( word as u16 ) ) ),
+                    Some( 4 ) => Some(Ok( IData::Word( word as u32 ) ) ),
+                    Some( 6 ) => Some(Ok( IData::Long48( word ) ) ),
+                    Some( 8 ) => Some(Ok( IData::Long64( word ) ) ),
+                    _ => Some(Ok( IData::Raw( bytes ) ) ),
                 }
             }
         }
     }
 }
 
+/// ISA string used for raw binaries and assembler text if --march is not given
+const DEFAULT_MARCH : &str = "rv32gcbvh_zicbom_zicboz_zicbop_zicond_zawrs_zihintpause_zfa_zk_zks_zcb_svinval";
 
 fn parse_hex(src: &str) -> Result<u32, ParseIntError> {
     u32::from_str_radix(src, 16)
 }
 
+#[derive(StructOpt, Clone)]
+enum Command {
+    /// print control flow graphs of functions
+    Cfg {
+        /// in Graphviz DOT format
+        #[structopt(long)]
+        dot : bool,
+        /// with live registers of blocks, and registers read and written by instructions
+        #[structopt(long)]
+        live : bool,
+    },
+    /// print instructions of functions lifted into IR
+    Lift,
+    /// print functions as pseudo-C
+    Decompile,
+    /// check stack frames and calling convention of functions
+    Abi {
+        /// name of function which sets up gp, tp and stack, besides usual ones like _start
+        #[structopt(long = "startup", number_of_values = 1)]
+        startup : Vec<String>,
+    },
+    /// print cross-references and call graph
+    Xref {
+        /// only references to given symbol or address (hex)
+        #[structopt(long)]
+        to : Option<String>,
+        /// in JSON format
+        #[structopt(long)]
+        json : bool,
+    },
+}
+
 #[derive(StructOpt, Default, Clone)]
 struct Cli {
     #[structopt(default_value = "prog.bin")]
     file : String,
     #[structopt(short, parse(try_from_str = parse_hex), default_value="0")]
     start_addr : u32,
+    /// treat file as assembler text and encode it
+    #[structopt(short, long)]
+    asm : bool,
+    /// ISA string, like rv64imac_zba_zbb, selects decoded extensions. Taken from ELF file if not given
+    #[structopt(long)]
+    march : Option<String>,
+    /// add vendor extension to standard ones, like xthead
+    #[structopt(short = "x", long = "vendor", number_of_values = 1)]
+    vendor : Vec<String>,
+    /// add instructions described in riscv-opcodes format file
+    #[structopt(long = "opcodes", number_of_values = 1)]
+    opcodes : Vec<String>,
+    /// decode all bytes one after another, instead of following control flow from entry points
+    #[structopt(long)]
+    linear : bool,
+    /// address of additional entry point (hex), start address is an entry point of raw binary
+    #[structopt(short = "e", long = "entry", parse(try_from_str = parse_hex), number_of_values = 1)]
+    entries : Vec<u32>,
+    /// print table of found functions: start, end, stack frame size and name
+    #[structopt(long)]
+    functions : bool,
+    #[structopt(subcommand)]
+    command : Option<Command>,
 }
 
-fn main() -> std::io::Result<()> {
-    let args = Cli::from_args();
-
-    let isa16 = ISARV32C::new();
-    let isa32 = ISARV32IMA::new();
-
+/// encode every line of text file, '#' starts comment
+fn assemble(args : &Cli, isa16 : &ISA<CompactType>, isa32 : &IsaSet<RV32Type>) -> std::io::Result<()> {
     let file = File::open(&args.file)?;
     let buf_reader = BufReader::new(file);
-    let idata_stream = IDataStream::new(buf_reader);
-
-    println!("Opened file: {}", &args.file);
 
     let mut start_addr : u32 = args.start_addr;
+    for line in buf_reader.lines() {
+        let line = line?;
+        let text = line.split('#').next().unwrap_or("").trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Some( v ) = encode_set(text, isa32) {
+            println!("{:#010X} {:40} {:#010X}", start_addr, text, v);
+            start_addr += 4;
+        } else if let Some( v ) = encode(text, isa16) {
+            println!("{:#010X} {:40}     {:#06X}", start_addr, text, v);
+            start_addr += 2;
+        } else {
+            println!("{:#010X} {:40} Can't encode!", start_addr, text);
+        }
+    }
+
+    Ok(())
+}
+
+/// decode stream of instructions, addresses start from given one. Labels are shown before their addresses
+fn disassemble<R : BufRead>(idata_stream : IDataStream<R>, start_addr : u64, tables : &Tables, labels : &BTreeMap<u64, String>)
+    -> std::io::Result<()> {
+    let mut start_addr = start_addr;
     for i in idata_stream {
         let i = i?;
-        match i {
-            IData::Word( v ) =>  {
-                let dscr = decode(v, &isa32); //translate32(v, &isa);
-                println!("{:#010X} {:40} {:#010X?}  ", start_addr, dscr, v);
-                start_addr += 4;
-           },
-           IData::Half ( v ) => {
-               let dscr = decode(v, &isa16);
-               println!("{:#010X} {:40}     {:#06X}", start_addr, dscr, v);
-               start_addr += 2;
-           },
-       }
+        if let Some( name ) = labels.get(&start_addr) {
+            println!("{:#010X} <{}>:", start_addr, name);
+        }
+        let (dscr, len, word) = match i {
+            IData::Word( v ) => (decode_set(v, tables.set32), 4, v as u64),
+            IData::Half( v ) => (decode(v, tables.isa16), 2, v as u64),
+            IData::Long48( v ) => (try_decode(v, tables.isa48).unwrap_or_else(|| raw_insn(&v.to_le_bytes()[..6])), 6, v),
+            IData::Long64( v ) => (try_decode(v, tables.isa64).unwrap_or_else(|| raw_insn(&v.to_le_bytes())), 8, v),
+            IData::Raw( bytes ) => {
+                //encoding is shown by .insn itself
+                println!("{}", line(start_addr, &raw_insn(&bytes), 0, 0));
+                start_addr += bytes.len() as u64;
+                continue;
+            },
+            IData::Trailing( bytes ) => {
+                let list : Vec<String> = bytes.iter().map(|b| format!("{:#04x}", b)).collect();
+                println!("{}", line(start_addr, &format!(".byte {}", list.join(", ")), 0, 0));
+                start_addr += bytes.len() as u64;
+                continue;
+            },
+        };
+        println!("{}", line(start_addr, &dscr, len, word));
+        start_addr += len as u64;
+    }
+
+    Ok(())
+}
+
+/// labels of functions, and of jump tables and their targets, given labels are kept
+fn found_labels(insns : &BTreeMap<u64, Insn>, found : &[Function], labels : &BTreeMap<u64, String>,
+                tables : &Tables, image : &Image) -> BTreeMap<u64, String> {
+    let mut labels = labels.clone();
+    for f in found {
+        labels.entry(f.start).or_insert_with(|| f.name.clone());
+    }
+    for t in jump_tables(insns, found, image, tables.xlen) {
+        labels.entry(t.table).or_insert_with(|| format!("jumptable_{:08x}", t.table));
+        for a in t.targets {
+            labels.entry(a).or_insert_with(|| format!("loc_{:08x}", a));
+        }
+    }
+    labels
+}
+
+/// decode all instructions of code, labels of functions and jump tables are found by traversal from entries
+fn disassemble_linear(code : &[u8], base : u64, entries : &[u64], labels : &BTreeMap<u64, String>, tables : &Tables,
+                      image : &Image) -> std::io::Result<()> {
+    let insns = discover(code, base, entries, tables, image);
+    let found = functions(&insns, entries, labels, tables.xlen);
+    let labels = found_labels(&insns, &found, labels, tables, image);
+    disassemble(IDataStream::new(code), base, tables, &labels)
+}
+
+/// decode instructions reachable from entry points, found prologues and jump tables, other bytes are shown as data.
+/// Functions without symbols get synthetic sub_<address> labels, addresses built in registers are shown as comments
+#[allow(clippy::too_many_arguments)]
+fn disassemble_recursive(code : &[u8], base : u64, entries : &[u64], labels : &BTreeMap<u64, String>, tables : &Tables,
+                         image : &Image, args : &Cli, conv : &Convention) {
+    let (insns, found, labels) = section_functions(code, base, entries, labels, tables, image);
+    match &args.command {
+        Some( Command::Cfg { dot : graphviz, live } ) => {
+            let graphs = cfgs(&insns, &found);
+            let lines = match graphviz {
+                true => dot(&args.file, &graphs, &labels),
+                false => cfg_text(&graphs, &labels, *live),
+            };
+            for l in lines {
+                println!("{}", l);
+            }
+            return;
+        },
+        Some( Command::Lift ) => {
+            for l in ir_text(&cfgs(&insns, &found), tables.xlen, &labels) {
+                println!("{}", l);
+            }
+            return;
+        },
+        Some( Command::Decompile ) => {
+            for l in decompile(&cfgs(&insns, &found), tables.xlen, &labels) {
+                println!("{}", l);
+            }
+            return;
+        },
+        Some( Command::Abi { .. } ) => {
+            for l in abi_text(&cfgs(&insns, &found), tables.xlen, &labels, conv) {
+                println!("{}", l);
+            }
+            return;
+        },
+        Some( Command::Xref { .. } ) => unreachable!("references are printed for all sections at once"),
+        None => {},
+    }
+    if args.functions {
+        println!("{:10} {:10} {:>6} name", "start", "end", "frame");
+        for f in &found {
+            let frame = f.frame.map_or(String::from("-"), |v| v.to_string());
+            println!("{:#010X} {:#010X} {:>6} {}", f.start, f.end, frame, f.name);
+        }
+        println!();
+    }
+    let comments = annotations(&cfgs(&insns, &found), tables.xlen, &labels);
+    for l in listing(code, base, &insns, &labels, &comments) {
+        println!("{}", l);
+    }
+}
+
+/// instructions and functions of code, with labels of functions and jump tables
+fn section_functions(code : &[u8], base : u64, entries : &[u64], labels : &BTreeMap<u64, String>, tables : &Tables,
+                     image : &Image) -> (BTreeMap<u64, Insn>, Vec<Function>, BTreeMap<u64, String>) {
+    let insns = discover(code, base, entries, tables, image);
+    let found = functions(&insns, entries, labels, tables.xlen);
+    let labels = found_labels(&insns, &found, labels, tables, image);
+    (insns, found, labels)
+}
+
+/// print references of all sections, or only references to given symbol or address
+fn print_xrefs(mut xrefs : Xrefs, labels : &BTreeMap<u64, String>, to : &Option<String>, as_json : bool)
+    -> std::io::Result<()> {
+    if let Some( to ) = to {
+        let addr = labels.iter().find(|(_, name)| *name == to).map(|(a, _)| *a)
+            .or_else(|| u64::from_str_radix(&to.trim_start_matches("0x").replace('_', ""), 16).ok())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown symbol {}", to)))?;
+        xrefs.list = xrefs.to(addr).cloned().collect();
+    }
+    match (as_json, to) {
+        (true, _) => println!("{}", json(&xrefs, labels)),
+        (false, Some( _ )) => xref_text(xrefs.list.iter(), labels).iter().for_each(|l| println!("{}", l)),
+        (false, None) => {
+            println!("Call graph:");
+            call_graph_text(&xrefs, labels).iter().for_each(|l| println!("{}", l));
+            println!();
+            println!("References:");
+            xref_text(xrefs.list.iter(), labels).iter().for_each(|l| println!("{}", l));
+        },
+    }
+    Ok(())
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Cli::from_args();
+
+    let data = match args.asm {
+        true => Vec::new(),
+        false => std::fs::read(&args.file)?,
+    };
+    let elf = match Elf::is_elf(&data) {
+        true => Some( Elf::parse(&data).map_err(|e| Error::new(ErrorKind::InvalidData, e))? ),
+        false => None,
+    };
+    let march = match (&args.march, &elf) {
+        (Some( march ), _) => march.clone(),
+        (None, Some( elf )) => elf.march().map_err(|e| Error::new(ErrorKind::InvalidData, e))?,
+        (None, None) => String::from(DEFAULT_MARCH),
+    };
+
+    let vendors = VendorRegistry::default();
+    let march = March::parse_with(&march, &vendors).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    let isa16 = march.isa16();
+    let mut set32 = march.isa32_with(&vendors).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    for name in &args.vendor {
+        let fragment = vendors.fragment(name, march.xlen).ok_or_else(||
+            Error::new(ErrorKind::InvalidInput, format!("Unknown vendor extension {}", name)))?;
+        if let Err( conflicts ) = set32.add(name, fragment) {
+            let list : Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+            return Err( Error::new(ErrorKind::InvalidInput, list.join("\n")) );
+        }
+    }
+    for name in &args.opcodes {
+        let fragment = parse_opcodes( &std::fs::read_to_string(name)? )
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", name, e)))?;
+        if let Err( conflicts ) = set32.add(name, fragment) {
+            let list : Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+            return Err( Error::new(ErrorKind::InvalidInput, list.join("\n")) );
+        }
+    }
+
+    //no standard instructions are longer than 32 bits, tables are for custom extensions
+    let isa48 = ISA::<Long48Type>::default();
+    let isa64 = ISA::<Long64Type>::default();
+
+    if args.asm {
+        return assemble(&args, &isa16, &set32);
+    }
+
+    //graphs and functions are built from traversal, and DOT or JSON output has no other text
+    if args.linear && args.command.is_some() {
+        return Err( Error::new(ErrorKind::InvalidInput, "--linear can't be used with subcommands") );
+    }
+    if args.linear && args.functions {
+        return Err( Error::new(ErrorKind::InvalidInput, "--functions can't be used with --linear") );
+    }
+    let linear = args.linear;
+    let quiet = matches!(args.command, Some( Command::Cfg { dot : true, .. } ) | Some( Command::Xref { json : true, .. } ));
+    if !quiet {
+        println!("Opened file: {}", &args.file);
+    }
+
+    let tables = Tables { isa16 : &isa16, set32 : &set32, isa48 : &isa48, isa64 : &isa64, xlen : march.xlen };
+
+    //stack alignment is given by ELF attributes, or by ABI of ISA
+    let mut conv = Convention::new(&march);
+    if let Some( align ) = elf.as_ref().map(|e| e.attributes()).transpose().map_err(|e| Error::new(ErrorKind::InvalidData, e))?
+        .flatten().and_then(|a| a.stack_align()) {
+        conv.stack_align = align as i64;
+    }
+    if let Some( Command::Abi { startup } ) = &args.command {
+        conv.startup.extend( startup.iter().cloned() );
+    }
+    let mut entries : Vec<u64> = args.entries.iter().map(|e| *e as u64).collect();
+
+    let elf = match elf {
+        Some( elf ) => elf,
+        None => {
+            let base = args.start_addr as u64;
+            entries.insert(0, base);
+            let image = Image::new(base, &data);
+            if let Some( Command::Xref { to, json } ) = &args.command {
+                let (insns, found, labels) = section_functions(&data, base, &entries, &BTreeMap::new(), &tables, &image);
+                return print_xrefs(Xrefs::build(&cfgs(&insns, &found), tables.xlen), &labels, to, *json);
+            }
+            match linear {
+                true => disassemble_linear(&data, base, &entries, &BTreeMap::new(), &tables, &image)?,
+                false => disassemble_recursive(&data, base, &entries, &BTreeMap::new(), &tables, &image, &args, &conv),
+            }
+            return Ok(());
+        },
+    };
+
+    if !quiet {
+        println!("ELF flags: {}", elf.describe_flags());
+        if let Some( attributes ) = elf.attributes().map_err(|e| Error::new(ErrorKind::InvalidData, e))? {
+            for a in &attributes.list {
+                println!("{}", a);
+            }
+        }
+    }
+
+    //defined functions and $x mapping symbols start code, mapping, file and undefined symbols are not labels
+    let symbols = elf.symbols().map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    entries.push( elf.entry );
+    let mut labels = BTreeMap::new();
+    for s in &symbols {
+        if s.shndx != SHN_UNDEF && (s.kind == STT_FUNC || s.name == "$x" || s.name.starts_with("$x.")) {
+            entries.push( s.value );
+        }
+        if s.is_label() {
+            labels.entry(s.value).or_insert_with(|| s.name.clone());
+        }
+    }
+
+    //jump tables are read from all loaded sections
+    let mut image = Image::default();
+    for section in elf.loaded() {
+        image.add(section.addr, elf.section_data(section).map_err(|e| Error::new(ErrorKind::InvalidData, e))?);
+    }
+
+    //references between sections are found, and one document is printed for all of them
+    if let Some( Command::Xref { to, json } ) = &args.command {
+        let mut xrefs = Xrefs::default();
+        let mut names = labels.clone();
+        for section in elf.executable() {
+            let bytes = elf.section_data(section).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            let (insns, found, l) = section_functions(bytes, section.addr, &entries, &labels, &tables, &image);
+            xrefs.list.extend( Xrefs::build(&cfgs(&insns, &found), tables.xlen).list );
+            names.extend(l);
+        }
+        xrefs.list.sort();
+        return print_xrefs(xrefs, &names, to, *json);
+    }
+
+    for section in elf.executable() {
+        if !quiet {
+            println!();
+            println!("Section {}:", section.name);
+        }
+        let bytes = elf.section_data(section).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        match linear {
+            true => disassemble_linear(bytes, section.addr, &entries, &labels, &tables, &image)?,
+            false => disassemble_recursive(bytes, section.addr, &entries, &labels, &tables, &image, &args, &conv),
+        }
     }
 
     Ok(())
@@ -1,3 +0,0 @@
-mod instruction;
-
-pub use instruction::*;